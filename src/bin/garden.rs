@@ -32,6 +32,10 @@ fn cmd_main() -> Result<()> {
 
     let app = model::ApplicationContext::from_options(&options)?;
     match options.command {
+        cli::Command::Branch(branch) => cmds::branch::main(&app, &branch),
+        cli::Command::Bundle(bundle) => cmds::bundle::main(&app, &bundle),
+        cli::Command::Cache(cache) => cmds::cache::main(&app, &cache),
+        cli::Command::Checkout(checkout) => cmds::checkout::main(&app, &checkout),
         cli::Command::Cmd(mut cmd) => cmds::cmd::main_cmd(&app, &mut cmd),
         cli::Command::Completion(_) => Ok(()), // Handled above
         cli::Command::Custom(args) => cmds::cmd::main_custom(&app, &args),
@@ -40,9 +44,17 @@ fn cmd_main() -> Result<()> {
         cli::Command::Grow(grow) => cmds::grow::main(&app, &grow),
         cli::Command::Init(_) => Ok(()), // Handled above
         cli::Command::List(mut list) => cmds::list::main(&app, &mut list),
+        cli::Command::Lock(mut lock) => cmds::lock::main(&app, &mut lock),
+        cli::Command::Mv(mv) => cmds::mv::main(&app, &mv),
         cli::Command::Plant(plant) => cmds::plant::main(&app, &plant),
         cli::Command::Prune(mut prune) => cmds::prune::main(&app, &mut prune),
+        cli::Command::Rm(rm) => cmds::rm::main(&app, &rm),
         cli::Command::Shell(shell) => cmds::shell::main(&app, &shell),
+        cli::Command::Status(mut status) => cmds::status::main(&app, &mut status),
+        cli::Command::Trash(trash) => cmds::trash::main(&app, &trash),
+        cli::Command::Update(update) => cmds::update::main(&app, &update),
+        cli::Command::Validate(validate) => cmds::validate::main(&app, &validate),
+        cli::Command::Worktree(worktree) => cmds::worktree::main(&app, &worktree),
     }
 }
 
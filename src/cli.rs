@@ -91,6 +91,14 @@ fn debug_level(debug: &[String], name: &str) -> u8 {
 
 #[derive(Clone, Debug, Subcommand)]
 pub enum Command {
+    /// Create, switch, list and delete topic branches across trees
+    Branch(cmds::branch::BranchOptions),
+    /// Create Git bundles for transferring trees to offline machines
+    Bundle(cmds::bundle::BundleOptions),
+    /// Manage the mirror repository cache
+    Cache(cmds::cache::CacheOptions),
+    /// Checkout the commits recorded in "garden.lock" for trees
+    Checkout(cmds::checkout::CheckoutOptions),
     /// Run custom commands over gardens
     Cmd(cmds::cmd::CmdOptions),
     /// Generate shell completions
@@ -109,13 +117,30 @@ pub enum Command {
     /// List available gardens, groups, trees and commands
     #[command(name = "ls")]
     List(cmds::list::ListOptions),
+    /// Record the current commit for trees in "garden.lock"
+    Lock(cmds::lock::LockOptions),
+    /// Move a tree to a new location and update the garden file
+    Mv(cmds::mv::MvOptions),
     /// Add pre-existing worktrees to a garden configuration file
     Plant(cmds::plant::PlantOptions),
     /// Remove unreferenced Git repositories
     Prune(cmds::prune::PruneOptions),
+    /// Remove trees from the garden file
+    Rm(cmds::rm::RmOptions),
     /// Open a shell in a garden environment
     #[command(alias = "sh")]
     Shell(cmds::shell::ShellOptions),
+    /// Report the Git status of trees
+    Status(cmds::status::StatusOptions),
+    /// Manage repositories that were moved into the trash by "garden prune --trash"
+    Trash(cmds::trash::TrashOptions),
+    /// Fetch remotes and fast-forward trees to their upstream branches
+    #[command(alias = "sync")]
+    Update(cmds::update::UpdateOptions),
+    /// Check garden files for unknown keys, dangling references and undefined variables
+    Validate(cmds::validate::ValidateOptions),
+    /// Add, remove and list worktree trees
+    Worktree(cmds::worktree::WorktreeOptions),
 }
 
 impl std::default::Default for Command {
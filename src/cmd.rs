@@ -1,3 +1,5 @@
+use std::io::BufRead;
+
 use crate::{constants, display, errors, eval, model, syntax};
 
 /// Convert an exit status to Result<(), GardenError>.
@@ -24,6 +26,59 @@ fn status_code(result: subprocess::Result<subprocess::ExitStatus>) -> i32 {
     }
 }
 
+/// Run a command and prefix each line of its stdout and stderr output.
+/// Lines are written individually so that output from parallel commands does not interleave.
+pub(crate) fn status_with_prefix(exec: subprocess::Exec, prefix: &str) -> i32 {
+    let exec = exec
+        .stdout(subprocess::Redirection::Pipe)
+        .stderr(subprocess::Redirection::Pipe);
+    let Ok(mut process) = exec.popen() else {
+        return errors::EX_ERROR;
+    };
+    let stdout = process.stdout.take();
+    let stderr = process.stderr.take();
+    std::thread::scope(|scope| {
+        if let Some(stdout) = stdout {
+            scope.spawn(move || {
+                for line in std::io::BufReader::new(stdout).split(b'\n') {
+                    let Ok(line) = line else {
+                        break;
+                    };
+                    println!("{} {}", prefix, String::from_utf8_lossy(&line));
+                }
+            });
+        }
+        if let Some(stderr) = stderr {
+            scope.spawn(move || {
+                for line in std::io::BufReader::new(stderr).split(b'\n') {
+                    let Ok(line) = line else {
+                        break;
+                    };
+                    eprintln!("{} {}", prefix, String::from_utf8_lossy(&line));
+                }
+            });
+        }
+    });
+
+    status_code(process.wait())
+}
+
+/// Run a command and capture its stdout and stderr output.
+/// Returns the exit status along with the captured (stdout, stderr) output.
+pub(crate) fn capture_output(exec: subprocess::Exec) -> (i32, Vec<u8>, Vec<u8>) {
+    let exec = exec
+        .stdout(subprocess::Redirection::Pipe)
+        .stderr(subprocess::Redirection::Pipe);
+    match exec.capture() {
+        Ok(capture) => (
+            exit_status(capture.exit_status),
+            capture.stdout,
+            capture.stderr,
+        ),
+        Err(_) => (errors::EX_ERROR, Vec::new(), Vec::new()),
+    }
+}
+
 /// Take a subprocess capture and return a string without trailing whitespace.
 fn stdout(capture: &subprocess::CaptureData) -> String {
     capture.stdout_str().trim_end().to_string()
@@ -117,7 +172,6 @@ where
 /// - quiet: Suppress messages when set true.
 /// - verbose: increase verbosity of messages.
 /// - command: String vector of the command to run.
-
 pub(crate) fn exec_in_context<S>(
     app_context: &model::ApplicationContext,
     config: &model::Configuration,
@@ -139,7 +193,7 @@ where
             path = tree.path_as_ref()?;
 
             // Sparse gardens/missing trees are okay -> skip these entries.
-            if !display::print_tree(tree, config.tree_branches, verbose, quiet, false) {
+            if !display::print_tree(tree, config.tree_branches, false, verbose, quiet, false) {
                 return Ok(());
             }
         } else {
@@ -149,7 +203,7 @@ where
         path = tree.path_as_ref()?;
 
         // Sparse gardens/missing trees are okay -> skip these entries.
-        if !display::print_tree(tree, config.tree_branches, verbose, quiet, false) {
+        if !display::print_tree(tree, config.tree_branches, false, verbose, quiet, false) {
             return Ok(());
         }
     } else {
@@ -179,7 +233,6 @@ where
 /// The command might be a path that only exists inside the resolved
 /// environment.  Resolve the path by looking for the presence of PATH
 /// and updating the command when it exists.
-
 fn resolve_command<S>(command: &[S], env: &[(String, String)]) -> Vec<String>
 where
     S: AsRef<std::ffi::OsStr>,
@@ -316,6 +369,15 @@ pub(crate) fn shell_quote(arg: &str) -> String {
         .unwrap_or_else(|_| arg.to_string())
 }
 
+/// Shell quote a list of command arguments into a single string for display purposes.
+pub(crate) fn shell_quote_command(command: &[&str]) -> String {
+    command
+        .iter()
+        .map(|arg| shell_quote(arg))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
 /// Get the default number of jobs to run in parallel
 pub(crate) fn default_num_jobs() -> usize {
     match std::thread::available_parallelism() {
@@ -0,0 +1,426 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use yansi::Paint;
+
+use crate::{cmd, display, errors, git, model, model::IndexMap, query};
+
+/// Create, switch, list and delete topic branches across trees
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct BranchOptions {
+    #[command(subcommand)]
+    command: BranchCommand,
+}
+
+/// Branch subcommands
+#[derive(Clone, Debug, Subcommand)]
+pub enum BranchCommand {
+    /// Delete a branch from trees
+    Delete(BranchDeleteOptions),
+    /// List the topic branches in trees
+    List(BranchListOptions),
+    /// Create a branch from each tree's configured branch and switch to it
+    Start(BranchStartOptions),
+    /// Switch trees to an existing branch
+    Switch(BranchSwitchOptions),
+}
+
+/// Delete a branch from trees
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct BranchDeleteOptions {
+    /// Delete the branch even when it has not been merged
+    #[arg(long, short)]
+    force: bool,
+    /// Name of the branch to delete
+    name: String,
+    /// Tree query for the gardens, groups or trees to modify
+    #[arg(required = true)]
+    queries: Vec<String>,
+}
+
+/// List the topic branches in trees
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct BranchListOptions {
+    /// Tree query for the gardens, groups or trees to display
+    queries: Vec<String>,
+}
+
+/// Create a branch from each tree's configured branch and switch to it
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct BranchStartOptions {
+    /// Switch trees that have uncommitted changes
+    #[arg(long, short)]
+    force: bool,
+    /// Name of the branch to create
+    name: String,
+    /// Tree query for the gardens, groups or trees to modify
+    #[arg(required = true)]
+    queries: Vec<String>,
+}
+
+/// Switch trees to an existing branch
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct BranchSwitchOptions {
+    /// Switch trees that have uncommitted changes
+    #[arg(long, short)]
+    force: bool,
+    /// Name of the branch to switch to
+    name: String,
+    /// Tree query for the gardens, groups or trees to modify
+    #[arg(required = true)]
+    queries: Vec<String>,
+}
+
+/// A checkout whose branches are managed by "garden branch".
+struct Checkout {
+    name: String,
+    path: std::path::PathBuf,
+    /// The configured "branch" for the tree.
+    branch: String,
+    /// The upstream branch for the configured "branch".
+    upstream: Option<String>,
+    default_remote: String,
+}
+
+impl Checkout {
+    /// Return the revision that new branches are created from.
+    /// The upstream branch is preferred over the local branch.
+    fn base(&self) -> Option<String> {
+        if let Some(upstream) = &self.upstream {
+            if git::rev_exists(&self.path, upstream) {
+                return Some(upstream.to_string());
+            }
+        }
+        if !self.branch.is_empty() && git::rev_exists(&self.path, &self.branch) {
+            return Some(self.branch.to_string());
+        }
+
+        None
+    }
+}
+
+/// The outcome of running a branch command on a single tree.
+enum BranchStatus {
+    /// The branch was created and checked out.
+    Created(String),
+    /// The branch was checked out.
+    Switched,
+    /// The branch is already checked out.
+    Current,
+    /// The branch was deleted.
+    Deleted,
+    /// Skipped: the tree has uncommitted changes.
+    Dirty,
+    /// Skipped: the branch does not exist.
+    NotFound,
+    /// Skipped: the tree has not been grown.
+    Missing,
+    /// A Git command failed with the specified exit status.
+    Failed(i32, String),
+}
+
+impl display::SummaryStatus for BranchStatus {
+    fn label(&self) -> &'static str {
+        match self {
+            BranchStatus::Created(_) => "created",
+            BranchStatus::Switched => "switched",
+            BranchStatus::Current => "current",
+            BranchStatus::Deleted => "deleted",
+            BranchStatus::Missing => "missing",
+            BranchStatus::Failed(_, _) => "failed",
+            BranchStatus::Dirty | BranchStatus::NotFound => "skipped",
+        }
+    }
+
+    fn details(&self) -> String {
+        match self {
+            BranchStatus::Created(base) => format!("from {base}"),
+            BranchStatus::Dirty => string!("uncommitted changes, use --force to switch"),
+            BranchStatus::NotFound => string!("branch not found"),
+            BranchStatus::Failed(_, message) => message.to_string(),
+            BranchStatus::Switched
+            | BranchStatus::Current
+            | BranchStatus::Deleted
+            | BranchStatus::Missing => String::new(),
+        }
+    }
+
+    fn paint_label(&self, label: &str) -> String {
+        match self {
+            BranchStatus::Created(_) | BranchStatus::Switched | BranchStatus::Deleted => {
+                label.green().bold().to_string()
+            }
+            BranchStatus::Current => label.green().to_string(),
+            BranchStatus::Missing => label.red().dim().to_string(),
+            BranchStatus::Failed(_, _) => label.red().bold().to_string(),
+            _ => label.yellow().to_string(),
+        }
+    }
+}
+
+/// Main entry point for the "garden branch" command
+pub fn main(app_context: &model::ApplicationContext, options: &BranchOptions) -> Result<()> {
+    let quiet = app_context.options.quiet;
+    let verbose = app_context.options.verbose;
+    let results: Vec<(String, BranchStatus)> = match &options.command {
+        BranchCommand::Delete(delete) => checkouts(app_context, &delete.queries)
+            .iter()
+            .map(|checkout| {
+                let status = delete_branch(checkout, &delete.name, delete.force, verbose);
+                (checkout.name.to_string(), status)
+            })
+            .collect(),
+        BranchCommand::List(list) => {
+            let mut queries = list.queries.clone();
+            if queries.is_empty() {
+                queries.push(string!("@*"));
+            }
+            if !quiet {
+                list_branches(&checkouts(app_context, &queries));
+            }
+            return Ok(());
+        }
+        BranchCommand::Start(start) => checkouts(app_context, &start.queries)
+            .iter()
+            .map(|checkout| {
+                let status = start_branch(checkout, &start.name, start.force, verbose);
+                (checkout.name.to_string(), status)
+            })
+            .collect(),
+        BranchCommand::Switch(switch) => checkouts(app_context, &switch.queries)
+            .iter()
+            .map(|checkout| {
+                let status = switch_branch(checkout, &switch.name, switch.force, verbose);
+                (checkout.name.to_string(), status)
+            })
+            .collect(),
+    };
+
+    if !quiet {
+        display::print_summary(&results);
+    }
+
+    // Return the last non-zero exit status.
+    let mut exit_status = errors::EX_OK;
+    for (_, status) in &results {
+        if let BranchStatus::Failed(status, _) = status {
+            exit_status = *status;
+        }
+    }
+
+    cmd::result_from_exit_status(exit_status).map_err(|err| err.into())
+}
+
+/// Evaluate the tree queries and gather the checkouts that can have branches.
+fn checkouts(app_context: &model::ApplicationContext, queries: &[String]) -> Vec<Checkout> {
+    let config = app_context.get_root_config();
+    let mut checkouts: IndexMap<std::path::PathBuf, Checkout> = IndexMap::new();
+    for query in queries {
+        let contexts = query::resolve_trees(app_context, config, None, query);
+        for context in &contexts {
+            let eval_context = model::EvalContext::from_app_context(app_context, context);
+            let tree_config = eval_context.graft_config.unwrap_or(config);
+            let Some(tree) = tree_config.trees.get(&context.tree) else {
+                continue;
+            };
+            if tree.is_symlink || tree.is_bare_repository {
+                continue;
+            }
+            let Some(path) = tree.pathbuf() else {
+                continue;
+            };
+            if checkouts.contains_key(&path) {
+                continue;
+            }
+            let branch = tree.eval_branch(&eval_context);
+            let upstream = match tree.branches.get(&branch) {
+                Some(expr) => Some(eval_context.tree_variable(expr)),
+                None if !branch.is_empty() => Some(format!("{}/{branch}", tree.default_remote)),
+                None => None,
+            }
+            .filter(|upstream| !upstream.is_empty());
+            let checkout = Checkout {
+                name: tree.get_name().to_string(),
+                path: path.clone(),
+                branch,
+                upstream,
+                default_remote: tree.default_remote.to_string(),
+            };
+            checkouts.insert(path, checkout);
+        }
+    }
+
+    checkouts.into_values().collect()
+}
+
+/// Create a branch from the checkout's configured branch and switch to it.
+/// The new branch tracks a branch of the same name on the default remote.
+fn start_branch(checkout: &Checkout, name: &str, force: bool, verbose: u8) -> BranchStatus {
+    if !checkout.path.exists() {
+        return BranchStatus::Missing;
+    }
+    // Switch to the branch when it was previously started.
+    if git::branches(&checkout.path)
+        .iter()
+        .any(|branch| branch == name)
+    {
+        return switch_branch(checkout, name, force, verbose);
+    }
+    if let Some(status) = check_dirty(checkout, force) {
+        return status;
+    }
+    let base = checkout.base().unwrap_or_else(|| string!("HEAD"));
+    let command = ["git", "switch", "--quiet", "--no-track", "-c", name, &base];
+    if let Some(status) = run_command(&command, &checkout.path, verbose) {
+        return status;
+    }
+    // git config branch.<name>.remote <default-remote>
+    // git config branch.<name>.merge refs/heads/<name>
+    let remote_key = format!("branch.{name}.remote");
+    let merge_key = format!("branch.{name}.merge");
+    let merge_ref = format!("refs/heads/{name}");
+    for command in [
+        ["git", "config", &remote_key, &checkout.default_remote],
+        ["git", "config", &merge_key, &merge_ref],
+    ] {
+        if let Some(status) = run_command(&command, &checkout.path, verbose) {
+            return status;
+        }
+    }
+
+    BranchStatus::Created(base)
+}
+
+/// Switch a checkout to an existing branch.
+fn switch_branch(checkout: &Checkout, name: &str, force: bool, verbose: u8) -> BranchStatus {
+    if !checkout.path.exists() {
+        return BranchStatus::Missing;
+    }
+    if git::symbolic_branch(&checkout.path).as_deref() == Some(name) {
+        return BranchStatus::Current;
+    }
+    if !git::branches(&checkout.path)
+        .iter()
+        .any(|branch| branch == name)
+    {
+        return BranchStatus::NotFound;
+    }
+    if let Some(status) = check_dirty(checkout, force) {
+        return status;
+    }
+    let command = ["git", "switch", "--quiet", name];
+    if let Some(status) = run_command(&command, &checkout.path, verbose) {
+        return status;
+    }
+
+    BranchStatus::Switched
+}
+
+/// Delete a branch from a checkout.
+fn delete_branch(checkout: &Checkout, name: &str, force: bool, verbose: u8) -> BranchStatus {
+    if !checkout.path.exists() {
+        return BranchStatus::Missing;
+    }
+    if !git::branches(&checkout.path)
+        .iter()
+        .any(|branch| branch == name)
+    {
+        return BranchStatus::NotFound;
+    }
+    let delete_opt = if force { "-D" } else { "-d" };
+    let command = ["git", "branch", "--quiet", delete_opt, name];
+    if let Some(status) = run_command(&command, &checkout.path, verbose) {
+        return status;
+    }
+
+    BranchStatus::Deleted
+}
+
+/// Return BranchStatus::Dirty when a checkout has uncommitted changes and force is not set.
+fn check_dirty(checkout: &Checkout, force: bool) -> Option<BranchStatus> {
+    if force {
+        return None;
+    }
+    match git::status(&checkout.path) {
+        Ok(status) if status.is_dirty() => Some(BranchStatus::Dirty),
+        Ok(_) => None,
+        Err(errors::CommandError::ExitStatus { status, .. }) => {
+            Some(BranchStatus::Failed(status, string!("git status failed")))
+        }
+    }
+}
+
+/// Run a command and return BranchStatus::Failed when the command fails.
+fn run_command(command: &[&str], path: &std::path::Path, verbose: u8) -> Option<BranchStatus> {
+    if verbose > 1 {
+        display::print_quoted_command(command);
+    }
+    let exit_status = cmd::run_command(command, path);
+    if exit_status != errors::EX_OK {
+        return Some(BranchStatus::Failed(
+            exit_status,
+            format!(
+                "{} returned exit status {exit_status}",
+                command[..2].join(" ")
+            ),
+        ));
+    }
+
+    None
+}
+
+/// The (tree, commits ahead, is current) details for a branch in a tree.
+type BranchEntry<'a> = (&'a str, Option<usize>, bool);
+
+/// Display the topic branches in each tree along with the number of commits
+/// that each branch is ahead of the tree's configured branch.
+fn list_branches(checkouts: &[Checkout]) {
+    let mut branches: IndexMap<String, Vec<BranchEntry>> = IndexMap::new();
+    for checkout in checkouts {
+        if !checkout.path.exists() {
+            continue;
+        }
+        let base = checkout.base();
+        let current = git::symbolic_branch(&checkout.path);
+        for branch in git::branches(&checkout.path) {
+            if branch == checkout.branch {
+                continue;
+            }
+            let ahead = base.as_ref().and_then(|base| {
+                let range = format!("{base}..{branch}");
+                git::count_commits(&checkout.path, &range)
+            });
+            let is_current = current.as_deref() == Some(branch.as_str());
+            branches
+                .entry(branch)
+                .or_default()
+                .push((&checkout.name, ahead, is_current));
+        }
+    }
+
+    let name_width = branches
+        .values()
+        .flatten()
+        .map(|(name, _, _)| name.len())
+        .max()
+        .unwrap_or_default();
+    for (branch, trees) in &branches {
+        println!("{}", branch.green().bold());
+        for (name, ahead, is_current) in trees {
+            let ahead = match ahead {
+                Some(ahead) => format!("+{ahead}"),
+                None => string!("?"),
+            };
+            let name = format!("{name:<name_width$}");
+            let mut row = format!("  {} {} {ahead}", "#".cyan(), name.blue().bold());
+            if *is_current {
+                row.push_str(&format!(" {}", "(current)".dim()));
+            }
+            println!("{row}");
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand, ValueHint};
+use yaml_rust::{yaml, Yaml};
+use yansi::Paint;
+
+use crate::{cmd, config, display, errors, git, model, model::IndexMap, path, query};
+
+/// Create Git bundles for transferring trees to offline machines
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct BundleOptions {
+    #[command(subcommand)]
+    command: BundleCommand,
+}
+
+/// Bundle subcommands
+#[derive(Clone, Debug, Subcommand)]
+pub enum BundleCommand {
+    /// Write a bundle for each tree along with an index of their remotes and branches
+    Create(BundleCreateOptions),
+}
+
+/// Write a bundle for each tree along with an index of their remotes and branches
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct BundleCreateOptions {
+    /// Only bundle the commits that are not present in a previous bundle directory
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    base: Option<String>,
+    /// Increase verbosity level (default: 0)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Tree query for the gardens, groups or trees to bundle
+    query: String,
+    /// Directory where the bundles and their index are written
+    #[arg(value_hint = ValueHint::DirPath)]
+    dir: String,
+}
+
+/// The bundle index is written alongside the bundles.
+const INDEX: &str = "index.yaml";
+/// Index keys.
+const BRANCHES: &str = "branches";
+const BUNDLE: &str = "bundle";
+const COMMIT: &str = "commit";
+const REMOTES: &str = "remotes";
+const TREES: &str = "trees";
+const UPSTREAM: &str = "upstream";
+
+/// The trees recorded in a bundle index.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BundleIndex {
+    dir: std::path::PathBuf,
+    trees: IndexMap<String, BundleTree>,
+}
+
+/// A tree recorded in a bundle index.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BundleTree {
+    /// The bundle's filename. Incremental bundles omit trees without new commits.
+    bundle: Option<String>,
+    /// Remote names mapped to their URLs.
+    remotes: IndexMap<String, String>,
+    /// Local branch names mapped to their upstream branch and commit.
+    branches: IndexMap<String, BundleBranch>,
+}
+
+/// A branch recorded in a bundle index.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BundleBranch {
+    /// The remote branch that the branch tracks, e.g. "origin/main".
+    pub(crate) upstream: String,
+    /// The commit that the branch pointed to when the bundle was created.
+    commit: String,
+}
+
+impl BundleIndex {
+    /// Return the path to the bundle for the specified tree.
+    pub(crate) fn bundle_path(&self, tree: &str) -> Option<std::path::PathBuf> {
+        let bundle = self.trees.get(tree)?.bundle.as_ref()?;
+        Some(self.dir.join(bundle))
+    }
+
+    /// Return true when the specified tree is recorded in the index.
+    pub(crate) fn contains(&self, tree: &str) -> bool {
+        self.trees.contains_key(tree)
+    }
+
+    /// Return the remotes recorded for the specified tree.
+    pub(crate) fn remotes(&self, tree: &str) -> Option<&IndexMap<String, String>> {
+        self.trees.get(tree).map(|bundle_tree| &bundle_tree.remotes)
+    }
+
+    /// Return the branches recorded for the specified tree.
+    pub(crate) fn branches(&self, tree: &str) -> Option<&IndexMap<String, BundleBranch>> {
+        self.trees
+            .get(tree)
+            .map(|bundle_tree| &bundle_tree.branches)
+    }
+}
+
+/// Main entry point for the "garden bundle" command
+pub fn main(app_context: &model::ApplicationContext, options: &BundleOptions) -> Result<()> {
+    match &options.command {
+        BundleCommand::Create(create_options) => create(app_context, create_options),
+    }
+}
+
+/// Read the bundle index from a directory created by "garden bundle create".
+pub(crate) fn read_index(dir: &str) -> Result<BundleIndex, errors::GardenError> {
+    // Bundles are accessed from within each tree so the directory must be absolute.
+    let dir = path::abspath(&path::current_dir().join(dir));
+    let doc = config::reader::read_yaml(dir.join(INDEX))?;
+    let mut index = BundleIndex {
+        dir,
+        ..BundleIndex::default()
+    };
+    let Some(trees) = doc[TREES].as_hash() else {
+        return Ok(index);
+    };
+    for (name, entry) in trees {
+        let Some(name) = name.as_str() else {
+            continue;
+        };
+        let mut bundle_tree = BundleTree {
+            bundle: entry[BUNDLE].as_str().map(|bundle| bundle.to_string()),
+            ..BundleTree::default()
+        };
+        if let Some(remotes) = entry[REMOTES].as_hash() {
+            for (remote, url) in remotes {
+                if let (Some(remote), Some(url)) = (remote.as_str(), url.as_str()) {
+                    bundle_tree
+                        .remotes
+                        .insert(remote.to_string(), url.to_string());
+                }
+            }
+        }
+        if let Some(branches) = entry[BRANCHES].as_hash() {
+            for (branch, details) in branches {
+                let Some(branch) = branch.as_str() else {
+                    continue;
+                };
+                bundle_tree.branches.insert(
+                    branch.to_string(),
+                    BundleBranch {
+                        upstream: details[UPSTREAM].as_str().unwrap_or_default().to_string(),
+                        commit: details[COMMIT].as_str().unwrap_or_default().to_string(),
+                    },
+                );
+            }
+        }
+        index.trees.insert(name.to_string(), bundle_tree);
+    }
+
+    Ok(index)
+}
+
+/// Write the bundle index into the bundle directory.
+fn write_index(index: &BundleIndex) -> Result<(), errors::GardenError> {
+    let mut trees = yaml::Hash::new();
+    for (name, bundle_tree) in &index.trees {
+        let mut entry = yaml::Hash::new();
+        if let Some(bundle) = &bundle_tree.bundle {
+            entry.insert(
+                Yaml::String(BUNDLE.to_string()),
+                Yaml::String(bundle.to_string()),
+            );
+        }
+        let mut remotes = yaml::Hash::new();
+        for (remote, url) in &bundle_tree.remotes {
+            remotes.insert(
+                Yaml::String(remote.to_string()),
+                Yaml::String(url.to_string()),
+            );
+        }
+        entry.insert(Yaml::String(REMOTES.to_string()), Yaml::Hash(remotes));
+        let mut branches = yaml::Hash::new();
+        for (branch, details) in &bundle_tree.branches {
+            let mut branch_entry = yaml::Hash::new();
+            branch_entry.insert(
+                Yaml::String(UPSTREAM.to_string()),
+                Yaml::String(details.upstream.to_string()),
+            );
+            branch_entry.insert(
+                Yaml::String(COMMIT.to_string()),
+                Yaml::String(details.commit.to_string()),
+            );
+            branches.insert(Yaml::String(branch.to_string()), Yaml::Hash(branch_entry));
+        }
+        entry.insert(Yaml::String(BRANCHES.to_string()), Yaml::Hash(branches));
+        trees.insert(Yaml::String(name.to_string()), Yaml::Hash(entry));
+    }
+    let mut doc = yaml::Hash::new();
+    doc.insert(Yaml::String(TREES.to_string()), Yaml::Hash(trees));
+
+    config::writer::write_yaml(&Yaml::Hash(doc), index.dir.join(INDEX))
+}
+
+/// Return the bundle filename for a tree.
+fn bundle_name(tree: &str) -> String {
+    let name: String = tree
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    format!("{name}.bundle")
+}
+
+/// Return the configured branches for a tree mapped to their upstream branches.
+fn configured_branches(
+    eval_context: &model::EvalContext,
+    tree: &model::Tree,
+    path: &std::path::Path,
+) -> IndexMap<String, String> {
+    let mut branches = IndexMap::new();
+    let mut branch = tree.eval_branch(eval_context);
+    if branch.is_empty() {
+        branch = git::symbolic_branch(path).unwrap_or_default();
+    }
+    if !branch.is_empty() {
+        let upstream = tree
+            .get_upstream_branch(eval_context, &branch)
+            .unwrap_or_else(|| format!("{}/{branch}", tree.default_remote));
+        branches.insert(branch, upstream);
+    }
+    for (branch, expr) in &tree.branches {
+        let upstream = eval_context.tree_variable(expr);
+        if !upstream.is_empty() {
+            branches.insert(branch.to_string(), upstream);
+        }
+    }
+
+    branches
+}
+
+/// Write a bundle for each tree along with an index of their remotes and branches.
+fn create(app_context: &model::ApplicationContext, options: &BundleCreateOptions) -> Result<()> {
+    let config = app_context.get_root_config();
+    let quiet = app_context.options.quiet;
+    let verbose = app_context.options.verbose + options.verbose;
+    let base = options.base.as_deref().map(read_index).transpose()?;
+    let dir = std::path::PathBuf::from(&options.dir);
+    std::fs::create_dir_all(&dir)
+        .map_err(|err| errors::GardenError::OSError(format!("unable to create {dir:?}: {err}")))?;
+    let dir = dir.canonicalize().unwrap_or(dir);
+    let mut index = BundleIndex {
+        dir,
+        ..BundleIndex::default()
+    };
+    let mut exit_status = errors::EX_OK;
+
+    for context in query::resolve_trees(app_context, config, None, &options.query) {
+        let eval_context = model::EvalContext::from_app_context(app_context, &context);
+        let tree_config = eval_context.graft_config.unwrap_or(config);
+        let Some(tree) = tree_config.trees.get(&context.tree) else {
+            continue;
+        };
+        // Worktrees share their parent's repository and symlinks have nothing to bundle.
+        if tree.is_symlink || tree.is_worktree {
+            continue;
+        }
+        // Grafted trees are recorded using their "graft::tree" names.
+        let name = query::qualified_tree_name(app_context, &context);
+        let Some(path) = tree.canonical_pathbuf() else {
+            eprintln!("{} {}: tree does not exist", "error:".red().bold(), name);
+            exit_status = errors::EX_ERROR;
+            continue;
+        };
+
+        let mut bundle_tree = BundleTree::default();
+        for (remote, var) in &tree.remotes {
+            bundle_tree
+                .remotes
+                .insert(remote.to_string(), eval_context.tree_variable(var));
+        }
+        let base_branches = base.as_ref().and_then(|base| base.branches(&name));
+
+        // Incremental bundles exclude the commits recorded in the base index.
+        let mut revs: Vec<String> = Vec::new();
+        for (branch, upstream) in configured_branches(&eval_context, tree, &path) {
+            let refname = format!("refs/heads/{branch}");
+            let Some(commit) = git::commit_id(&path, &refname) else {
+                continue;
+            };
+            let base_commit = base_branches
+                .and_then(|branches| branches.get(&branch))
+                .map(|details| details.commit.as_str())
+                .filter(|base_commit| git::rev_exists(&path, base_commit));
+            match base_commit {
+                Some(base_commit) if base_commit == commit => (),
+                Some(base_commit) => {
+                    revs.push(refname);
+                    revs.push(format!("^{base_commit}"));
+                }
+                None => revs.push(refname),
+            }
+            bundle_tree
+                .branches
+                .insert(branch, BundleBranch { upstream, commit });
+        }
+        // Trees on a detached HEAD without a configured branch have nothing to bundle.
+        if bundle_tree.branches.is_empty() {
+            eprintln!(
+                "{} {}: no branches to bundle: configure a branch or checkout a branch",
+                "error:".red().bold(),
+                name
+            );
+            exit_status = errors::EX_ERROR;
+            continue;
+        }
+        if !revs.is_empty() {
+            // HEAD is included so that clones checkout the current branch.
+            let head = git::symbolic_branch(&path).map(|branch| format!("refs/heads/{branch}"));
+            if head.is_some_and(|head| revs.contains(&head)) {
+                revs.push(string!("HEAD"));
+            }
+            if let Some(model::Pin::Tag(tag)) = tree.pin() {
+                let refname = format!("refs/tags/{tag}");
+                if git::rev_exists(&path, &refname) {
+                    revs.push(refname);
+                }
+            }
+            let bundle = bundle_name(&name);
+            let bundle_path = index.dir.join(&bundle).to_string_lossy().to_string();
+            let mut command = vec!["git", "bundle", "create", "--quiet", bundle_path.as_str()];
+            command.extend(revs.iter().map(String::as_str));
+            if verbose > 1 {
+                display::print_command_vec(&command);
+            }
+            let status = cmd::run_command(&command, &path);
+            if status != errors::EX_OK {
+                exit_status = status;
+                continue;
+            }
+            if !quiet {
+                println!("{} {} -> {bundle}", "bundled".green(), name);
+            }
+            bundle_tree.bundle = Some(bundle);
+        }
+        index.trees.insert(name, bundle_tree);
+    }
+    write_index(&index)?;
+
+    // Return the last non-zero exit status.
+    cmd::result_from_exit_status(exit_status).map_err(|err| err.into())
+}
@@ -0,0 +1,194 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use yansi::Paint;
+
+use crate::{errors, model, model::StringSet, path, query};
+
+/// Manage the mirror repository cache
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct CacheOptions {
+    #[command(subcommand)]
+    command: CacheCommand,
+}
+
+/// Cache subcommands
+#[derive(Clone, Debug, Subcommand)]
+pub enum CacheCommand {
+    /// Remove cached mirrors that are not referenced by any tree
+    Gc(CacheGcOptions),
+}
+
+/// Remove cached mirrors that are not referenced by any tree
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct CacheGcOptions {
+    /// Print the mirrors that would be removed without removing them
+    #[arg(long, short = 'n')]
+    dry_run: bool,
+}
+
+/// Main entry point for the "garden cache" command
+pub fn main(app_context: &model::ApplicationContext, options: &CacheOptions) -> Result<()> {
+    match &options.command {
+        CacheCommand::Gc(gc_options) => gc(app_context, gc_options),
+    }
+}
+
+/// Each mirror records the clones that borrow its objects in this file.
+const CLONES: &str = "garden-clones";
+
+/// Return the directory name used for the mirror of the specified URL.
+pub(crate) fn mirror_name(url: &str) -> String {
+    let mut name: String = url
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !name.ends_with(".git") {
+        name.push_str(".git");
+    }
+
+    name
+}
+
+/// Record a clone that borrows objects from a mirror using "git clone --reference".
+/// The cache can be shared by multiple gardens so the clones are recorded in the mirror.
+pub(crate) fn register_clone(
+    mirror: &std::path::Path,
+    clone: &std::path::Path,
+) -> Result<(), errors::GardenError> {
+    let clones_path = mirror.join(CLONES);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&clones_path)
+        .map_err(|err| {
+            errors::GardenError::IOError(format!("unable to open {clones_path:?}: {err}"))
+        })?;
+    let line = format!("{}\n", path::abspath(clone).display());
+    file.write_all(line.as_bytes()).map_err(|err| {
+        errors::GardenError::IOError(format!("unable to write {clones_path:?}: {err}"))
+    })
+}
+
+/// Return the recorded clones that still borrow objects from a mirror.
+fn clones_using_mirror(mirror: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let Ok(clones) = std::fs::read_to_string(mirror.join(CLONES)) else {
+        return Vec::new();
+    };
+    let Ok(objects) = mirror.join("objects").canonicalize() else {
+        return Vec::new();
+    };
+    let mut result: Vec<std::path::PathBuf> = Vec::new();
+    for clone in clones.lines().map(std::path::PathBuf::from) {
+        if result.contains(&clone) {
+            continue;
+        }
+        // Bare clones keep their objects at the top level.
+        let is_borrowing = [".git/objects/info/alternates", "objects/info/alternates"]
+            .iter()
+            .filter_map(|alternates| std::fs::read_to_string(clone.join(alternates)).ok())
+            .any(|alternates| {
+                alternates.lines().any(|alternate| {
+                    std::path::Path::new(alternate)
+                        .canonicalize()
+                        .is_ok_and(|alternate| alternate == objects)
+                })
+            });
+        if is_borrowing {
+            result.push(clone);
+        }
+    }
+
+    result
+}
+
+/// Remove the cached mirrors that are not used by any of the configured trees.
+/// Mirrors that are borrowed from by clones in other gardens are not removed.
+fn gc(app_context: &model::ApplicationContext, options: &CacheGcOptions) -> Result<()> {
+    let config = app_context.get_root_config();
+    let quiet = app_context.options.quiet;
+    let Some(cache_dir) = config.cache_path(app_context) else {
+        return Err(errors::GardenError::ConfigurationError(string!(
+            "garden.cache is not enabled"
+        ))
+        .into());
+    };
+    if !cache_dir.exists() {
+        return Ok(());
+    }
+
+    // Gather the mirror names for all of the configured trees.
+    let mut mirrors = StringSet::new();
+    for context in query::resolve_trees(app_context, config, None, "@*") {
+        let eval_context = model::EvalContext::from_app_context(app_context, &context);
+        let tree_config = eval_context.graft_config.unwrap_or(config);
+        let Some(tree) = tree_config.trees.get(&context.tree) else {
+            continue;
+        };
+        if tree.is_symlink || tree.is_worktree {
+            continue;
+        }
+        if let Some(url) = tree.eval_url(&eval_context) {
+            mirrors.insert(mirror_name(&url));
+        }
+    }
+
+    let entries = std::fs::read_dir(&cache_dir).map_err(|err| {
+        errors::GardenError::IOError(format!("unable to read {cache_dir:?}: {err}"))
+    })?;
+    let mut unused: Vec<std::path::PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            // Only bare repositories created by "garden grow" are considered.
+            path.join("HEAD").is_file()
+                && path
+                    .file_name()
+                    .is_some_and(|name| !mirrors.contains(name.to_string_lossy().as_ref()))
+        })
+        .collect();
+    unused.sort();
+
+    for path in &unused {
+        let clones = clones_using_mirror(path);
+        if !clones.is_empty() {
+            if !quiet {
+                eprintln!(
+                    "{} {}: used by {}",
+                    "skipped".yellow(),
+                    path.display(),
+                    clones
+                        .iter()
+                        .map(|clone| clone.display().to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                );
+            }
+            continue;
+        }
+        if !quiet {
+            let label = if options.dry_run {
+                "would remove"
+            } else {
+                "removed"
+            };
+            println!("{} {}", label.yellow(), path.display());
+        }
+        if !options.dry_run {
+            std::fs::remove_dir_all(path).map_err(|err| {
+                errors::GardenError::IOError(format!("unable to remove {path:?}: {err}"))
+            })?;
+        }
+    }
+
+    Ok(())
+}
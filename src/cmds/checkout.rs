@@ -0,0 +1,63 @@
+use anyhow::Result;
+use clap::Parser;
+
+use crate::{cmd, cmds, display, errors, model, query};
+
+/// Checkout the commits recorded in "garden.lock" for trees
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct CheckoutOptions {
+    /// Checkout the commits recorded in "garden.lock"
+    #[arg(long, required = true)]
+    locked: bool,
+    /// Increase verbosity level (default: 0)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Filter trees by name post-query using a glob pattern
+    #[arg(long, short, default_value = "*")]
+    trees: String,
+    /// Tree query for the gardens, groups or trees to checkout
+    #[arg(required = true)]
+    queries: Vec<String>,
+}
+
+/// Main entry point for the "garden checkout" command
+pub fn main(app_context: &model::ApplicationContext, options: &CheckoutOptions) -> Result<()> {
+    let config = app_context.get_root_config();
+    let quiet = app_context.options.quiet;
+    let verbose = app_context.options.verbose + options.verbose;
+    let lockfile = cmds::lock::lockfile_path(config)?;
+    let locked_trees = cmds::lock::read_lockfile(&lockfile)?;
+    let mut exit_status = errors::EX_OK;
+
+    for query in &options.queries {
+        let contexts = query::resolve_and_filter_trees(app_context, config, query, &options.trees);
+        for context in &contexts {
+            let eval_context = model::EvalContext::from_app_context(app_context, context);
+            let tree_config = eval_context.graft_config.unwrap_or(config);
+            let Some(tree) = tree_config.trees.get(&context.tree) else {
+                continue;
+            };
+            if tree.is_symlink || tree.is_bare_repository {
+                continue;
+            }
+            // Missing trees are skipped.
+            if !display::print_tree(tree, config.tree_branches, false, verbose, quiet, false) {
+                continue;
+            }
+            let status = cmds::lock::checkout_locked_context(
+                app_context,
+                context,
+                &locked_trees,
+                quiet,
+                verbose,
+            );
+            if status != errors::EX_OK {
+                exit_status = status;
+            }
+        }
+    }
+
+    // Return the last non-zero exit status.
+    cmd::result_from_exit_status(exit_status).map_err(|err| err.into())
+}
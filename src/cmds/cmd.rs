@@ -1,4 +1,5 @@
-use std::sync::atomic;
+use std::io::Write;
+use std::sync::{atomic, Arc, Mutex};
 
 use anyhow::Result;
 use clap::{CommandFactory, FromArgMatches, Parser};
@@ -49,9 +50,23 @@ pub struct CmdOptions {
         value_name = "JOBS",
     )]
     num_jobs: Option<usize>,
+    /// Command output mode [stream, prefix, group]
+    #[arg(
+        long,
+        default_value_t = model::OutputMode::Stream,
+        value_name = "MODE",
+        value_parser = model::OutputMode::parse_from_str,
+    )]
+    output: model::OutputMode,
     /// Be quiet
     #[arg(short, long)]
     quiet: bool,
+    /// Write a JSON report of the results for each tree and command
+    #[arg(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath)]
+    report: Option<std::path::PathBuf>,
+    /// Display a summary of the exit status and duration for each tree and command
+    #[arg(long)]
+    summary: bool,
     /// Increase verbosity level (default: 0)
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
@@ -112,9 +127,23 @@ pub struct CustomOptions {
         value_name = "JOBS",
     )]
     num_jobs: Option<usize>,
+    /// Command output mode [stream, prefix, group]
+    #[arg(
+        long,
+        default_value_t = model::OutputMode::Stream,
+        value_name = "MODE",
+        value_parser = model::OutputMode::parse_from_str,
+    )]
+    output: model::OutputMode,
     /// Be quiet
     #[arg(short, long)]
     quiet: bool,
+    /// Write a JSON report of the results for each tree and command
+    #[arg(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath)]
+    report: Option<std::path::PathBuf>,
+    /// Display a summary of the exit status and duration for each tree and command
+    #[arg(long)]
+    summary: bool,
     /// Increase verbosity level (default: 0)
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
@@ -161,6 +190,7 @@ pub fn main_cmd(app_context: &model::ApplicationContext, options: &mut CmdOption
     } else {
         cmd(app_context, &options.query, &params)?
     };
+    finish(&params)?;
 
     cmd::result_from_exit_status(exit_status).map_err(|err| err.into())
 }
@@ -180,6 +210,10 @@ pub struct CmdParams {
     force: bool,
     keep_going: bool,
     num_jobs: Option<usize>,
+    output: model::OutputMode,
+    report: Option<std::path::PathBuf>,
+    results: Arc<Mutex<Vec<CommandResult>>>,
+    summary: bool,
     #[derivative(Default(value = "true"))]
     exit_on_error: bool,
     quiet: bool,
@@ -200,6 +234,9 @@ impl From<CmdOptions> for CmdParams {
             force: options.force,
             keep_going: options.keep_going,
             num_jobs: options.num_jobs,
+            output: options.output,
+            report: options.report.clone(),
+            summary: options.summary,
             tree_pattern: glob::Pattern::new(&options.trees).unwrap_or_default(),
             quiet: options.quiet,
             verbose: options.verbose,
@@ -229,6 +266,9 @@ impl From<CustomOptions> for CmdParams {
             exit_on_error: options.exit_on_error,
             force: options.force,
             num_jobs: options.num_jobs,
+            output: options.output,
+            report: options.report.clone(),
+            summary: options.summary,
             tree_pattern: glob::Pattern::new(&options.trees).unwrap_or_default(),
             quiet: options.quiet,
             verbose: options.verbose,
@@ -254,6 +294,117 @@ impl CmdParams {
 
         Ok(())
     }
+
+    /// Record the result of running a command in a tree.
+    fn record_result(&self, result: CommandResult) {
+        if let Ok(mut results) = self.results.lock() {
+            results.push(result);
+        }
+    }
+
+    /// Return the recorded results with failures listed last.
+    fn sorted_results(&self) -> Vec<CommandResult> {
+        let mut results = match self.results.lock() {
+            Ok(results) => results.clone(),
+            Err(_) => Vec::new(),
+        };
+        results.sort_by_key(|result| result.status != errors::EX_OK);
+
+        results
+    }
+}
+
+/// The result of running a named command in a tree.
+#[derive(Clone, Debug)]
+struct CommandResult {
+    tree: String,
+    command: String,
+    status: i32,
+    start: std::time::SystemTime,
+    end: std::time::SystemTime,
+}
+
+impl CommandResult {
+    /// Return the wall-clock time taken to run the command.
+    fn duration(&self) -> std::time::Duration {
+        self.end.duration_since(self.start).unwrap_or_default()
+    }
+}
+
+/// Print a summary of the results and write the "--report" file.
+/// The summary is displayed when "--summary" is used. It is also displayed with "-v"
+/// or when a command failed when commands were run in multiple trees or when
+/// multiple commands were run.
+fn finish(params: &CmdParams) -> Result<(), errors::GardenError> {
+    let results = params.sorted_results();
+    let failed = results.iter().any(|result| result.status != errors::EX_OK);
+    let display_summary = params.summary || ((params.verbose > 0 || failed) && results.len() > 1);
+    if !params.quiet && display_summary {
+        print_summary(&results);
+    }
+    if let Some(report) = &params.report {
+        write_report(report, &results)?;
+    }
+
+    Ok(())
+}
+
+/// Print an aligned summary of the results to stderr.
+fn print_summary(results: &[CommandResult]) {
+    let tree_width = results
+        .iter()
+        .map(|result| result.tree.len())
+        .max()
+        .unwrap_or_default();
+    let command_width = results
+        .iter()
+        .map(|result| result.command.len())
+        .max()
+        .unwrap_or_default();
+    eprintln!();
+    for result in results {
+        let status = if result.status == errors::EX_OK {
+            format!("{:<9}", "ok").green().to_string()
+        } else {
+            format!("{:<9}", format!("exit {}", result.status))
+                .red()
+                .bold()
+                .to_string()
+        };
+        eprintln!(
+            "{} {} {} {} {}",
+            "#".cyan(),
+            format!("{:<tree_width$}", result.tree).blue().bold(),
+            format!("{:<command_width$}", result.command).yellow(),
+            status,
+            format!("{:.2}s", result.duration().as_secs_f64()).dim(),
+        );
+    }
+}
+
+/// Write the results to a JSON report file.
+fn write_report(
+    path: &std::path::Path,
+    results: &[CommandResult],
+) -> Result<(), errors::GardenError> {
+    let results: Vec<serde_json::Value> = results
+        .iter()
+        .map(|result| {
+            serde_json::json!({
+                "tree": result.tree,
+                "command": result.command,
+                "status": result.status,
+                "start": humantime::format_rfc3339_millis(result.start).to_string(),
+                "end": humantime::format_rfc3339_millis(result.end).to_string(),
+                "duration": result.duration().as_secs_f64(),
+            })
+        })
+        .collect();
+    let report = serde_json::json!({ "results": results });
+    let contents = serde_json::to_string_pretty(&report).unwrap_or_default() + "\n";
+    std::fs::write(path, contents).map_err(|err| {
+        errors::GardenError::IOError(format!("unable to write {}: {err}", path.display()))
+    })
 }
 
 /// Format an error
@@ -301,7 +452,7 @@ pub fn main_custom(app_context: &model::ApplicationContext, arguments: &Vec<Stri
 /// Run commands across trees.
 ///
 /// Resolve the trees queries down to a set of tree indexes paired with
-/// an optional garden context.
+/// an optional garden context. Trees are visited after the trees they depend upon.
 ///
 /// If the names resolve to gardens, each garden is processed independently.
 /// Trees that exist in multiple matching gardens will be processed multiple
@@ -309,14 +460,14 @@ pub fn main_custom(app_context: &model::ApplicationContext, arguments: &Vec<Stri
 ///
 /// If the names resolve to trees, each tree is processed independently
 /// with no garden context.
-
 fn cmd(app_context: &model::ApplicationContext, query: &str, params: &CmdParams) -> Result<i32> {
     let config = app_context.get_root_config_mut();
     let contexts = query::resolve_trees(app_context, config, None, query);
+    let (contexts, dependencies) = query::sort_by_dependencies(app_context, contexts)?;
     if params.breadth_first {
-        run_cmd_breadth_first(app_context, &contexts, params)
+        run_cmd_breadth_first(app_context, &contexts, &dependencies, params)
     } else {
-        run_cmd_depth_first(app_context, &contexts, params)
+        run_cmd_depth_first(app_context, &contexts, &dependencies, params)
     }
 }
 
@@ -328,10 +479,11 @@ fn cmd_parallel(
 ) -> Result<i32> {
     let config = app_context.get_root_config_mut();
     let contexts = query::resolve_trees(app_context, config, None, query);
+    let (contexts, dependencies) = query::sort_by_dependencies(app_context, contexts)?;
     if params.breadth_first {
-        run_cmd_breadth_first_parallel(app_context, &contexts, params)
+        run_cmd_breadth_first_parallel(app_context, &contexts, &dependencies, params)
     } else {
-        run_cmd_depth_first_parallel(app_context, &contexts, params)
+        run_cmd_depth_first_parallel(app_context, &contexts, &dependencies, params)
     }
 }
 
@@ -411,6 +563,111 @@ impl ShellParams {
     }
 }
 
+/// Command output for a single tree. Output is displayed according to the output mode.
+/// Grouped output is buffered and displayed when the TreeOutput is dropped.
+struct TreeOutput {
+    mode: model::OutputMode,
+    prefix: String,
+    header: Option<String>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl TreeOutput {
+    fn new(mode: model::OutputMode, name: &str) -> Self {
+        let prefix = match mode {
+            model::OutputMode::Prefix => display::tree_prefix(name),
+            _ => String::new(),
+        };
+        Self {
+            mode,
+            prefix,
+            header: None,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        }
+    }
+
+    /// Display a command string before it is run.
+    fn print_command(&mut self, cmd_str: &str) {
+        let command = format!("{} {}", ":".cyan(), cmd_str.green());
+        match self.mode {
+            model::OutputMode::Stream => println!("{command}"),
+            model::OutputMode::Prefix => println!("{} {}", self.prefix, command),
+            model::OutputMode::Group => {
+                self.stdout.extend_from_slice(command.as_bytes());
+                self.stdout.push(b'\n');
+            }
+        }
+    }
+
+    /// Run a command and return its exit status.
+    fn status(&mut self, exec: subprocess::Exec) -> i32 {
+        match self.mode {
+            model::OutputMode::Stream => cmd::status(exec),
+            model::OutputMode::Prefix => cmd::status_with_prefix(exec, &self.prefix),
+            model::OutputMode::Group => {
+                let (status, stdout, stderr) = cmd::capture_output(exec);
+                self.stdout.extend(stdout);
+                self.stderr.extend(stderr);
+                status
+            }
+        }
+    }
+}
+
+impl Drop for TreeOutput {
+    /// Display grouped output while holding the stdout and stderr locks so that
+    /// output from trees running in parallel is not interleaved.
+    fn drop(&mut self) {
+        if self.header.is_none() && self.stdout.is_empty() && self.stderr.is_empty() {
+            return;
+        }
+        let mut stdout = std::io::stdout().lock();
+        let mut stderr = std::io::stderr().lock();
+        if let Some(header) = &self.header {
+            writeln!(stderr, "{header}").unwrap_or(());
+        }
+        stdout.write_all(&self.stdout).unwrap_or(());
+        stdout.flush().unwrap_or(());
+        stderr.write_all(&self.stderr).unwrap_or(());
+    }
+}
+
+/// Print the tree header. Returns false when the tree does not exist.
+/// Grouped output records the header so that it is displayed alongside the tree's output.
+fn print_tree(
+    tree: &model::Tree,
+    config: &model::Configuration,
+    params: &CmdParams,
+    output: &mut TreeOutput,
+) -> bool {
+    let exists = tree.pathbuf().is_some_and(|path| path.exists());
+    if output.mode != model::OutputMode::Group || !exists {
+        return display::print_tree(
+            tree,
+            config.tree_branches,
+            false,
+            params.verbose,
+            params.quiet,
+            params.force,
+        );
+    }
+    if !params.quiet {
+        if let Ok(path) = tree.path_as_ref() {
+            output.header = Some(display::display_tree(
+                tree,
+                path,
+                config.tree_branches,
+                false,
+                params.verbose,
+            ));
+        }
+    }
+
+    true
+}
+
 /// Check whether the TreeContext is relevant to the current CmdParams.
 /// Returns None when the extracted details are not applicable.
 fn extract_context_state<'a>(
@@ -427,10 +684,7 @@ fn extract_context_state<'a>(
         Some(config_id) => app_context.get_config(config_id),
         None => app_context.get_root_config(),
     };
-    let tree = match config.trees.get(&context.tree) {
-        Some(tree) => tree,
-        None => return None,
-    };
+    let tree = config.trees.get(&context.tree)?;
     if tree.is_symlink {
         return None;
     }
@@ -442,18 +696,20 @@ fn extract_context_state<'a>(
 fn run_cmd_breadth_first(
     app_context: &model::ApplicationContext,
     contexts: &[model::TreeContext],
+    dependencies: &[Vec<usize>],
     params: &CmdParams,
 ) -> Result<i32> {
     let mut exit_status: i32 = errors::EX_OK;
     let quiet = params.quiet;
-    let verbose = params.verbose;
-    let shell = app_context.get_root_config().shell.as_str();
-    let shell_params = ShellParams::new(shell, params.exit_on_error, params.word_split);
+    let mut failed = vec![false; contexts.len()];
     // Loop over each command, evaluate the tree environment,
     // and run the command in each context.
     for name in &params.commands {
         // One invocation runs multiple commands
-        for context in contexts {
+        for (idx, context) in contexts.iter().enumerate() {
+            if dependency_failed(context, &dependencies[idx], &mut failed, idx, quiet) {
+                continue;
+            }
             let Some((config, tree)) = extract_context_state(app_context, context, params) else {
                 continue;
             };
@@ -465,8 +721,9 @@ fn run_cmd_breadth_first(
                 continue;
             };
             let fallback_path;
+            let mut output = TreeOutput::new(params.output, tree.get_name());
             // Sparse gardens/missing trees are ok -> skip these entries.
-            if !display::print_tree(tree, config.tree_branches, verbose, quiet, params.force) {
+            if !print_tree(tree, config, params, &mut output) {
                 if params.force {
                     fallback_path = config.fallback_execdir_string();
                     path = &fallback_path;
@@ -475,23 +732,13 @@ fn run_cmd_breadth_first(
                 }
             }
 
-            // Expand one named command to include its pre-commands and post-commands.
-            let command_names = cmd::expand_command_names(app_context, context, name);
-            for command_name in command_names.iter() {
-                // One command maps to multiple command sequences.
-                // When the scope is tree, only the tree's commands
-                // are included.  When the scope includes a gardens,
-                // its matching commands are appended to the end.
-                let cmd_seq_vec = eval::command(app_context, context, command_name);
-                app_context.get_root_config_mut().reset();
-
-                if let Err(cmd_status) =
-                    run_cmd_vec(path, &shell_params, &env, &cmd_seq_vec, params)
-                {
-                    exit_status = cmd_status;
-                    if !params.keep_going {
-                        return Ok(cmd_status);
-                    }
+            if let Err(cmd_status) =
+                run_named_command(app_context, context, name, path, &env, params, &mut output)
+            {
+                exit_status = cmd_status;
+                failed[idx] = true;
+                if !params.keep_going {
+                    return Ok(cmd_status);
                 }
             }
         }
@@ -508,23 +755,21 @@ fn run_cmd_breadth_first(
 fn run_cmd_breadth_first_parallel(
     app_context: &model::ApplicationContext,
     contexts: &[model::TreeContext],
+    dependencies: &[Vec<usize>],
     params: &CmdParams,
 ) -> Result<i32> {
     let exit_status = atomic::AtomicI32::new(errors::EX_OK);
     let quiet = params.quiet;
-    let verbose = params.verbose;
-    let shell = app_context.get_root_config().shell.as_str();
-    let shell_params = ShellParams::new(shell, params.exit_on_error, params.word_split);
     // Loop over each command, evaluate the tree environment, and run the command in each context.
     params.commands.par_iter().for_each(|name| {
-        let mut exit_early = false;
+        let mut failed = vec![false; contexts.len()];
         // Create a thread-specific ApplicationContext.
         let app_context_clone = app_context.clone();
         let app_context = &app_context_clone;
         // One invocation runs multiple commands
-        for context in contexts {
-            if exit_early {
-                break;
+        for (idx, context) in contexts.iter().enumerate() {
+            if dependency_failed(context, &dependencies[idx], &mut failed, idx, quiet) {
+                continue;
             }
             let Some((config, tree)) = extract_context_state(app_context, context, params) else {
                 continue;
@@ -537,8 +782,9 @@ fn run_cmd_breadth_first_parallel(
                 continue;
             };
             let fallback_path;
+            let mut output = TreeOutput::new(params.output, tree.get_name());
             // Sparse gardens/missing trees are ok -> skip these entries.
-            if !display::print_tree(tree, config.tree_branches, verbose, quiet, params.force) {
+            if !print_tree(tree, config, params, &mut output) {
                 if params.force {
                     fallback_path = config.fallback_execdir_string();
                     path = &fallback_path;
@@ -547,24 +793,13 @@ fn run_cmd_breadth_first_parallel(
                 }
             }
 
-            // Expand one named command to include its pre-commands and post-commands.
-            let command_names = cmd::expand_command_names(app_context, context, name);
-            for command_name in command_names.iter() {
-                // One command maps to multiple command sequences.
-                // When the scope is tree, only the tree's commands
-                // are included.  When the scope includes a gardens,
-                // its matching commands are appended to the end.
-                let cmd_seq_vec = eval::command(app_context, context, command_name);
-                app_context.get_root_config_mut().reset();
-
-                if let Err(cmd_status) =
-                    run_cmd_vec(path, &shell_params, &env, &cmd_seq_vec, params)
-                {
-                    exit_status.store(cmd_status, atomic::Ordering::Relaxed);
-                    if !params.keep_going {
-                        exit_early = true;
-                        break;
-                    }
+            if let Err(cmd_status) =
+                run_named_command(app_context, context, name, path, &env, params, &mut output)
+            {
+                exit_status.store(cmd_status, atomic::Ordering::Relaxed);
+                failed[idx] = true;
+                if !params.keep_going {
+                    break;
                 }
             }
         }
@@ -578,15 +813,17 @@ fn run_cmd_breadth_first_parallel(
 fn run_cmd_depth_first(
     app_context: &model::ApplicationContext,
     contexts: &[model::TreeContext],
+    dependencies: &[Vec<usize>],
     params: &CmdParams,
 ) -> Result<i32> {
     let mut exit_status: i32 = errors::EX_OK;
     let quiet = params.quiet;
-    let verbose = params.verbose;
-    let shell = app_context.get_root_config().shell.as_str();
-    let shell_params = ShellParams::new(shell, params.exit_on_error, params.word_split);
+    let mut failed = vec![false; contexts.len()];
     // Loop over each context, evaluate the tree environment and run the command.
-    for context in contexts {
+    for (idx, context) in contexts.iter().enumerate() {
+        if dependency_failed(context, &dependencies[idx], &mut failed, idx, quiet) {
+            continue;
+        }
         let Some((config, tree)) = extract_context_state(app_context, context, params) else {
             continue;
         };
@@ -597,8 +834,9 @@ fn run_cmd_depth_first(
         let Ok(mut path) = tree.path_as_ref() else {
             continue;
         };
+        let mut output = TreeOutput::new(params.output, tree.get_name());
         // Sparse gardens/missing trees are ok -> skip these entries.
-        if !display::print_tree(tree, config.tree_branches, verbose, quiet, params.force) {
+        if !print_tree(tree, config, params, &mut output) {
             if params.force {
                 fallback_path = config.fallback_execdir_string();
                 path = &fallback_path;
@@ -608,22 +846,13 @@ fn run_cmd_depth_first(
         }
         // One invocation runs multiple commands
         for name in &params.commands {
-            // Expand one named command to include its pre-commands and post-commands.
-            let command_names = cmd::expand_command_names(app_context, context, name);
-            for command_name in command_names.iter() {
-                // One command maps to multiple command sequences.
-                // When the scope is tree, only the tree's commands
-                // are included.  When the scope includes a gardens,
-                // its matching commands are appended to the end.
-                let cmd_seq_vec = eval::command(app_context, context, command_name);
-                app_context.get_root_config_mut().reset();
-                if let Err(cmd_status) =
-                    run_cmd_vec(path, &shell_params, &env, &cmd_seq_vec, params)
-                {
-                    exit_status = cmd_status;
-                    if !params.keep_going {
-                        return Ok(cmd_status);
-                    }
+            if let Err(cmd_status) =
+                run_named_command(app_context, context, name, path, &env, params, &mut output)
+            {
+                exit_status = cmd_status;
+                failed[idx] = true;
+                if !params.keep_going {
+                    return Ok(cmd_status);
                 }
             }
         }
@@ -635,66 +864,40 @@ fn run_cmd_depth_first(
 
 /// Run commands depth-first in parallel.
 /// All trees are visited concurrently in parallel. Commands are run serially within
-/// the scope of a single tree.
+/// the scope of a single tree. Trees are scheduled once the trees they depend upon
+/// have finished successfully.
 fn run_cmd_depth_first_parallel(
     app_context: &model::ApplicationContext,
     contexts: &[model::TreeContext],
+    dependencies: &[Vec<usize>],
     params: &CmdParams,
 ) -> Result<i32> {
     let exit_status = atomic::AtomicI32::new(errors::EX_OK);
-    let quiet = params.quiet;
-    let verbose = params.verbose;
-    let shell = app_context.get_root_config().shell.as_str();
-    let shell_params = ShellParams::new(shell, params.exit_on_error, params.word_split);
-    // Loop over each context, evaluate the tree environment and run the command.
-    contexts.par_iter().for_each(|context| {
-        let mut exit_early = false;
-        // Create a thread-specific ApplicationContext.
-        let app_context_clone = app_context.clone();
-        let app_context = &app_context_clone;
-
-        let Some((config, tree)) = extract_context_state(app_context, context, params) else {
-            return;
-        };
-        // Evaluate the tree environment
-        let env = eval::environment(app_context, config, context);
-        // Run each command in the tree's context
-        let fallback_path;
-        let Ok(mut path) = tree.path_as_ref() else {
-            return;
-        };
-        // Sparse gardens/missing trees are ok -> skip these entries.
-        if !display::print_tree(tree, config.tree_branches, verbose, quiet, params.force) {
-            if params.force {
-                fallback_path = config.fallback_execdir_string();
-                path = &fallback_path;
-            } else {
-                return;
-            }
+    // Contexts are scheduled as soon as their last remaining dependency finishes.
+    let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); contexts.len()];
+    for (idx, deps) in dependencies.iter().enumerate() {
+        for dep_idx in deps {
+            dependents[*dep_idx].push(idx);
         }
-        // One invocation runs multiple commands
-        for name in &params.commands {
-            if exit_early {
-                break;
-            }
-            // Expand one named command to include its pre-commands and post-commands.
-            let command_names = cmd::expand_command_names(app_context, context, name);
-            for command_name in command_names.iter() {
-                // One command maps to multiple command sequences.
-                // When the scope is tree, only the tree's commands
-                // are included.  When the scope includes a gardens,
-                // its matching commands are appended to the end.
-                let cmd_seq_vec = eval::command(app_context, context, command_name);
-                app_context.get_root_config_mut().reset();
-                if let Err(cmd_status) =
-                    run_cmd_vec(path, &shell_params, &env, &cmd_seq_vec, params)
-                {
-                    exit_status.store(cmd_status, atomic::Ordering::Relaxed);
-                    if !params.keep_going {
-                        exit_early = true;
-                        break;
-                    }
-                }
+    }
+    let scheduler = Mutex::new(DependencyScheduler {
+        remaining: dependencies.iter().map(Vec::len).collect(),
+        failed: vec![false; contexts.len()],
+    });
+    let state = ParallelState {
+        app_context,
+        contexts,
+        dependencies,
+        dependents: &dependents,
+        params,
+        scheduler: &scheduler,
+        exit_status: &exit_status,
+    };
+    rayon::scope(|scope| {
+        for (idx, deps) in dependencies.iter().enumerate() {
+            if deps.is_empty() {
+                let state = &state;
+                scope.spawn(move |scope| run_scheduled_context(scope, state, idx));
             }
         }
     });
@@ -705,6 +908,188 @@ fn run_cmd_depth_first_parallel(
     Ok(exit_status.load(atomic::Ordering::SeqCst))
 }
 
+/// Dependency bookkeeping for the parallel depth-first scheduler.
+struct DependencyScheduler {
+    /// The number of unfinished dependencies for each context.
+    remaining: Vec<usize>,
+    /// Contexts that failed or were skipped because a dependency failed.
+    failed: Vec<bool>,
+}
+
+/// State shared by the contexts scheduled by run_cmd_depth_first_parallel().
+struct ParallelState<'a> {
+    app_context: &'a model::ApplicationContext,
+    contexts: &'a [model::TreeContext],
+    dependencies: &'a [Vec<usize>],
+    dependents: &'a [Vec<usize>],
+    params: &'a CmdParams,
+    scheduler: &'a Mutex<DependencyScheduler>,
+    exit_status: &'a atomic::AtomicI32,
+}
+
+/// Run a context whose dependencies have finished and then schedule
+/// the dependents that have no remaining dependencies.
+fn run_scheduled_context<'scope>(
+    scope: &rayon::Scope<'scope>,
+    state: &'scope ParallelState<'scope>,
+    idx: usize,
+) {
+    let context = &state.contexts[idx];
+    let dependency_failed = {
+        let scheduler = state.scheduler.lock().unwrap();
+        state.dependencies[idx]
+            .iter()
+            .any(|dep_idx| scheduler.failed[*dep_idx])
+    };
+    let failed = if dependency_failed {
+        if !state.params.quiet {
+            print_dependency_failure(context);
+        }
+        true
+    } else {
+        // Create a thread-specific ApplicationContext.
+        let app_context = state.app_context.clone();
+        run_context_parallel(&app_context, context, state.params, state.exit_status)
+    };
+
+    let mut ready = Vec::new();
+    {
+        let mut scheduler = state.scheduler.lock().unwrap();
+        scheduler.failed[idx] = failed;
+        for dependent_idx in &state.dependents[idx] {
+            scheduler.remaining[*dependent_idx] -= 1;
+            if scheduler.remaining[*dependent_idx] == 0 {
+                ready.push(*dependent_idx);
+            }
+        }
+    }
+    for dependent_idx in ready {
+        scope.spawn(move |scope| run_scheduled_context(scope, state, dependent_idx));
+    }
+}
+
+/// Run all of the commands in a single tree context from a parallel worker.
+/// Returns true when a command failed.
+fn run_context_parallel(
+    app_context: &model::ApplicationContext,
+    context: &model::TreeContext,
+    params: &CmdParams,
+    exit_status: &atomic::AtomicI32,
+) -> bool {
+    let mut failed = false;
+    let Some((config, tree)) = extract_context_state(app_context, context, params) else {
+        return failed;
+    };
+    // Evaluate the tree environment
+    let env = eval::environment(app_context, config, context);
+    // Run each command in the tree's context
+    let fallback_path;
+    let Ok(mut path) = tree.path_as_ref() else {
+        return failed;
+    };
+    let mut output = TreeOutput::new(params.output, tree.get_name());
+    // Sparse gardens/missing trees are ok -> skip these entries.
+    if !print_tree(tree, config, params, &mut output) {
+        if params.force {
+            fallback_path = config.fallback_execdir_string();
+            path = &fallback_path;
+        } else {
+            return failed;
+        }
+    }
+    // One invocation runs multiple commands
+    for name in &params.commands {
+        if let Err(cmd_status) =
+            run_named_command(app_context, context, name, path, &env, params, &mut output)
+        {
+            exit_status.store(cmd_status, atomic::Ordering::Relaxed);
+            failed = true;
+            if !params.keep_going {
+                break;
+            }
+        }
+    }
+
+    failed
+}
+
+/// Check whether any of a context's dependencies have failed.
+/// The context is marked as failed so that its own dependents are skipped.
+fn dependency_failed(
+    context: &model::TreeContext,
+    dependencies: &[usize],
+    failed: &mut [bool],
+    idx: usize,
+    quiet: bool,
+) -> bool {
+    if !dependencies.iter().any(|dep_idx| failed[*dep_idx]) {
+        return false;
+    }
+    failed[idx] = true;
+    if !quiet {
+        print_dependency_failure(context);
+    }
+
+    true
+}
+
+/// Report a tree that was skipped because one of its dependencies failed.
+fn print_dependency_failure(context: &model::TreeContext) {
+    eprintln!(
+        "{} {}: skipped because a dependency failed",
+        "warning:".yellow().bold(),
+        context.tree
+    );
+}
+
+/// Run a named command in a tree context and record its result for the summary.
+/// The named command is expanded to include its pre-commands and post-commands.
+fn run_named_command(
+    app_context: &model::ApplicationContext,
+    context: &model::TreeContext,
+    name: &str,
+    path: &str,
+    env: &Vec<(String, String)>,
+    params: &CmdParams,
+    output: &mut TreeOutput,
+) -> Result<(), i32> {
+    let shell = app_context.get_root_config().shell.as_str();
+    let shell_params = ShellParams::new(shell, params.exit_on_error, params.word_split);
+    let start = std::time::SystemTime::now();
+    let mut exit_status = errors::EX_OK;
+    // Expand one named command to include its pre-commands and post-commands.
+    let command_names = cmd::expand_command_names(app_context, context, name);
+    for command_name in command_names.iter() {
+        // One command maps to multiple command sequences.
+        // When the scope is tree, only the tree's commands
+        // are included.  When the scope includes a gardens,
+        // its matching commands are appended to the end.
+        let cmd_seq_vec = eval::command(app_context, context, command_name);
+        app_context.get_root_config_mut().reset();
+        if let Err(cmd_status) = run_cmd_vec(path, &shell_params, env, &cmd_seq_vec, params, output)
+        {
+            exit_status = cmd_status;
+            if !params.keep_going {
+                break;
+            }
+        }
+    }
+    if !params.dry_run {
+        params.record_result(CommandResult {
+            tree: context.tree.to_string(),
+            command: name.to_string(),
+            status: exit_status,
+            start,
+            end: std::time::SystemTime::now(),
+        });
+    }
+
+    match exit_status {
+        errors::EX_OK => Ok(()),
+        _ => Err(exit_status),
+    }
+}
+
 /// Run a vector of custom commands using the configured shell.
 /// Parameters:
 /// - path: The current working directory for the command.
@@ -712,12 +1097,14 @@ fn run_cmd_depth_first_parallel(
 /// - env: Environment variables to set.
 /// - cmd_seq_vec: Vector of vector of command strings to run.
 /// - arguments: Additional command line arguments available in $1, $2, $N.
+/// - output: Displays or buffers the command output according to the output mode.
 fn run_cmd_vec(
     path: &str,
     shell_params: &ShellParams,
     env: &Vec<(String, String)>,
     cmd_seq_vec: &[Vec<String>],
     params: &CmdParams,
+    output: &mut TreeOutput,
 ) -> Result<(), i32> {
     // Get the current executable name
     let current_exe = cmd::current_exe();
@@ -725,7 +1112,7 @@ fn run_cmd_vec(
     for cmd_seq in cmd_seq_vec {
         for cmd_str in cmd_seq {
             if params.verbose > 1 {
-                println!("{} {}", ":".cyan(), &cmd_str.green());
+                output.print_command(cmd_str);
             }
             if params.dry_run {
                 continue;
@@ -755,7 +1142,7 @@ fn run_cmd_vec(
             }
             // When a command list is used then the return code from the final command
             // is the one that is returned when --no-errexit is in effect.
-            let status = cmd::status(exec);
+            let status = output.status(exec);
             if status != errors::EX_OK {
                 exit_status = status;
                 if params.exit_on_error {
@@ -777,15 +1164,20 @@ fn run_cmd_vec(
 fn cmds(app: &model::ApplicationContext, params: &CmdParams) -> Result<()> {
     let exit_status = atomic::AtomicI32::new(errors::EX_OK);
     if params.num_jobs.is_some() {
-        params.queries.par_iter().for_each(|query| {
-            let status = cmd_parallel(&app.clone(), query, params).unwrap_or(errors::EX_IOERR);
+        let results: Vec<Result<i32>> = params
+            .queries
+            .par_iter()
+            .map(|query| cmd_parallel(&app.clone(), query, params))
+            .collect();
+        for result in results {
+            let status = result?;
             if status != errors::EX_OK {
                 exit_status.store(status, atomic::Ordering::Relaxed);
             }
-        });
+        }
     } else {
         for query in &params.queries {
-            let status = cmd(app, query, params).unwrap_or(errors::EX_IOERR);
+            let status = cmd(app, query, params)?;
             if status != errors::EX_OK {
                 exit_status.store(status, atomic::Ordering::Relaxed);
                 if !params.keep_going {
@@ -794,6 +1186,7 @@ fn cmds(app: &model::ApplicationContext, params: &CmdParams) -> Result<()> {
             }
         }
     }
+    finish(params)?;
     // Return the last non-zero exit status.
     cmd::result_from_exit_status(exit_status.load(atomic::Ordering::SeqCst))
         .map_err(|err| err.into())
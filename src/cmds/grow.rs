@@ -1,10 +1,17 @@
 /// Grow garden worktrees
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::sync::{Arc, LazyLock, Mutex};
+
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, ValueHint};
+use is_terminal::IsTerminal;
+use rayon::prelude::*;
 use yansi::Paint;
 
 use crate::{
-    cmd, constants, display, errors, git, model,
+    cmd, cmds, constants, display, errors, git, model,
     model::{IndexMap, IndexSet, StringSet},
     query,
 };
@@ -15,6 +22,28 @@ type GitConfigMap = IndexMap<String, StringSet>;
 #[derive(Parser, Clone, Debug)]
 #[command(author, about, long_about)]
 pub struct GrowOptions {
+    /// Report differences between the configuration and the repositories without changing anything
+    #[arg(long, default_value_t = false, conflicts_with = "locked")]
+    check: bool,
+    /// Clone trees from the bundles created by "garden bundle create"
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath, conflicts_with = "check")]
+    from_bundles: Option<String>,
+    /// Checkout the commits recorded in "garden.lock"
+    #[arg(long, default_value_t = false)]
+    locked: bool,
+    /// Grow trees in parallel using the specified number of jobs.
+    #[arg(
+        long = "jobs",
+        short = 'j',
+        require_equals = false,
+        num_args = 0..=1,
+        default_missing_value = "0",
+        value_name = "JOBS",
+    )]
+    num_jobs: Option<usize>,
+    /// Remove remotes that are not configured for the tree
+    #[arg(long, default_value_t = false)]
+    prune_remotes: bool,
     /// Increase verbosity level (default: 0)
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
@@ -26,21 +55,189 @@ pub struct GrowOptions {
     queries: Vec<String>,
 }
 
+/// Parameters shared by the functions that grow trees.
+struct GrowParams<'a> {
+    /// Repositories created using "git worktree" share a common Git configuration
+    /// and only need to be configured once. This tracks the repositories that
+    /// have already been configured.
+    configured_worktrees: &'a Mutex<StringSet>,
+    /// Trees are cloned from bundles when growing with "--from-bundles".
+    bundles: Option<&'a cmds::bundle::BundleIndex>,
+    /// Output from commands is captured when growing trees in parallel.
+    output: Option<RefCell<Vec<u8>>>,
+    /// Remove remotes that are not configured for the tree.
+    prune_remotes: bool,
+    quiet: bool,
+    verbose: u8,
+}
+
+impl<'a> GrowParams<'a> {
+    /// Create parameters for growing trees serially.
+    fn new(
+        configured_worktrees: &'a Mutex<StringSet>,
+        bundles: Option<&'a cmds::bundle::BundleIndex>,
+        prune_remotes: bool,
+        quiet: bool,
+        verbose: u8,
+    ) -> Self {
+        Self {
+            configured_worktrees,
+            bundles,
+            output: None,
+            prune_remotes,
+            quiet,
+            verbose,
+        }
+    }
+
+    /// Create parameters that capture command output for growing trees in parallel.
+    fn captured(
+        configured_worktrees: &'a Mutex<StringSet>,
+        bundles: Option<&'a cmds::bundle::BundleIndex>,
+        prune_remotes: bool,
+        quiet: bool,
+        verbose: u8,
+    ) -> Self {
+        Self {
+            configured_worktrees,
+            bundles,
+            output: Some(RefCell::new(Vec::new())),
+            prune_remotes,
+            quiet,
+            verbose,
+        }
+    }
+
+    /// Record the shared repository path. Returns false if it was already configured.
+    fn configure_worktree(&self, shared_worktree_path: String) -> bool {
+        match self.configured_worktrees.lock() {
+            Ok(mut configured_worktrees) => configured_worktrees.insert(shared_worktree_path),
+            Err(_) => false,
+        }
+    }
+
+    /// Return the bundle that a tree is grown from when growing with "--from-bundles".
+    fn bundle_path(&self, tree: &str) -> Option<std::path::PathBuf> {
+        self.bundles?.bundle_path(tree)
+    }
+
+    /// Run a command and return its exit status.
+    fn run(&self, exec: subprocess::Exec) -> i32 {
+        match &self.output {
+            Some(output) => {
+                let (status, stdout, stderr) = cmd::capture_output(exec);
+                let mut output = output.borrow_mut();
+                output.extend(stdout);
+                output.extend(stderr);
+                status
+            }
+            None => cmd::status(exec),
+        }
+    }
+
+    /// Run a command in the specified directory and return its exit status.
+    fn run_command(&self, command: &[&str], path: &dyn AsRef<std::path::Path>) -> i32 {
+        self.run(cmd::exec_in_dir(command, path.as_ref()))
+    }
+
+    /// Print a command from a list of arguments.
+    fn print_quoted_command(&self, command: &[&str]) {
+        self.print_command_str(&cmd::shell_quote_command(command));
+    }
+
+    /// Print a single command from a string.
+    fn print_command_str(&self, command: &str) {
+        let line = format!("{} {}", ":".cyan(), command.green());
+        match &self.output {
+            Some(output) => {
+                let mut output = output.borrow_mut();
+                output.extend(line.as_bytes());
+                output.push(b'\n');
+            }
+            None => println!("{line}"),
+        }
+    }
+
+    /// Print an error message.
+    fn print_error(&self, message: &str) {
+        self.print_message(&format!("{} {message}", "error:".red().bold()));
+    }
+
+    /// Print a warning message.
+    fn print_warning(&self, message: &str) {
+        self.print_message(&format!("{} {message}", "warning:".yellow().bold()));
+    }
+
+    /// Print a message to stderr or into the captured output.
+    fn print_message(&self, line: &str) {
+        match &self.output {
+            Some(output) => {
+                let mut output = output.borrow_mut();
+                output.extend(line.as_bytes());
+                output.push(b'\n');
+            }
+            None => eprintln!("{line}"),
+        }
+    }
+
+    /// Take the captured output.
+    fn take_output(&self) -> Vec<u8> {
+        self.output
+            .as_ref()
+            .map(|output| output.take())
+            .unwrap_or_default()
+    }
+}
+
 /// Main entry point for the "garden grow" command
 pub fn main(app_context: &model::ApplicationContext, options: &GrowOptions) -> Result<()> {
+    cmd::initialize_threads_option(options.num_jobs)?;
     let quiet = app_context.options.quiet;
     let verbose = app_context.options.verbose + options.verbose;
+    if options.check {
+        return check(app_context, options, quiet, verbose);
+    }
     let mut exit_status = errors::EX_OK;
-    let mut configured_worktrees: StringSet = IndexSet::new();
+    let configured_worktrees: Mutex<StringSet> = Mutex::new(IndexSet::new());
+    let locked_trees = if options.locked {
+        let lockfile = cmds::lock::lockfile_path(app_context.get_root_config())?;
+        Some(cmds::lock::read_lockfile(&lockfile)?)
+    } else {
+        None
+    };
+    let bundles = options
+        .from_bundles
+        .as_deref()
+        .map(cmds::bundle::read_index)
+        .transpose()?;
     for query in &options.queries {
-        let status = grow(
-            app_context,
-            &mut configured_worktrees,
-            quiet,
-            verbose,
-            query,
-            &options.trees,
-        )?;
+        let status = if options.num_jobs.is_some() {
+            grow_parallel(
+                app_context,
+                &configured_worktrees,
+                locked_trees.as_ref(),
+                bundles.as_ref(),
+                options,
+                quiet,
+                verbose,
+                query,
+            )
+        } else {
+            let params = GrowParams::new(
+                &configured_worktrees,
+                bundles.as_ref(),
+                options.prune_remotes,
+                quiet,
+                verbose,
+            );
+            grow(
+                app_context,
+                &params,
+                locked_trees.as_ref(),
+                query,
+                &options.trees,
+            )?
+        };
         if status != errors::EX_OK {
             exit_status = status;
         }
@@ -53,9 +250,8 @@ pub fn main(app_context: &model::ApplicationContext, options: &GrowOptions) -> R
 /// Create/update trees in the evaluated tree query.
 fn grow(
     app_context: &model::ApplicationContext,
-    configured_worktrees: &mut StringSet,
-    quiet: bool,
-    verbose: u8,
+    params: &GrowParams,
+    locked_trees: Option<&cmds::lock::LockedTrees>,
     query: &str,
     tree_pattern: &str,
 ) -> Result<i32> {
@@ -65,24 +261,330 @@ fn grow(
 
     for tree_context in &contexts {
         let eval_context = model::EvalContext::from_app_context(app_context, tree_context);
-        let status = grow_tree_from_context(&eval_context, configured_worktrees, quiet, verbose)?;
+        // Errors are reported and the remaining trees are grown.
+        let status = match grow_tree_from_context(&eval_context, params) {
+            Ok(status) => status,
+            Err(err) => {
+                eprintln!("{} {err}", "error:".red().bold());
+                match err.downcast::<errors::GardenError>() {
+                    Ok(garden_err) => garden_err.into(),
+                    Err(_) => errors::EX_ERROR,
+                }
+            }
+        };
         if status != errors::EX_OK {
             // Return the last non-zero exit status.
             exit_status = status;
         }
+        // Checkout the locked commit once the tree has been grown.
+        if let Some(locked_trees) = locked_trees {
+            let status = cmds::lock::checkout_locked_context(
+                app_context,
+                tree_context,
+                locked_trees,
+                params.quiet,
+                params.verbose,
+            );
+            if status != errors::EX_OK {
+                exit_status = status;
+            }
+        }
     }
 
     Ok(exit_status)
 }
 
-/// Grow the tree specified by the context into existence.
-/// Trees without remotes are silently ignored.
-fn grow_tree_from_context(
-    eval_context: &model::EvalContext,
-    configured_worktrees: &mut StringSet,
+/// Create/update trees in the evaluated tree query in parallel.
+/// Worktree parents are grown before their child worktrees. Worktrees that share
+/// a parent repository are grown by a single job so that the repository is
+/// only modified by one thread at a time.
+#[allow(clippy::too_many_arguments)]
+fn grow_parallel(
+    app_context: &model::ApplicationContext,
+    configured_worktrees: &Mutex<StringSet>,
+    locked_trees: Option<&cmds::lock::LockedTrees>,
+    bundles: Option<&cmds::bundle::BundleIndex>,
+    options: &GrowOptions,
     quiet: bool,
     verbose: u8,
-) -> Result<i32> {
+    query: &str,
+) -> i32 {
+    let config = app_context.get_root_config();
+    let contexts = query::resolve_and_filter_trees(app_context, config, query, &options.trees);
+    let (trees, worktrees) = partition_worktrees(app_context, contexts);
+    let total = trees.len() + worktrees.values().map(Vec::len).sum::<usize>();
+    let progress = Progress::new(total, quiet, verbose);
+    let grow_contexts = |contexts: &[model::TreeContext]| {
+        // ApplicationContext is cloned so that variables are evaluated independently
+        // in each thread.
+        let app_context = app_context.clone();
+        let mut exit_status = errors::EX_OK;
+        for context in contexts {
+            let status = grow_context_with_progress(
+                &app_context,
+                context,
+                configured_worktrees,
+                locked_trees,
+                bundles,
+                &progress,
+                options.prune_remotes,
+                verbose,
+            );
+            if status != errors::EX_OK {
+                exit_status = status;
+            }
+        }
+        exit_status
+    };
+
+    let tree_statuses: Vec<i32> = trees
+        .par_iter()
+        .map(|context| grow_contexts(std::slice::from_ref(context)))
+        .collect();
+    let worktree_statuses: Vec<i32> = worktrees
+        .values()
+        .collect::<Vec<_>>()
+        .par_iter()
+        .map(|contexts| grow_contexts(contexts))
+        .collect();
+    progress.clear();
+
+    // Return the last non-zero exit status.
+    tree_statuses
+        .into_iter()
+        .chain(worktree_statuses)
+        .rfind(|status| *status != errors::EX_OK)
+        .unwrap_or(errors::EX_OK)
+}
+
+/// Separate worktrees from the trees that can be grown independently.
+/// Worktrees are grouped by their shared parent repository. Parents are
+/// added to the independent trees so that they are grown before their children.
+fn partition_worktrees(
+    app_context: &model::ApplicationContext,
+    contexts: Vec<model::TreeContext>,
+) -> (
+    Vec<model::TreeContext>,
+    IndexMap<String, Vec<model::TreeContext>>,
+) {
+    let config = app_context.get_root_config();
+    let mut trees: Vec<model::TreeContext> = Vec::new();
+    let mut worktrees: IndexMap<String, Vec<model::TreeContext>> = IndexMap::new();
+    let mut parents: Vec<model::TreeContext> = Vec::new();
+    // Trees that are reachable through multiple groups or gardens are only grown once.
+    let mut seen: HashSet<(Option<model::ConfigId>, String)> = HashSet::new();
+    for context in contexts {
+        if !seen.insert((context.config, context.tree.to_string())) {
+            continue;
+        }
+        let eval_context = model::EvalContext::from_app_context(app_context, &context);
+        let is_worktree = context_tree(&eval_context).is_some_and(|tree| tree.is_worktree);
+        if !is_worktree {
+            trees.push(context);
+            continue;
+        }
+        let shared_worktree_path = query::shared_worktree_path(app_context, config, &context);
+        let parent_context = context_tree(&eval_context).and_then(|tree| {
+            query::tree_from_name(
+                eval_context.config,
+                &tree.eval_worktree(&eval_context),
+                context.garden.as_ref(),
+                context.group.as_ref(),
+            )
+        });
+        if let Some(parent_context) = parent_context {
+            parents.push(parent_context);
+        }
+        worktrees
+            .entry(shared_worktree_path)
+            .or_default()
+            .push(context);
+    }
+    for parent_context in parents {
+        let eval_context = model::EvalContext::from_app_context(app_context, &parent_context);
+        // Nested worktrees are grown by their parent's job.
+        let is_worktree = context_tree(&eval_context).is_some_and(|tree| tree.is_worktree);
+        if !is_worktree && seen.insert((parent_context.config, parent_context.tree.to_string())) {
+            trees.push(parent_context);
+        }
+    }
+
+    (trees, worktrees)
+}
+
+/// Grow a tree while capturing its output and reporting progress.
+#[allow(clippy::too_many_arguments)]
+fn grow_context_with_progress(
+    app_context: &model::ApplicationContext,
+    context: &model::TreeContext,
+    configured_worktrees: &Mutex<StringSet>,
+    locked_trees: Option<&cmds::lock::LockedTrees>,
+    bundles: Option<&cmds::bundle::BundleIndex>,
+    progress: &Progress,
+    prune_remotes: bool,
+    verbose: u8,
+) -> i32 {
+    progress.start(&context.tree);
+    let eval_context = model::EvalContext::from_app_context(app_context, context);
+    let params = GrowParams::captured(configured_worktrees, bundles, prune_remotes, true, verbose);
+    let mut exit_status = match grow_tree_from_context(&eval_context, &params) {
+        Ok(status) => status,
+        Err(err) => {
+            if let Some(output) = &params.output {
+                let message = format!("{} {err}\n", "error:".red().bold());
+                output.borrow_mut().extend(message.as_bytes());
+            }
+            match err.downcast::<errors::GardenError>() {
+                Ok(garden_err) => garden_err.into(),
+                Err(_) => errors::EX_ERROR,
+            }
+        }
+    };
+    if let Some(locked_trees) = locked_trees {
+        let status =
+            cmds::lock::checkout_locked_context(app_context, context, locked_trees, true, verbose);
+        if status != errors::EX_OK {
+            exit_status = status;
+        }
+    }
+    let header = context_tree(&eval_context)
+        .and_then(|tree| {
+            let path = tree.path_as_ref().ok()?;
+            Some(display::display_tree(
+                tree,
+                path,
+                eval_context.config.tree_branches,
+                false,
+                verbose,
+            ))
+        })
+        .unwrap_or_else(|| context.tree.to_string());
+    progress.finish(&context.tree, &header, exit_status, &params.take_output());
+
+    exit_status
+}
+
+/// Return the tree for an evaluation context.
+fn context_tree<'a>(eval_context: &'a model::EvalContext) -> Option<&'a model::Tree> {
+    eval_context
+        .graft_config
+        .unwrap_or(eval_context.config)
+        .trees
+        .get(&eval_context.tree_context.tree)
+}
+
+/// Display the progress of trees being grown in parallel.
+/// A live progress line is displayed when stderr is a terminal.
+/// Each tree is displayed on its own line once it has been grown.
+struct Progress {
+    state: Mutex<ProgressState>,
+    total: usize,
+    is_terminal: bool,
+    quiet: bool,
+    verbose: u8,
+}
+
+/// The mutable state of a progress display.
+#[derive(Default)]
+struct ProgressState {
+    active: StringSet,
+    completed: usize,
+}
+
+impl Progress {
+    /// The number of active trees to display in the progress line.
+    const MAX_ACTIVE: usize = 3;
+
+    fn new(total: usize, quiet: bool, verbose: u8) -> Self {
+        Self {
+            state: Mutex::new(ProgressState::default()),
+            total,
+            is_terminal: std::io::stderr().is_terminal(),
+            quiet,
+            verbose,
+        }
+    }
+
+    /// Record a tree that has started growing.
+    fn start(&self, name: &str) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        state.active.insert(name.to_string());
+        self.draw(&state);
+    }
+
+    /// Record a tree that has finished growing and display its output.
+    fn finish(&self, name: &str, header: &str, status: i32, output: &[u8]) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        state.active.shift_remove(name);
+        state.completed += 1;
+        // Command output is displayed for failures and when verbose.
+        let display_output = status != errors::EX_OK || self.verbose > 0;
+        if !self.quiet || display_output {
+            self.clear_line();
+            let mut stderr = std::io::stderr().lock();
+            writeln!(stderr, "{header}").unwrap_or(());
+            if display_output {
+                stderr.write_all(output).unwrap_or(());
+            }
+            if status != errors::EX_OK {
+                writeln!(
+                    stderr,
+                    "{} {name}: exit status {status}",
+                    "error:".red().bold()
+                )
+                .unwrap_or(());
+            }
+        }
+        self.draw(&state);
+    }
+
+    /// Clear the progress line.
+    fn clear(&self) {
+        if let Ok(_state) = self.state.lock() {
+            self.clear_line();
+        }
+    }
+
+    /// Clear the progress line. The state lock must be held by the caller.
+    fn clear_line(&self) {
+        if self.is_terminal && !self.quiet {
+            eprint!("\r\x1b[2K");
+        }
+    }
+
+    /// Draw the progress line. The state lock must be held by the caller.
+    fn draw(&self, state: &ProgressState) {
+        if !self.is_terminal || self.quiet || state.active.is_empty() {
+            return;
+        }
+        let mut names: Vec<&str> = state
+            .active
+            .iter()
+            .take(Self::MAX_ACTIVE)
+            .map(String::as_str)
+            .collect();
+        let remaining = state.active.len().saturating_sub(Self::MAX_ACTIVE);
+        let more;
+        if remaining > 0 {
+            more = format!("(+{remaining})");
+            names.push(&more);
+        }
+        eprint!(
+            "\r\x1b[2K{} {}",
+            format!("[{}/{}]", state.completed, self.total).cyan(),
+            names.join(" ")
+        );
+        std::io::stderr().flush().unwrap_or(());
+    }
+}
+
+/// Grow the tree specified by the context into existence.
+/// Trees without remotes are silently ignored.
+fn grow_tree_from_context(eval_context: &model::EvalContext, params: &GrowParams) -> Result<i32> {
     let mut exit_status = errors::EX_OK;
 
     let tree = if let Some(graft_cfg) = eval_context.graft_config {
@@ -101,7 +603,13 @@ fn grow_tree_from_context(
         }
     };
 
-    display::print_tree_details(tree, eval_context.config.tree_branches, verbose, quiet);
+    display::print_tree_details(
+        tree,
+        eval_context.config.tree_branches,
+        false,
+        params.verbose,
+        params.quiet,
+    );
     let Some(pathbuf) = tree.pathbuf() else {
         return Err(errors::GardenError::ConfigurationError(format!(
             "invalid path for tree: {tree}",
@@ -121,15 +629,17 @@ fn grow_tree_from_context(
 
     let branch = tree.eval_branch(eval_context);
     if !is_empty_tree(&pathbuf) {
-        return update_tree_from_context(
-            eval_context,
-            configured_worktrees,
-            &pathbuf,
-            &branch,
-            false,
-            quiet,
-            verbose,
-        );
+        // Existing trees are updated from incremental bundles.
+        exit_status = fetch_from_bundle(eval_context, tree, params, &pathbuf);
+        let status = update_tree_from_context(eval_context, params, &pathbuf, &branch, false)?;
+        if status != errors::EX_OK {
+            exit_status = status;
+        }
+        let status = checkout_pin(tree, params, &pathbuf, false);
+        if status != errors::EX_OK {
+            exit_status = status;
+        }
+        return Ok(exit_status);
     }
 
     if tree.is_symlink {
@@ -142,19 +652,49 @@ fn grow_tree_from_context(
     }
 
     if tree.is_worktree {
-        return grow_tree_from_context_as_worktree(
-            eval_context,
-            configured_worktrees,
-            quiet,
-            verbose,
-        );
+        exit_status = grow_tree_from_context_as_worktree(eval_context, params)?;
+        if exit_status == errors::EX_OK {
+            exit_status = checkout_pin(tree, params, &pathbuf, true);
+        }
+        return Ok(exit_status);
     }
 
-    // The "url" field maps to the default remote.
-    let Some(url) = tree.eval_url(eval_context) else {
-        return Ok(exit_status);
+    // The "url" field maps to the default remote. Trees are cloned from their
+    // bundle when growing with "--from-bundles". The remote URLs are configured
+    // by update_tree_from_context() once the tree has been cloned.
+    // Trees without a bundle are never cloned from the network when using "--from-bundles".
+    let name = query::qualified_tree_name(eval_context.app_context, eval_context.tree_context);
+    let bundle = params.bundle_path(&name);
+    let url = match (&bundle, params.bundles) {
+        (Some(bundle), _) => bundle.to_string_lossy().to_string(),
+        (None, Some(bundles)) if !bundles.contains(&name) => {
+            return Err(errors::GardenError::ConfigurationError(format!(
+                "{name}: not found in the bundle index"
+            ))
+            .into());
+        }
+        // Incremental bundles cannot be used to clone trees that they have no commits for.
+        (None, Some(_)) => {
+            return Err(errors::GardenError::ConfigurationError(format!(
+                "{name}: the bundle index does not contain a bundle for this tree"
+            ))
+            .into());
+        }
+        (None, None) => {
+            let Some(url) = tree.eval_url(eval_context) else {
+                return Ok(exit_status);
+            };
+            url
+        }
     };
 
+    // Clone using objects from the cached mirror when "garden.cache" is enabled.
+    let mirror = eval_context
+        .config
+        .cache_path(eval_context.app_context)
+        .filter(|_| bundle.is_none())
+        .and_then(|cache_dir| update_mirror(params, &cache_dir, &url));
+
     // git clone [options] <url> <path>
     let mut cmd: Vec<&str> = ["git", "clone"].to_vec();
 
@@ -186,7 +726,7 @@ fn grow_tree_from_context(
     // "git clone --depth=N" creates shallow clones with truncated history.
     let clone_depth = tree.clone_depth;
     let clone_depth_opt;
-    if clone_depth > 0 {
+    if clone_depth > 0 && bundle.is_none() {
         clone_depth_opt = format!("--depth={clone_depth}");
         cmd.push(&clone_depth_opt);
     }
@@ -200,60 +740,198 @@ fn grow_tree_from_context(
     } else {
         cmd.push("--no-single-branch");
     }
+    // "git clone --filter=<spec>" creates a partial clone, eg. "blob:none".
+    let clone_filter_opt;
+    if !tree.clone_filter.is_empty() && bundle.is_none() {
+        clone_filter_opt = format!("--filter={}", tree.clone_filter);
+        cmd.push(&clone_filter_opt);
+    }
+    // "git clone --sparse" checks out only the top-level files. The configured
+    // "sparse-checkout" paths are applied by update_tree_from_context().
+    if !tree.sparse_checkout.is_empty() && !tree.is_bare_repository {
+        cmd.push("--sparse");
+    }
+
+    // "git clone --reference <mirror> [--dissociate]" borrows objects from the cache.
+    let mirror_path;
+    if let Some(mirror) = &mirror {
+        mirror_path = mirror.to_string_lossy().to_string();
+        cmd.push("--reference");
+        cmd.push(&mirror_path);
+        if eval_context.config.cache_dissociate {
+            cmd.push("--dissociate");
+        }
+    }
 
     // <url> <path>
     let path = tree.path_as_ref()?;
     cmd.push(&url);
     cmd.push(path);
-    if verbose > 1 {
-        print_quoted_command(&cmd);
+    if params.verbose > 1 {
+        params.print_quoted_command(&cmd);
     }
 
-    let exec = cmd::exec_cmd(&cmd);
-    let status = cmd::status(exec);
+    let status = params.run(cmd::exec_cmd(&cmd));
     if status != 0 {
         exit_status = status;
     }
+    // Record clones that borrow objects from the mirror so that "garden cache gc"
+    // does not remove mirrors that are still in use.
+    if let Some(mirror) = mirror.filter(|_| status == errors::EX_OK) {
+        if !eval_context.config.cache_dissociate {
+            if let Err(err) = cmds::cache::register_clone(&mirror, &pathbuf) {
+                params.print_error(&err.to_string());
+            }
+        }
+    }
+    // Branches that track other remotes are fetched from the bundle.
+    if exit_status == errors::EX_OK {
+        exit_status = fetch_from_bundle(eval_context, tree, params, &pathbuf);
+    }
 
-    let status = update_tree_from_context(
-        eval_context,
-        configured_worktrees,
-        &pathbuf,
-        &branch,
-        true,
-        quiet,
-        verbose,
-    )?;
+    let status = update_tree_from_context(eval_context, params, &pathbuf, &branch, true)?;
     if status != errors::EX_OK {
         exit_status = status;
     }
+    // Checkout the "tag" or "commit" that the tree is pinned to.
+    if exit_status == errors::EX_OK {
+        exit_status = checkout_pin(tree, params, &pathbuf, true);
+    }
 
     Ok(exit_status)
 }
 
-/// Print a command from a list of arguments.
-fn print_quoted_command(command: &[&str]) {
-    let quoted_args = command
-        .iter()
-        .map(|arg| cmd::shell_quote(arg))
-        .collect::<Vec<String>>();
-    print_command_str(&quoted_args.join(" "));
+/// Fetch the branches and tags in a tree's bundle into its remote-tracking branches.
+fn fetch_from_bundle(
+    eval_context: &model::EvalContext,
+    tree: &model::Tree,
+    params: &GrowParams,
+    path: &std::path::Path,
+) -> i32 {
+    let name = query::qualified_tree_name(eval_context.app_context, eval_context.tree_context);
+    let Some(bundle_path) = params.bundle_path(&name) else {
+        return errors::EX_OK;
+    };
+    let Some(branches) = params.bundles.and_then(|bundles| bundles.branches(&name)) else {
+        return errors::EX_OK;
+    };
+    // Incremental bundles only contain the branches that have new commits.
+    let heads = git::bundle_heads(path, &bundle_path);
+    let mut refspecs: Vec<String> = Vec::new();
+    for (branch, details) in branches {
+        let refname = format!("refs/heads/{branch}");
+        if !heads.contains(&refname) {
+            continue;
+        }
+        if tree.is_bare_repository {
+            refspecs.push(format!("+{refname}:{refname}"));
+        } else {
+            refspecs.push(format!("+{refname}:refs/remotes/{}", details.upstream));
+        }
+    }
+    for refname in heads.iter().filter(|head| head.starts_with("refs/tags/")) {
+        refspecs.push(format!("+{refname}:{refname}"));
+    }
+    if refspecs.is_empty() {
+        return errors::EX_OK;
+    }
+
+    // git fetch --quiet <bundle> <refspec>...
+    let bundle = bundle_path.to_string_lossy().to_string();
+    let mut command = vec!["git", "fetch", "--quiet", bundle.as_str()];
+    command.extend(refspecs.iter().map(String::as_str));
+    if params.verbose > 1 {
+        params.print_quoted_command(&command);
+    }
+
+    params.run_command(&command, &path)
 }
 
-/// Print a single command from a string.
-fn print_command_str(cmd: &str) {
-    println!("{} {}", ":".cyan(), cmd.green())
+/// Fetch and checkout the tag or commit that a tree is pinned to on a detached HEAD.
+/// Existing trees are only moved to the pin when HEAD is already detached and the
+/// worktree has no uncommitted changes so that local work is never disturbed.
+fn checkout_pin(
+    tree: &model::Tree,
+    params: &GrowParams,
+    path: &std::path::Path,
+    is_new: bool,
+) -> i32 {
+    let Some(pin) = tree.pin() else {
+        return errors::EX_OK;
+    };
+    if tree.is_bare_repository || tree.is_symlink {
+        return errors::EX_OK;
+    }
+    let rev = pin.rev();
+    if git::head_matches(path, &rev) {
+        return errors::EX_OK;
+    }
+    if !is_new {
+        let name = tree.get_name();
+        if let Some(branch) = git::symbolic_branch(path) {
+            params.print_warning(&format!(
+                "{name}: not moved to {pin} because branch {branch} is checked out"
+            ));
+            return errors::EX_OK;
+        }
+        match git::status(path) {
+            Ok(status) if !status.is_dirty() => (),
+            _ => {
+                params.print_warning(&format!(
+                    "{name}: not moved to {pin} because it has uncommitted changes"
+                ));
+                return errors::EX_OK;
+            }
+        }
+    }
+
+    // Fetch only the pinned revision. Shallow clones fetch it using the configured depth.
+    if !git::rev_exists(path, &rev) {
+        let remote = tree.default_remote.as_str();
+        let refspec = match &pin {
+            model::Pin::Tag(tag) => format!("+refs/tags/{tag}:refs/tags/{tag}"),
+            model::Pin::Commit(commit) => commit.to_string(),
+        };
+        let depth_opt = format!("--depth={}", tree.clone_depth);
+        let mut command = vec!["git", "fetch", "--quiet"];
+        if tree.clone_depth > 0 {
+            command.push(&depth_opt);
+        }
+        command.push(remote);
+        command.push(&refspec);
+        if params.verbose > 1 {
+            params.print_quoted_command(&command);
+        }
+        if params.run_command(&command, &path) != errors::EX_OK {
+            // Abbreviated commit IDs cannot be fetched directly. Fetch the remote instead.
+            let command = ["git", "fetch", "--quiet", remote];
+            if params.verbose > 1 {
+                params.print_quoted_command(&command);
+            }
+            params.run_command(&command, &path);
+        }
+        if !git::rev_exists(path, &rev) {
+            params.print_error(&format!("{}: unable to fetch {pin}", tree.get_name()));
+            return errors::EX_ERROR;
+        }
+    }
+
+    // git checkout --quiet --detach <rev>
+    let command = ["git", "checkout", "--quiet", "--detach", rev.as_str()];
+    if params.verbose > 1 {
+        params.print_quoted_command(&command);
+    }
+
+    params.run_command(&command, &path)
 }
 
 /// Add remotes that do not already exist and synchronize .git/config values.
 fn update_tree_from_context(
     eval_context: &model::EvalContext,
-    configured_worktrees: &mut StringSet,
+    params: &GrowParams,
     path: &dyn AsRef<std::path::Path>,
     branch: &str,
     checkout: bool,
-    _quiet: bool,
-    verbose: u8,
 ) -> Result<i32> {
     let mut exit_status = errors::EX_OK;
 
@@ -278,6 +956,12 @@ fn update_tree_from_context(
         return Ok(exit_status);
     }
 
+    // Sparse checkouts are configured per-worktree.
+    let status = update_sparse_checkout(tree, params, path);
+    if status != errors::EX_OK {
+        exit_status = status;
+    }
+
     // Repositories created using "git worktree" share a common Git configuration
     // and only need to be configured once. Skip configuring the repository
     // if we've already processed it.
@@ -286,7 +970,11 @@ fn update_tree_from_context(
         eval_context.config,
         eval_context.tree_context,
     );
-    if !configured_worktrees.insert(shared_worktree_path) {
+    if !params.configure_worktree(shared_worktree_path) {
+        let status = update_submodules_and_lfs(tree, params, path);
+        if status != errors::EX_OK {
+            exit_status = status;
+        }
         return Ok(exit_status);
     }
 
@@ -305,50 +993,73 @@ fn update_tree_from_context(
     // The "default-remote" field is used to change the name of the default "origin" remote.
     if tree.default_remote != constants::ORIGIN {
         set_gitconfig_value(
+            params,
             "checkout.defaultRemoteName",
             &tree.default_remote,
             path,
-            verbose,
         );
     }
 
     let mut fetched_remotes: StringSet = IndexSet::new();
     fetched_remotes.insert(tree.default_remote.to_string());
+    // Remotes are never fetched when growing with "--from-bundles" so that trees
+    // can be grown without network access.
+    let offline = params.bundles.is_some();
+
+    // Trees without configured remotes use the remotes recorded in the bundle index
+    // so that trees grown from bundles do not keep the bundle as their remote.
+    let mut remotes: Vec<(String, String)> = tree
+        .remotes
+        .iter()
+        .map(|(remote, var)| {
+            let url = eval_context.rewrite_url(&eval_context.tree_variable(var));
+            (remote.to_string(), url)
+        })
+        .collect();
+    if remotes.is_empty() {
+        let name = query::qualified_tree_name(eval_context.app_context, eval_context.tree_context);
+        if let Some(bundle_remotes) = params.bundles.and_then(|bundles| bundles.remotes(&name)) {
+            remotes.extend(
+                bundle_remotes
+                    .iter()
+                    .map(|(remote, url)| (remote.to_string(), url.to_string())),
+            );
+        }
+    }
 
     // Loop over remotes and add/update the git remote configuration.
-    for (remote, var) in &tree.remotes {
-        let url = eval_context.tree_variable(var);
+    for (remote, url) in &remotes {
         if existing_remotes.contains(remote) {
             let remote_key = format!("remote.{remote}.url");
-            let status = set_gitconfig_value(&remote_key, &url, path, verbose);
+            let status = set_gitconfig_value(params, &remote_key, url, path);
             if status != errors::EX_OK {
                 exit_status = status;
             }
         } else {
             let command = ["git", "remote", "add", remote.as_ref(), url.as_ref()];
-            if verbose > 1 {
-                print_command_str(&command.join(" "));
+            if params.verbose > 1 {
+                params.print_command_str(&command.join(" "));
             }
-            let status = cmd::run_command(&command, path.as_ref());
+            let status = params.run_command(&command, path);
             if status != errors::EX_OK {
                 exit_status = status;
             }
 
             // git config remote.<name>.tagopt --no-tags
             let key = format!("remote.{}.tagopt", remote);
-            let status = set_gitconfig_value(&key, "--no-tags", path, verbose);
+            let status = set_gitconfig_value(params, &key, "--no-tags", path);
             if status != errors::EX_OK {
                 exit_status = status;
             }
 
             let remote_for_branch = tree.get_remote_for_branch(eval_context, branch);
-            if Some(remote) == remote_for_branch.as_ref() {
+            if Some(remote) == remote_for_branch.as_ref() && !offline {
                 let command = ["git", "fetch", remote];
-                if verbose > 1 {
-                    print_command_str(&command.join(" "));
+                if params.verbose > 1 {
+                    params.print_command_str(&command.join(" "));
                 }
                 fetched_remotes.insert(remote.to_string());
-                let status = cmd::run_command(&command, path.as_ref());
+                let status = params.run_command(&command, path);
                 if status != errors::EX_OK {
                     exit_status = status;
                 }
@@ -356,6 +1067,24 @@ fn update_tree_from_context(
         }
     }
 
+    // Remove remotes that are no longer configured. Trees without any configured
+    // remotes are left alone so that local-only trees keep their remotes.
+    if params.prune_remotes && !tree.remotes.is_empty() {
+        for remote in &existing_remotes {
+            if tree.remotes.contains_key(remote) {
+                continue;
+            }
+            let command = ["git", "remote", "remove", remote.as_str()];
+            if params.verbose > 1 {
+                params.print_command_str(&command.join(" "));
+            }
+            let status = params.run_command(&command, path);
+            if status != errors::EX_OK {
+                exit_status = status;
+            }
+        }
+    }
+
     // Set gitconfig settings.
     let mut gitconfig_cache: GitConfigMap = GitConfigMap::new();
     for (var_name, variables) in &tree.gitconfig {
@@ -367,10 +1096,10 @@ fn update_tree_from_context(
             };
             let status = if variables.len() > 1 {
                 // Multiple values are set using "git config --add <name> <value>"
-                append_gitconfig_value(&name, &value, path, &mut gitconfig_cache)
+                append_gitconfig_value(params, &name, &value, path, &mut gitconfig_cache)
             } else {
                 // Single values are set directly using "git config <name> <value>".
-                set_gitconfig_value(&name, &value, path, verbose)
+                set_gitconfig_value(params, &name, &value, path)
             };
             if status != errors::EX_OK {
                 exit_status = status;
@@ -390,23 +1119,23 @@ fn update_tree_from_context(
                     continue;
                 }
                 if let Some(remote_for_branch) = tree.get_remote_for_branch(eval_context, branch) {
-                    if !fetched_remotes.contains(&remote_for_branch) {
+                    if !offline && !fetched_remotes.contains(&remote_for_branch) {
                         fetched_remotes.insert(remote_for_branch.to_string());
                         let command = ["git", "fetch", remote_for_branch.as_str()];
-                        if verbose > 1 {
-                            print_command_str(&command.join(" "));
+                        if params.verbose > 1 {
+                            params.print_command_str(&command.join(" "));
                         }
-                        let status = cmd::run_command(&command, path.as_ref());
+                        let status = params.run_command(&command, path);
                         if status != errors::EX_OK {
                             exit_status = status;
                         }
                     }
                 }
                 let command = ["git", "branch", "--track", branch, remote_branch.as_str()];
-                if verbose > 1 {
-                    print_command_str(&command.join(" "));
+                if params.verbose > 1 {
+                    params.print_command_str(&command.join(" "));
                 }
-                let status = cmd::run_command(&command, path.as_ref());
+                let status = params.run_command(&command, path);
                 if status != errors::EX_OK {
                     exit_status = status;
                 }
@@ -417,17 +1146,191 @@ fn update_tree_from_context(
     // Checkout the configured branch if we are creating the repository initially.
     if checkout && !branch.is_empty() && tree.branches.contains_key(branch) {
         let command = ["git", "checkout", branch, "--"];
-        let status = cmd::run_command(&command, path.as_ref());
+        let status = params.run_command(&command, path);
         if status != errors::EX_OK {
             exit_status = status;
         }
     }
 
+    let status = update_submodules_and_lfs(tree, params, path);
+    if status != errors::EX_OK {
+        exit_status = status;
+    }
+
     Ok(exit_status)
 }
 
+/// The "git lfs" command is provided by the "git-lfs" executable.
+const GIT_LFS: &str = "git-lfs";
+
+/// Initialize submodules and fetch Git LFS files when configured.
+fn update_submodules_and_lfs(
+    tree: &model::Tree,
+    params: &GrowParams,
+    path: &dyn AsRef<std::path::Path>,
+) -> i32 {
+    let mut exit_status = errors::EX_OK;
+    if tree.is_bare_repository {
+        return exit_status;
+    }
+
+    // git submodule update --init [--recursive]
+    if tree.submodules != model::Submodules::None {
+        let mut command = vec!["git", "submodule", "update", "--init"];
+        if tree.submodules == model::Submodules::Recursive {
+            command.push("--recursive");
+        }
+        if params.verbose > 1 {
+            params.print_quoted_command(&command);
+        }
+        let status = params.run_command(&command, path);
+        if status != errors::EX_OK {
+            exit_status = status;
+        }
+    }
+
+    // git lfs install --local && git lfs pull
+    if tree.is_lfs {
+        if which::which(GIT_LFS).is_err() {
+            params.print_error(&format!(
+                "{}: {GIT_LFS} was not found on $PATH: unable to fetch LFS files",
+                tree.get_name()
+            ));
+            return errors::EX_UNAVAILABLE;
+        }
+        let commands: [&[&str]; 2] = [
+            &["git", "lfs", "install", "--local"],
+            &["git", "lfs", "pull"],
+        ];
+        for command in commands {
+            if params.verbose > 1 {
+                params.print_quoted_command(command);
+            }
+            let status = params.run_command(command, path);
+            if status != errors::EX_OK {
+                exit_status = status;
+                break;
+            }
+        }
+    }
+
+    exit_status
+}
+
+/// Per-mirror locks. Each lock records whether its mirror has been updated by the current
+/// process and is held while the mirror is updated so that trees sharing a mirror do not
+/// update it concurrently. Trees that use different mirrors are updated in parallel.
+static MIRROR_LOCKS: LazyLock<Mutex<HashMap<std::path::PathBuf, Arc<Mutex<bool>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Create or update the cached mirror for a URL. Returns the mirror's path when
+/// it can be used as a reference repository.
+fn update_mirror(
+    params: &GrowParams,
+    cache_dir: &std::path::Path,
+    url: &str,
+) -> Option<std::path::PathBuf> {
+    let mirror = cache_dir.join(cmds::cache::mirror_name(url));
+    // Claim the mirror's lock. The global lock is released before git is run.
+    let mirror_lock = MIRROR_LOCKS
+        .lock()
+        .ok()?
+        .entry(mirror.clone())
+        .or_default()
+        .clone();
+    let mut is_updated = mirror_lock.lock().ok()?;
+    if *is_updated {
+        return Some(mirror);
+    }
+
+    let mirror_str = mirror.to_string_lossy().to_string();
+    let status = if mirror.exists() {
+        // git fetch origin
+        // Refs are not pruned because clones in other gardens may borrow their objects.
+        let mut command = vec!["git", "fetch"];
+        if params.verbose == 0 {
+            command.push("--quiet");
+        }
+        command.push(constants::ORIGIN);
+        if params.verbose > 1 {
+            params.print_quoted_command(&command);
+        }
+        params.run_command(&command, &mirror)
+    } else {
+        // git clone --mirror <url> <mirror>
+        if let Err(err) = std::fs::create_dir_all(cache_dir) {
+            params.print_error(&format!("unable to create {cache_dir:?}: {err}"));
+            return None;
+        }
+        let mut command = vec!["git", "clone", "--mirror"];
+        if params.verbose == 0 {
+            command.push("--quiet");
+        }
+        command.push(url);
+        command.push(&mirror_str);
+        if params.verbose > 1 {
+            params.print_quoted_command(&command);
+        }
+        params.run(cmd::exec_cmd(&command))
+    };
+    if status != errors::EX_OK {
+        params.print_error(&format!("unable to update the cached mirror for {url}"));
+    }
+    if !mirror.exists() {
+        return None;
+    }
+    *is_updated = true;
+
+    Some(mirror)
+}
+
+/// Reconcile the sparse-checkout paths with the "sparse-checkout" configuration.
+fn update_sparse_checkout(
+    tree: &model::Tree,
+    params: &GrowParams,
+    path: &dyn AsRef<std::path::Path>,
+) -> i32 {
+    if tree.sparse_checkout.is_empty() || tree.is_bare_repository {
+        return errors::EX_OK;
+    }
+    let configured: StringSet = tree
+        .sparse_checkout
+        .iter()
+        .map(|value| value.trim_matches('/').to_string())
+        .filter(|value| !value.is_empty())
+        .collect();
+    // "git sparse-checkout list" fails when sparse checkouts are not enabled.
+    let is_cone = {
+        let command = ["git", "config", "--bool", "core.sparseCheckoutCone"];
+        let exec = cmd::exec_in_dir(&command, path.as_ref());
+        matches!(cmd::stdout_to_string(exec), Ok(value) if value == "true")
+    };
+    if is_cone {
+        let command = ["git", "sparse-checkout", "list"];
+        let exec = cmd::exec_in_dir(&command, path.as_ref());
+        if let Ok(output) = cmd::stdout_to_string(exec) {
+            let existing: StringSet = output
+                .lines()
+                .map(|line| line.trim_matches('/').to_string())
+                .filter(|line| !line.is_empty())
+                .collect();
+            if existing.len() == configured.len() && existing.is_subset(&configured) {
+                return errors::EX_OK;
+            }
+        }
+    }
+
+    let mut command = vec!["git", "sparse-checkout", "set", "--cone"];
+    command.extend(configured.iter().map(String::as_str));
+    if params.verbose > 1 {
+        params.print_quoted_command(&command);
+    }
+    params.run_command(&command, path)
+}
+
 /// Apply a "gitconfig" value in the specified directory.
 fn append_gitconfig_value(
+    params: &GrowParams,
     name: &str,
     value: &str,
     path: &dyn AsRef<std::path::Path>,
@@ -458,7 +1361,7 @@ fn append_gitconfig_value(
         if !values.contains(value) {
             values.insert(value.to_string());
             let command = ["git", "config", "--add", name, value];
-            status = cmd::run_command(&command, path.as_ref());
+            status = params.run_command(&command, path);
         }
     }
 
@@ -467,26 +1370,24 @@ fn append_gitconfig_value(
 
 /// Set a simple gitconfig value.
 fn set_gitconfig_value(
+    params: &GrowParams,
     name: &str,
     value: &str,
     path: &dyn AsRef<std::path::Path>,
-    verbose: u8,
 ) -> i32 {
     let command = ["git", "config", name, value];
-    if verbose > 1 {
-        print_command_str(&command.join(" "));
+    if params.verbose > 1 {
+        params.print_command_str(&command.join(" "));
     }
 
-    cmd::run_command(&command, path.as_ref())
+    params.run_command(&command, path)
 }
 
 /// Use "git worktree" to create a worktree.
 /// Grow the parent worktree first and then create our worktree.
 fn grow_tree_from_context_as_worktree(
     eval_context: &model::EvalContext,
-    configured_worktrees: &mut StringSet,
-    quiet: bool,
-    verbose: u8,
+    params: &GrowParams,
 ) -> Result<i32> {
     let mut exit_status = errors::EX_OK;
     let tree = if let Some(graft_cfg) = eval_context.graft_config {
@@ -520,8 +1421,7 @@ fn grow_tree_from_context_as_worktree(
 
     let parent_eval_context =
         model::EvalContext::from_app_context(eval_context.app_context, &parent_tree_context);
-    exit_status =
-        grow_tree_from_context(&parent_eval_context, configured_worktrees, quiet, verbose)?;
+    exit_status = grow_tree_from_context(&parent_eval_context, params)?;
     if exit_status != 0 {
         return Err(errors::GardenError::WorktreeParentCreationError {
             tree: tree.get_name().into(),
@@ -576,10 +1476,10 @@ fn grow_tree_from_context_as_worktree(
         }
     }
 
-    if verbose > 1 {
-        print_quoted_command(&cmd);
+    if params.verbose > 1 {
+        params.print_quoted_command(&cmd);
     }
-    exit_status = cmd::run_command(&cmd, &parent_path);
+    exit_status = params.run_command(&cmd, parent_path);
     if exit_status != 0 {
         return Err(errors::GardenError::WorktreeGitCheckoutError {
             tree: tree.get_name().clone(),
@@ -680,3 +1580,236 @@ fn is_empty_tree(path: &dyn AsRef<std::path::Path>) -> bool {
 
     false
 }
+
+/// Differences between a tree's configuration and its repository.
+enum Drift {
+    /// The tree has not been grown.
+    Missing,
+    /// A configured remote does not exist in the repository.
+    MissingRemote { remote: String, url: String },
+    /// A remote's URL does not match the configured URL.
+    RemoteUrl {
+        remote: String,
+        expected: String,
+        found: String,
+    },
+    /// A remote exists in the repository but is not configured.
+    ExtraRemote(String),
+    /// A "gitconfig" value does not match the configured value.
+    GitConfig {
+        name: String,
+        expected: String,
+        found: Vec<String>,
+    },
+    /// A tracking branch from "branches" does not exist.
+    MissingBranch { branch: String, upstream: String },
+    /// A worktree is not attached to its configured parent tree.
+    WorktreeParent {
+        parent: String,
+        found: Option<std::path::PathBuf>,
+    },
+    /// HEAD does not match the tag or commit that the tree is pinned to.
+    Pin(String),
+}
+
+impl display::SummaryStatus for Drift {
+    fn label(&self) -> &'static str {
+        match self {
+            Drift::Missing => "missing",
+            Drift::MissingRemote { .. } => "missing-remote",
+            Drift::RemoteUrl { .. } => "remote-url",
+            Drift::ExtraRemote(_) => "extra-remote",
+            Drift::GitConfig { .. } => "gitconfig",
+            Drift::MissingBranch { .. } => "missing-branch",
+            Drift::WorktreeParent { .. } => "worktree",
+            Drift::Pin(_) => "pin",
+        }
+    }
+
+    fn details(&self) -> String {
+        match self {
+            Drift::Missing => String::new(),
+            Drift::MissingRemote { remote, url } => format!("{remote} {url}"),
+            Drift::RemoteUrl {
+                remote,
+                expected,
+                found,
+            } => format!("{remote} {expected} (found {found})"),
+            Drift::ExtraRemote(remote) => remote.to_string(),
+            Drift::GitConfig {
+                name,
+                expected,
+                found,
+            } => {
+                if found.is_empty() {
+                    format!("{name} = {expected} (unset)")
+                } else {
+                    format!("{name} = {expected} (found {})", found.join(", "))
+                }
+            }
+            Drift::MissingBranch { branch, upstream } => format!("{branch} -> {upstream}"),
+            Drift::WorktreeParent { parent, found } => match found {
+                Some(found) => format!("{parent} (attached to {})", found.display()),
+                None => format!("{parent} (not a worktree)"),
+            },
+            Drift::Pin(pin) => format!("{pin} (HEAD does not match)"),
+        }
+    }
+}
+
+/// Compare trees against their configuration without modifying them.
+/// Returns an error when drift is detected.
+fn check(
+    app_context: &model::ApplicationContext,
+    options: &GrowOptions,
+    quiet: bool,
+    verbose: u8,
+) -> Result<()> {
+    let config = app_context.get_root_config();
+    let mut results: Vec<(String, Drift)> = Vec::new();
+    let mut checked = StringSet::new();
+    for query in &options.queries {
+        let contexts = query::resolve_and_filter_trees(app_context, config, query, &options.trees);
+        for context in &contexts {
+            if !checked.insert(context.tree.to_string()) {
+                continue;
+            }
+            let eval_context = model::EvalContext::from_app_context(app_context, context);
+            for drift in check_tree_from_context(&eval_context) {
+                results.push((context.tree.to_string(), drift));
+            }
+        }
+    }
+    if results.is_empty() {
+        if verbose > 0 && !quiet {
+            println!("{} {}", "#".cyan(), "ok".green());
+        }
+        return Ok(());
+    }
+    if !quiet {
+        display::print_summary(&results);
+    }
+
+    cmd::result_from_exit_status(errors::EX_ERROR).map_err(|err| err.into())
+}
+
+/// Return the differences between a tree's configuration and its repository.
+fn check_tree_from_context(eval_context: &model::EvalContext) -> Vec<Drift> {
+    let mut drift = Vec::new();
+    let Some(tree) = context_tree(eval_context) else {
+        return drift;
+    };
+    let Some(path) = tree.pathbuf() else {
+        return drift;
+    };
+    if is_empty_tree(&path) {
+        drift.push(Drift::Missing);
+        return drift;
+    }
+    if tree.is_symlink {
+        return drift;
+    }
+    if let Some(pin) = tree.pin().filter(|_| !tree.is_bare_repository) {
+        if !git::head_matches(&path, &pin.rev()) {
+            drift.push(Drift::Pin(pin.to_string()));
+        }
+    }
+
+    // Worktrees share their Git configuration with their parent tree.
+    if tree.is_worktree {
+        let parent = tree.eval_worktree(eval_context);
+        let parent_path = eval_context
+            .graft_config
+            .unwrap_or(eval_context.config)
+            .trees
+            .get(&parent)
+            .and_then(|parent_tree| parent_tree.canonical_pathbuf());
+        let found = match git::worktree_details(&path).map(|details| details.tree_type) {
+            Ok(model::GitTreeType::Worktree(found)) => Some(found),
+            _ => None,
+        };
+        let is_attached = match (&found, &parent_path) {
+            (Some(found), Some(parent_path)) => {
+                found.canonicalize().ok().as_ref() == Some(parent_path)
+            }
+            _ => false,
+        };
+        if !is_attached {
+            drift.push(Drift::WorktreeParent { parent, found });
+        }
+        return drift;
+    }
+
+    // Remotes.
+    let existing_remotes = git::remotes(&path);
+    for (remote, var) in &tree.remotes {
+        let url = eval_context.rewrite_url(&eval_context.tree_variable(var));
+        if !existing_remotes.contains(remote) {
+            drift.push(Drift::MissingRemote {
+                remote: remote.to_string(),
+                url,
+            });
+            continue;
+        }
+        let remote_key = format!("remote.{remote}.url");
+        let found = git::config_values(&path, &remote_key)
+            .pop()
+            .unwrap_or_default();
+        if found != url {
+            drift.push(Drift::RemoteUrl {
+                remote: remote.to_string(),
+                expected: url,
+                found,
+            });
+        }
+    }
+    // Trees without any configured remotes are not checked for extra remotes.
+    if !tree.remotes.is_empty() {
+        for remote in &existing_remotes {
+            if !tree.remotes.contains_key(remote) {
+                drift.push(Drift::ExtraRemote(remote.to_string()));
+            }
+        }
+    }
+
+    // Git configuration values.
+    for (var_name, variables) in &tree.gitconfig {
+        let name = eval_context.tree_value(var_name);
+        let found = git::config_values(&path, &name);
+        for var in variables {
+            let expected = match var.get_value() {
+                Some(precomputed_value) => precomputed_value.to_string(),
+                None => eval_context.tree_variable(var),
+            };
+            // Single values must match the effective value. Multiple values must be present.
+            let matches = if variables.len() > 1 {
+                found.contains(&expected)
+            } else {
+                found.last() == Some(&expected)
+            };
+            if !matches {
+                drift.push(Drift::GitConfig {
+                    name: name.to_string(),
+                    expected,
+                    found: found.clone(),
+                });
+            }
+        }
+    }
+
+    // Tracking branches.
+    if !tree.branches.is_empty() {
+        let branches = git::branches(&path);
+        for (branch, expr) in &tree.branches {
+            let upstream = eval_context.tree_variable(expr);
+            if !upstream.is_empty() && !branches.contains(branch) {
+                drift.push(Drift::MissingBranch {
+                    branch: branch.to_string(),
+                    upstream,
+                });
+            }
+        }
+    }
+
+    drift
+}
@@ -1,7 +1,9 @@
 use anyhow::Result;
 use clap::Parser;
+use yaml_rust::{yaml, Yaml};
+use yansi::Paint;
 
-use crate::{constants, display, model, query};
+use crate::{config, constants, display, model, query};
 
 /// Query tree status
 #[derive(Parser, Clone, Debug)]
@@ -10,6 +12,17 @@ pub struct ListOptions {
     /// Display details for all trees, including missing trees
     #[arg(short, long, default_value_t = false)]
     all: bool,
+    /// Output format [text, json, yaml]
+    #[arg(
+        long,
+        default_value_t = model::OutputFormat::Text,
+        value_name = "FORMAT",
+        value_parser = model::OutputFormat::parse_from_str,
+    )]
+    format: model::OutputFormat,
+    /// Print the dependency graph in the order that trees are visited
+    #[arg(long, short, default_value_t = false)]
+    graph: bool,
     /// Do not show commands
     #[arg(long, short = 'C', default_value_t = false)]
     no_commands: bool,
@@ -37,7 +50,40 @@ pub fn main(app_context: &model::ApplicationContext, options: &mut ListOptions)
     if options.queries.is_empty() {
         options.queries.push(string!("@*"));
     }
-    list(app_context, options)
+    if options.graph {
+        return list_graph(app_context, options);
+    }
+    match options.format {
+        model::OutputFormat::Text => list(app_context, options),
+        _ => list_structured(app_context, options),
+    }
+}
+
+/// Print trees in dependency order along with the trees they depend upon.
+fn list_graph(app_context: &model::ApplicationContext, options: &ListOptions) -> Result<()> {
+    let config = app_context.get_root_config();
+    for query in &options.queries {
+        let contexts = query::resolve_and_filter_trees(app_context, config, query, &options.trees);
+        let (mut contexts, _) = query::sort_by_dependencies(app_context, contexts)?;
+        if options.reverse {
+            contexts.reverse();
+        }
+        for context in &contexts {
+            let tree_config = match context.config {
+                Some(config_id) => app_context.get_config(config_id),
+                None => config,
+            };
+            let Some(tree) = tree_config.trees.get(&context.tree) else {
+                continue;
+            };
+            println!("{} {}", "#".cyan(), tree.get_name().blue().bold());
+            for dep in &tree.depends {
+                println!("  {} {}", "->".blue(), dep.yellow());
+            }
+        }
+    }
+
+    Ok(())
 }
 
 /// List tree details
@@ -88,6 +134,7 @@ fn list(app_context: &model::ApplicationContext, options: &ListOptions) -> Resul
                             context,
                             tree,
                             display_worktrees,
+                            verbose,
                         );
                     }
                     if show_commands && !tree.commands.is_empty() {
@@ -110,9 +157,15 @@ fn list(app_context: &model::ApplicationContext, options: &ListOptions) -> Resul
             if idx > 0 {
                 println!();
             }
-            display::print_tree(tree, config.tree_branches, verbose, false, false);
+            display::print_tree(tree, config.tree_branches, true, verbose, false, false);
             if !only_commands {
-                display::print_tree_extended_details(app_context, context, tree, display_worktrees);
+                display::print_tree_extended_details(
+                    app_context,
+                    context,
+                    tree,
+                    display_worktrees,
+                    verbose,
+                );
             }
             if show_commands && !tree.commands.is_empty() {
                 display::print_commands(&tree.commands);
@@ -138,3 +191,171 @@ fn list(app_context: &model::ApplicationContext, options: &ListOptions) -> Resul
 
     Ok(())
 }
+
+/// Print a structured document describing the gardens, groups and trees.
+fn list_structured(app_context: &model::ApplicationContext, options: &ListOptions) -> Result<()> {
+    let config = app_context.get_root_config();
+    let mut trees = yaml::Array::new();
+    for query in &options.queries {
+        let mut contexts =
+            query::resolve_and_filter_trees(app_context, config, query, &options.trees);
+        if options.reverse {
+            contexts.reverse();
+        }
+        for context in &contexts {
+            let tree_config = match context.config {
+                Some(config_id) => app_context.get_config(config_id),
+                None => config,
+            };
+            if let Some(tree) = tree_config.trees.get(&context.tree) {
+                trees.push(tree_document(app_context, context, tree));
+            }
+        }
+    }
+
+    let mut groups = yaml::Hash::new();
+    for (name, group) in &config.groups {
+        groups.insert(Yaml::String(name.to_string()), string_array(&group.members));
+    }
+    let mut gardens = yaml::Hash::new();
+    for (name, garden) in &config.gardens {
+        let mut garden_doc = yaml::Hash::new();
+        garden_doc.insert(
+            Yaml::String(constants::GROUPS.to_string()),
+            string_array(&garden.groups),
+        );
+        garden_doc.insert(
+            Yaml::String(constants::TREES.to_string()),
+            string_array(&garden.trees),
+        );
+        gardens.insert(Yaml::String(name.to_string()), Yaml::Hash(garden_doc));
+    }
+
+    let mut doc = yaml::Hash::new();
+    doc.insert(
+        Yaml::String(constants::GARDENS.to_string()),
+        Yaml::Hash(gardens),
+    );
+    doc.insert(
+        Yaml::String(constants::GROUPS.to_string()),
+        Yaml::Hash(groups),
+    );
+    doc.insert(
+        Yaml::String(constants::TREES.to_string()),
+        Yaml::Array(trees),
+    );
+    let doc = Yaml::Hash(doc);
+    match options.format {
+        model::OutputFormat::Json => println!("{}", config::writer::json_string(&doc)),
+        _ => println!("{}", config::writer::yaml_string(&doc)),
+    }
+
+    Ok(())
+}
+
+/// Build a structured document for a tree using evaluated values.
+fn tree_document(
+    app_context: &model::ApplicationContext,
+    context: &model::TreeContext,
+    tree: &model::Tree,
+) -> Yaml {
+    let eval_context = model::EvalContext::from_app_context(app_context, context);
+    let path = tree.path_as_ref().map(String::as_str).unwrap_or_default();
+    let exists = !path.is_empty() && std::path::Path::new(path).exists();
+
+    let mut remotes = yaml::Hash::new();
+    for (name, remote) in &tree.remotes {
+        remotes.insert(
+            Yaml::String(name.to_string()),
+            Yaml::String(eval_context.rewrite_url(&eval_context.tree_variable(remote))),
+        );
+    }
+    let links = tree
+        .links
+        .iter()
+        .map(|link| Yaml::String(eval_context.tree_variable(link)))
+        .collect();
+    let mut commands = yaml::Hash::new();
+    for (name, values) in &tree.commands {
+        commands.insert(
+            Yaml::String(name.to_string()),
+            Yaml::Array(
+                values
+                    .iter()
+                    .map(|value| Yaml::String(value.get_expr().to_string()))
+                    .collect(),
+            ),
+        );
+    }
+    let worktree = if tree.is_worktree {
+        Yaml::String(tree.eval_worktree(&eval_context))
+    } else {
+        Yaml::Null
+    };
+    let symlink = match tree.symlink_as_ref() {
+        Ok(symlink) if tree.is_symlink => Yaml::String(symlink.to_string()),
+        _ => Yaml::Null,
+    };
+
+    let mut doc = yaml::Hash::new();
+    doc.insert(
+        Yaml::String(string!("name")),
+        Yaml::String(tree.get_name().to_string()),
+    );
+    doc.insert(
+        Yaml::String(constants::PATH.to_string()),
+        Yaml::String(path.to_string()),
+    );
+    doc.insert(Yaml::String(string!("exists")), Yaml::Boolean(exists));
+    doc.insert(
+        Yaml::String(constants::DESCRIPTION.to_string()),
+        Yaml::String(tree.description.to_string()),
+    );
+    doc.insert(
+        Yaml::String(constants::BRANCH.to_string()),
+        Yaml::String(tree.eval_branch(&eval_context)),
+    );
+    let (tag, commit) = match tree.pin() {
+        Some(model::Pin::Tag(tag)) => (Yaml::String(tag), Yaml::Null),
+        Some(model::Pin::Commit(commit)) => (Yaml::Null, Yaml::String(commit)),
+        None => (Yaml::Null, Yaml::Null),
+    };
+    doc.insert(Yaml::String(constants::TAG.to_string()), tag);
+    doc.insert(Yaml::String(constants::COMMIT.to_string()), commit);
+    doc.insert(
+        Yaml::String(constants::URL.to_string()),
+        tree.eval_url(&eval_context)
+            .map(Yaml::String)
+            .unwrap_or(Yaml::Null),
+    );
+    doc.insert(
+        Yaml::String(constants::REMOTES.to_string()),
+        Yaml::Hash(remotes),
+    );
+    doc.insert(
+        Yaml::String(constants::LINKS.to_string()),
+        Yaml::Array(links),
+    );
+    doc.insert(
+        Yaml::String(constants::COMMANDS.to_string()),
+        Yaml::Hash(commands),
+    );
+    doc.insert(
+        Yaml::String(constants::DEPENDS.to_string()),
+        string_array(&tree.depends),
+    );
+    doc.insert(Yaml::String(constants::WORKTREE.to_string()), worktree);
+    doc.insert(Yaml::String(constants::SYMLINK.to_string()), symlink);
+
+    Yaml::Hash(doc)
+}
+
+/// Convert a set of strings into a Yaml array.
+fn string_array(values: &model::StringSet) -> Yaml {
+    Yaml::Array(
+        values
+            .iter()
+            .map(|value| Yaml::String(value.to_string()))
+            .collect(),
+    )
+}
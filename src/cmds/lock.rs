@@ -0,0 +1,257 @@
+use anyhow::Result;
+use clap::Parser;
+use yaml_rust::{yaml, Yaml};
+use yansi::Paint;
+
+use crate::{cmd, config, constants, display, errors, git, model, model::IndexMap, query};
+
+/// Record the current commit for trees in "garden.lock"
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct LockOptions {
+    /// Increase verbosity level (default: 0)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Filter trees by name post-query using a glob pattern
+    #[arg(long, short, default_value = "*")]
+    trees: String,
+    /// Tree query for the gardens, groups or trees to lock
+    queries: Vec<String>,
+}
+
+/// A tree entry recorded in "garden.lock".
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LockedTree {
+    pub(crate) url: String,
+    pub(crate) branch: String,
+    pub(crate) commit: String,
+}
+
+/// Locked trees keyed by their fully-qualified "graft::tree" names.
+pub(crate) type LockedTrees = IndexMap<String, LockedTree>;
+
+/// Main entry point for the "garden lock" command
+pub fn main(app_context: &model::ApplicationContext, options: &mut LockOptions) -> Result<()> {
+    if options.queries.is_empty() {
+        options.queries.push(string!("@*"));
+    }
+    let config = app_context.get_root_config();
+    let quiet = app_context.options.quiet;
+    let verbose = app_context.options.verbose + options.verbose;
+    let lockfile = lockfile_path(config)?;
+    // Existing entries for trees that are not part of the query are retained.
+    let mut locked_trees = if lockfile.exists() {
+        read_lockfile(&lockfile)?
+    } else {
+        LockedTrees::new()
+    };
+    let mut exit_status = errors::EX_OK;
+
+    for query in &options.queries {
+        let contexts = query::resolve_and_filter_trees(app_context, config, query, &options.trees);
+        for context in &contexts {
+            let eval_context = model::EvalContext::from_app_context(app_context, context);
+            let tree_config = eval_context.graft_config.unwrap_or(config);
+            let Some(tree) = tree_config.trees.get(&context.tree) else {
+                continue;
+            };
+            if tree.is_symlink {
+                continue;
+            }
+            let (Some(path), Ok(path_str)) = (tree.pathbuf(), tree.path_as_ref()) else {
+                continue;
+            };
+            // Missing trees cannot be locked.
+            if !path.exists() {
+                if !quiet {
+                    eprintln!(
+                        "{}",
+                        display::display_missing_tree(tree, path_str, verbose, false)
+                    );
+                }
+                continue;
+            }
+            let Some(commit) = git::commit_id(&path, "HEAD") else {
+                error_message(tree.get_name(), "unable to resolve HEAD");
+                exit_status = errors::EX_ERROR;
+                continue;
+            };
+            let branch =
+                git::symbolic_branch(&path).unwrap_or_else(|| tree.eval_branch(&eval_context));
+            let url = tree.eval_url(&eval_context).unwrap_or_default();
+            let name = query::qualified_tree_name(app_context, context);
+            if !quiet {
+                println!("{} {} {}", "#".cyan(), name.blue().bold(), commit.yellow());
+            }
+            locked_trees.insert(
+                name,
+                LockedTree {
+                    url,
+                    branch,
+                    commit,
+                },
+            );
+        }
+    }
+
+    write_lockfile(&lockfile, &locked_trees)?;
+    if verbose > 0 && !quiet {
+        println!("{} {}", "wrote".green(), lockfile.display());
+    }
+
+    cmd::result_from_exit_status(exit_status).map_err(|err| err.into())
+}
+
+/// Return the path to the "garden.lock" file for a configuration.
+pub(crate) fn lockfile_path(
+    config: &model::Configuration,
+) -> Result<std::path::PathBuf, errors::GardenError> {
+    Ok(config.get_path()?.with_file_name(constants::GARDEN_LOCK))
+}
+
+/// Read locked trees from a "garden.lock" file.
+pub(crate) fn read_lockfile(path: &std::path::Path) -> Result<LockedTrees, errors::GardenError> {
+    let doc = config::reader::read_yaml(path)?;
+    let mut locked_trees = LockedTrees::new();
+    let Some(trees) = doc[constants::TREES].as_hash() else {
+        return Ok(locked_trees);
+    };
+    for (name, entry) in trees {
+        let Some(name) = name.as_str() else {
+            continue;
+        };
+        let field = |key: &str| entry[key].as_str().unwrap_or_default().to_string();
+        let locked_tree = LockedTree {
+            url: field(constants::URL),
+            branch: field(constants::BRANCH),
+            commit: field(constants::COMMIT),
+        };
+        if locked_tree.commit.is_empty() {
+            return Err(errors::GardenError::InvalidConfiguration {
+                msg: format!("{}: {name}: missing commit", path.display()),
+            });
+        }
+        locked_trees.insert(name.to_string(), locked_tree);
+    }
+
+    Ok(locked_trees)
+}
+
+/// Write locked trees to a "garden.lock" file.
+fn write_lockfile(
+    path: &std::path::Path,
+    locked_trees: &LockedTrees,
+) -> Result<(), errors::GardenError> {
+    let mut trees = yaml::Hash::new();
+    for (name, locked_tree) in locked_trees {
+        let mut entry = yaml::Hash::new();
+        entry.insert(
+            Yaml::String(constants::URL.to_string()),
+            Yaml::String(locked_tree.url.to_string()),
+        );
+        entry.insert(
+            Yaml::String(constants::BRANCH.to_string()),
+            Yaml::String(locked_tree.branch.to_string()),
+        );
+        entry.insert(
+            Yaml::String(constants::COMMIT.to_string()),
+            Yaml::String(locked_tree.commit.to_string()),
+        );
+        trees.insert(Yaml::String(name.to_string()), Yaml::Hash(entry));
+    }
+    let mut doc = yaml::Hash::new();
+    doc.insert(
+        Yaml::String(constants::TREES.to_string()),
+        Yaml::Hash(trees),
+    );
+
+    config::writer::write_yaml(&Yaml::Hash(doc), path)
+}
+
+/// Checkout the locked commit for a tree context.
+/// Trees without an entry in "garden.lock" are reported and left as-is.
+pub(crate) fn checkout_locked_context(
+    app_context: &model::ApplicationContext,
+    context: &model::TreeContext,
+    locked_trees: &LockedTrees,
+    quiet: bool,
+    verbose: u8,
+) -> i32 {
+    let config = match context.config {
+        Some(config_id) => app_context.get_config(config_id),
+        None => app_context.get_root_config(),
+    };
+    let Some(tree) = config.trees.get(&context.tree) else {
+        return errors::EX_OK;
+    };
+    let Some(path) = tree.pathbuf() else {
+        return errors::EX_OK;
+    };
+    if tree.is_symlink || tree.is_bare_repository || !path.exists() {
+        return errors::EX_OK;
+    }
+    let name = query::qualified_tree_name(app_context, context);
+    let Some(locked_tree) = locked_trees.get(&name) else {
+        if !quiet {
+            eprintln!(
+                "{} {}: not found in {}",
+                "warning:".yellow().bold(),
+                name,
+                constants::GARDEN_LOCK
+            );
+        }
+        return errors::EX_OK;
+    };
+    let remote = tree.default_remote.as_str();
+    let exit_status = checkout_locked(&path, locked_tree, remote, verbose);
+    if exit_status != errors::EX_OK {
+        error_message(&name, &format!("unable to checkout {}", locked_tree.commit));
+    }
+
+    exit_status
+}
+
+/// Checkout the locked commit in the specified worktree.
+/// The locked branch is checked out when it points to the locked commit.
+/// The commit is checked out on a detached HEAD otherwise.
+fn checkout_locked(
+    path: &std::path::Path,
+    locked_tree: &LockedTree,
+    remote: &str,
+    verbose: u8,
+) -> i32 {
+    let commit = locked_tree.commit.as_str();
+    // Fetch the commit when it is not present, eg. in shallow clones.
+    if !git::rev_exists(path, commit) {
+        let command = ["git", "fetch", "--quiet", remote, commit];
+        if verbose > 1 {
+            display::print_command_vec(&command);
+        }
+        if cmd::run_command(&command, path) != errors::EX_OK {
+            let command = ["git", "fetch", "--quiet", remote];
+            if verbose > 1 {
+                display::print_command_vec(&command);
+            }
+            cmd::run_command(&command, path);
+        }
+    }
+
+    let branch = locked_tree.branch.as_str();
+    let branch_ref = format!("refs/heads/{branch}");
+    let command =
+        if !branch.is_empty() && git::commit_id(path, &branch_ref).as_deref() == Some(commit) {
+            ["git", "checkout", "--quiet", branch, "--"]
+        } else {
+            ["git", "checkout", "--quiet", "--detach", commit]
+        };
+    if verbose > 1 {
+        display::print_command_vec(&command);
+    }
+
+    cmd::run_command(&command, path)
+}
+
+/// Print an error message for a tree.
+fn error_message(name: &str, message: &str) {
+    eprintln!("{} {}: {}", "error:".red().bold(), name, message);
+}
@@ -1,3 +1,15 @@
+/// Branch command
+pub mod branch;
+
+/// Bundle command
+pub mod bundle;
+
+/// Cache command
+pub mod cache;
+
+/// Checkout command
+pub mod checkout;
+
 /// Configuration-defined commands
 pub mod cmd;
 
@@ -19,11 +31,35 @@ pub mod init;
 /// List command
 pub mod list;
 
+/// Lock command
+pub mod lock;
+
+/// Mv command
+pub mod mv;
+
 /// Plant command
 pub mod plant;
 
 /// Prune command
 pub mod prune;
 
+/// Rm command
+pub mod rm;
+
 /// Shell command
 pub mod shell;
+
+/// Status command
+pub mod status;
+
+/// Trash command
+pub mod trash;
+
+/// Update command
+pub mod update;
+
+/// Validate command
+pub mod validate;
+
+/// Worktree command
+pub mod worktree;
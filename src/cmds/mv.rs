@@ -0,0 +1,258 @@
+use anyhow::Result;
+use clap::{Parser, ValueHint};
+use yaml_rust::{yaml, Yaml};
+use yansi::Paint;
+
+use crate::{cmd, config, constants, display, errors, git, model, path};
+
+/// Move a tree to a new location and update the garden file
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct MvOptions {
+    /// Rename the tree in the garden file
+    #[arg(long, short)]
+    name: Option<String>,
+    /// Increase verbosity level (default: 0)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Tree to move
+    tree: String,
+    /// New path for the tree
+    #[arg(value_hint = ValueHint::DirPath)]
+    path: String,
+}
+
+/// Main entry point for the "garden mv" command
+pub fn main(app_context: &model::ApplicationContext, options: &MvOptions) -> Result<()> {
+    let config = app_context.get_root_config();
+    let quiet = app_context.options.quiet;
+    let verbose = app_context.options.verbose + options.verbose;
+    let tree =
+        config
+            .trees
+            .get(&options.tree)
+            .ok_or_else(|| errors::GardenError::TreeNotFound {
+                tree: options.tree.to_string(),
+            })?;
+    let name = options.name.as_deref().unwrap_or(&options.tree);
+    if name != options.tree && config.trees.contains_key(name) {
+        return Err(errors::GardenError::ConfigurationError(format!(
+            "tree already exists: {name}"
+        ))
+        .into());
+    }
+    let Some(old_path) = tree
+        .pathbuf()
+        .filter(|path| path.exists() || path.is_symlink())
+    else {
+        return Err(errors::GardenError::ConfigurationError(format!(
+            "{}: tree does not exist on disk",
+            options.tree
+        ))
+        .into());
+    };
+
+    // Relative paths are relative to the current directory.
+    let root = config.root_path.canonicalize().map_err(|err| {
+        errors::GardenError::ConfigurationError(format!(
+            "unable to canonicalize config root: {err:?}"
+        ))
+    })?;
+    let new_path = std::path::PathBuf::from(&options.path);
+    let new_path = if new_path.is_absolute() {
+        new_path
+    } else {
+        path::current_dir().join(new_path)
+    };
+    if new_path.exists() {
+        return Err(errors::GardenError::FileExists(format!(
+            "{} already exists",
+            new_path.display()
+        ))
+        .into());
+    }
+
+    // Read the garden file before anything is moved so that trees defined in
+    // other files are rejected before the filesystem is modified.
+    let config_path = config.get_path()?;
+    let mut doc = config::reader::read_yaml(config_path)?;
+    let trees_key = Yaml::String(constants::TREES.to_string());
+    let old_key = Yaml::String(options.tree.to_string());
+    let is_defined = doc[constants::TREES]
+        .as_hash()
+        .is_some_and(|trees| trees.contains_key(&old_key));
+    if !is_defined {
+        return Err(errors::GardenError::ConfigurationError(format!(
+            "{} is not defined in {}",
+            options.tree,
+            config_path.display()
+        ))
+        .into());
+    }
+
+    // Worktree details are gathered before moving the tree.
+    let tree_type = if tree.is_symlink {
+        None
+    } else {
+        git::worktree_details(&old_path)
+            .ok()
+            .map(|details| details.tree_type)
+    };
+
+    // Create the new parent directory.
+    let parent = new_path.parent().ok_or_else(|| {
+        errors::GardenError::OSError(format!("{new_path:?}: unable to get parent directory"))
+    })?;
+    std::fs::create_dir_all(parent).map_err(|err| {
+        errors::GardenError::OSError(format!("unable to create {parent:?}: {err}"))
+    })?;
+    let new_path = path::abspath(parent).join(new_path.file_name().unwrap_or_default());
+
+    // Update the tree's "path" and rename the tree when "--name" is specified.
+    let relative_path = path::strip_prefix_into_string(&root, &new_path)?;
+    if let Yaml::Hash(ref mut doc_hash) = doc {
+        if let Some(Yaml::Hash(ref mut trees)) = doc_hash.get_mut(&trees_key) {
+            let mut renamed_trees = yaml::Hash::new();
+            for (key, value) in std::mem::take(trees) {
+                if key != old_key {
+                    renamed_trees.insert(key, value);
+                    continue;
+                }
+                // Trees specified as a single URL are promoted to a hash.
+                let mut entry = match value {
+                    Yaml::Hash(entry) => entry,
+                    Yaml::String(url) => {
+                        let mut entry = yaml::Hash::new();
+                        entry.insert(Yaml::String(constants::URL.to_string()), Yaml::String(url));
+                        entry
+                    }
+                    _ => yaml::Hash::new(),
+                };
+                let path_key = Yaml::String(constants::PATH.to_string());
+                if relative_path == name {
+                    entry.remove(&path_key);
+                } else {
+                    entry.insert(path_key, Yaml::String(relative_path.to_string()));
+                }
+                renamed_trees.insert(Yaml::String(name.to_string()), Yaml::Hash(entry));
+            }
+            *trees = renamed_trees;
+        }
+        if name != options.tree {
+            rename_references(doc_hash, &options.tree, name);
+        }
+    }
+
+    // Move the tree into its new location.
+    std::fs::rename(&old_path, &new_path).map_err(|err| {
+        errors::GardenError::IOError(format!(
+            "unable to move {} to {}: {err}",
+            old_path.display(),
+            new_path.display()
+        ))
+    })?;
+
+    // Move the tree back when its worktrees cannot be repaired or when the
+    // garden file cannot be written.
+    if let Err(err) = repair_worktree(tree_type.as_ref(), &new_path, verbose)
+        .and_then(|_| config::writer::write_yaml(&doc, config_path))
+    {
+        let is_restored = std::fs::rename(&new_path, &old_path).is_ok()
+            && repair_worktree(tree_type.as_ref(), &old_path, verbose).is_ok();
+        if !is_restored {
+            eprintln!(
+                "{} unable to restore {} from {}",
+                "error:".red().bold(),
+                old_path.display(),
+                new_path.display()
+            );
+        }
+        return Err(err.into());
+    }
+
+    if !quiet {
+        println!("{} {} -> {}", "moved".yellow(), options.tree, relative_path);
+    }
+
+    Ok(())
+}
+
+/// Repair the links between parent repositories and their worktrees after a tree is moved.
+fn repair_worktree(
+    tree_type: Option<&model::GitTreeType>,
+    path: &std::path::Path,
+    verbose: u8,
+) -> Result<(), errors::GardenError> {
+    let path_str = path.to_string_lossy().to_string();
+    let repair = match tree_type {
+        Some(model::GitTreeType::Parent) => Some((vec!["git", "worktree", "repair"], path)),
+        Some(model::GitTreeType::Worktree(parent_path)) => Some((
+            vec!["git", "worktree", "repair", path_str.as_str()],
+            parent_path.as_path(),
+        )),
+        _ => None,
+    };
+    if let Some((command, repair_path)) = repair {
+        if verbose > 1 {
+            display::print_command_vec(&command);
+        }
+        cmd::result_from_exit_status(cmd::run_command(&command, repair_path))?;
+    }
+
+    Ok(())
+}
+
+/// Update the groups, gardens and trees that reference a renamed tree.
+fn rename_references(doc_hash: &mut yaml::Hash, old_name: &str, new_name: &str) {
+    let trees_key = Yaml::String(constants::TREES.to_string());
+    let groups_key = Yaml::String(constants::GROUPS.to_string());
+    if let Some(Yaml::Hash(ref mut groups)) = doc_hash.get_mut(&groups_key) {
+        for members in groups.values_mut() {
+            rename_member(members, old_name, new_name);
+        }
+    }
+    let gardens_key = Yaml::String(constants::GARDENS.to_string());
+    if let Some(Yaml::Hash(ref mut gardens)) = doc_hash.get_mut(&gardens_key) {
+        for garden in gardens.values_mut() {
+            if let Some(members) = garden
+                .as_mut_hash()
+                .and_then(|garden| garden.get_mut(&trees_key))
+            {
+                rename_member(members, old_name, new_name);
+            }
+        }
+    }
+    // Worktrees, dependencies and extended trees refer to other trees by name.
+    if let Some(Yaml::Hash(ref mut trees)) = doc_hash.get_mut(&trees_key) {
+        let worktree_key = Yaml::String(constants::WORKTREE.to_string());
+        let depends_key = Yaml::String(constants::DEPENDS.to_string());
+        let extend_key = Yaml::String(constants::EXTEND.to_string());
+        for entry in trees.values_mut() {
+            let Yaml::Hash(ref mut entry) = entry else {
+                continue;
+            };
+            for key in [&worktree_key, &depends_key, &extend_key] {
+                if let Some(value) = entry.get_mut(key) {
+                    rename_member(value, old_name, new_name);
+                }
+            }
+        }
+    }
+}
+
+/// Rename a tree in a group, garden or tree's list of tree names.
+fn rename_member(members: &mut Yaml, old_name: &str, new_name: &str) {
+    match members {
+        Yaml::String(member) if member == old_name => {
+            *members = Yaml::String(new_name.to_string());
+        }
+        Yaml::Array(array) => {
+            for member in array.iter_mut() {
+                if member.as_str() == Some(old_name) {
+                    *member = Yaml::String(new_name.to_string());
+                }
+            }
+        }
+        _ => (),
+    }
+}
@@ -2,22 +2,31 @@ use anyhow::Result;
 use clap::{Parser, ValueHint};
 use yaml_rust::{yaml, Yaml};
 
-use crate::{cmd, config, constants, errors, eval, git, model, path, query};
+use crate::{cmd, cmds, config, constants, errors, eval, git, model, path, query};
 
 // Add pre-existing worktrees to a garden configuration file
 #[derive(Parser, Clone, Debug)]
 #[command(author, about, long_about)]
 pub struct PlantOptions {
+    /// Add the planted trees to the specified group
+    #[arg(long, short)]
+    group: Option<String>,
+    /// Maximum depth to search for repositories when using "--recursive"
+    #[arg(long, short = 'd', default_value_t = -1, requires = "recursive")]
+    max_depth: isize,
     /// Garden configuration file to write [default: "garden.yaml"]
     #[arg(long, short)]
     output: Option<String>,
+    /// Search the specified directories for Git repositories to plant
+    #[arg(long, short)]
+    recursive: bool,
     /// Sort all trees after planting new trees
     #[arg(long, short)]
     sort: bool,
     /// Increase verbosity level (default: 0)
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
-    /// Trees to plant
+    /// Trees to plant, or directories to search when using "--recursive"
     #[arg(required = true, value_hint=ValueHint::DirPath)]
     paths: Vec<String>,
 }
@@ -36,6 +45,14 @@ pub fn main(app_context: &model::ApplicationContext, options: &PlantOptions) ->
     let trees_key = Yaml::String(constants::TREES.to_string());
     config::reader::add_section(constants::TREES, &mut doc)?;
 
+    // Search for repositories when "--recursive" is used.
+    let paths = if options.recursive {
+        discover_repositories(config, &options.paths, options.max_depth)
+    } else {
+        options.paths.clone()
+    };
+    let mut planted = Vec::new();
+
     // Mutable YAML scope.
     {
         // Get a mutable reference to top-level document hash.
@@ -52,13 +69,20 @@ pub fn main(app_context: &model::ApplicationContext, options: &PlantOptions) ->
                 error!("invalid trees: not a hash");
             }
         };
-        for path in &options.paths {
-            if let Err(msg) = plant_path(Some(app_context), config, verbose, path, trees) {
-                error!("{}", msg);
+        for path in &paths {
+            match plant_path(Some(app_context), config, verbose, path, trees) {
+                Ok(key) => planted.push(key),
+                Err(msg) => {
+                    error!("{}", msg);
+                }
             }
         }
     }
 
+    if let Some(group) = &options.group {
+        add_group_members(&mut doc, group, &planted)?;
+    }
+
     if options.sort {
         // Get a mutable reference to top-level document hash.
         let doc_hash: &mut yaml::Hash = match doc {
@@ -83,7 +107,7 @@ pub fn main(app_context: &model::ApplicationContext, options: &PlantOptions) ->
             }
         }
         // Sort trees case-insensitively.
-        names_and_trees.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()));
+        names_and_trees.sort_by_key(|a| a.0.to_lowercase());
         // Build a new trees table and replace the existing entry with it.
         let mut sorted_trees = yaml::Hash::new();
         for (name, tree) in names_and_trees {
@@ -132,11 +156,16 @@ pub(crate) fn plant_path(
             Some(tree_name) => tree_name,
             None => {
                 let relative_path = path::strip_prefix(&root, &parent_path)?;
-                return Err(errors::GardenError::WorktreeParentNotPlantedError {
-                    parent: relative_path,
-                    tree: raw_path.into(),
+                // Parents planted earlier in the same command are only present in "trees".
+                let parent_key = relative_path.to_string_lossy().to_string();
+                if !trees.contains_key(&Yaml::String(parent_key.to_string())) {
+                    return Err(errors::GardenError::WorktreeParentNotPlantedError {
+                        parent: relative_path,
+                        tree: raw_path.into(),
+                    }
+                    .into());
                 }
-                .into());
+                parent_key
             }
         };
     }
@@ -364,6 +393,107 @@ pub(crate) fn plant_path(
     Ok(key)
 }
 
+/// Search directories for Git repositories that are not already configured.
+/// Parent repositories are returned before their child worktrees so that the
+/// parents are planted first. Worktrees whose parent repository is neither configured
+/// nor discovered are skipped.
+fn discover_repositories(
+    config: &model::Configuration,
+    paths: &[String],
+    max_depth: isize,
+) -> Vec<String> {
+    // Existing trees are skipped during traversal.
+    let configured_tree_paths = cmds::prune::configured_tree_paths(config);
+    let path_filters = Vec::new();
+    let mut repositories = Vec::new();
+    for path in paths {
+        // The searched directory is planted when it is a repository itself.
+        let pathbuf = path::abspath(&std::path::PathBuf::from(path));
+        let is_repository = pathbuf.join(".git").exists()
+            || pathbuf
+                .extension()
+                .is_some_and(|extension| extension == "git");
+        if is_repository && !configured_tree_paths.contains(&pathbuf) {
+            repositories.push(pathbuf);
+        }
+        let (send_repo_path, recv_repo_path) = crossbeam::channel::unbounded();
+        let traverse_filesystem = cmds::prune::TraverseFilesystem {
+            min_depth: -1,
+            max_depth,
+            send_repo_path,
+            root_path: std::path::PathBuf::from(path),
+            path_filters: &path_filters,
+            configured_tree_paths: &configured_tree_paths,
+        };
+        traverse_filesystem.traverse();
+        for message in recv_repo_path.try_iter() {
+            match message {
+                cmds::prune::PathBufMessage::Path(pathbuf) => repositories.push(pathbuf),
+                cmds::prune::PathBufMessage::Finished => break,
+            }
+        }
+    }
+    repositories.sort();
+    repositories.dedup();
+
+    // Plant parent repositories before their worktrees.
+    let mut parents = Vec::new();
+    let mut worktrees = Vec::new();
+    for path in repositories {
+        match git::worktree_details(&path).map(|details| details.tree_type) {
+            Ok(model::GitTreeType::Worktree(parent_path)) => {
+                worktrees.push((path, path::abspath(&parent_path)));
+            }
+            _ => parents.push(path),
+        }
+    }
+    let mut result: Vec<String> = parents
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    for (path, parent_path) in worktrees {
+        if configured_tree_paths.contains(&parent_path) || parents.contains(&parent_path) {
+            result.push(path.to_string_lossy().to_string());
+        } else {
+            eprintln!(
+                "{}: skipped because its parent repository {} is not planted",
+                path.display(),
+                parent_path.display()
+            );
+        }
+    }
+
+    result
+}
+
+/// Create or extend a group with the specified tree names.
+fn add_group_members(doc: &mut Yaml, group: &str, members: &[Yaml]) -> Result<()> {
+    config::reader::add_section(constants::GROUPS, doc)?;
+    let Yaml::Hash(ref mut doc_hash) = doc else {
+        return Ok(());
+    };
+    let groups_key = Yaml::String(constants::GROUPS.to_string());
+    let Some(Yaml::Hash(ref mut groups)) = doc_hash.get_mut(&groups_key) else {
+        return Err(errors::GardenError::ConfigurationError(string!("groups: not a hash")).into());
+    };
+    let entry = groups
+        .entry(Yaml::String(group.to_string()))
+        .or_insert(Yaml::Array(Vec::new()));
+    // Groups can be specified as a single string. Promote them to a list.
+    if let Yaml::String(member) = entry.clone() {
+        *entry = Yaml::Array(vec![Yaml::String(member)]);
+    }
+    if let Yaml::Array(group_members) = entry {
+        for member in members {
+            if !group_members.contains(member) {
+                group_members.push(member.clone());
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Return the currently configured evaluated value for a git remote.
 fn get_url_for_remote(
     app_context: &model::ApplicationContext,
@@ -5,7 +5,7 @@ use clap::Parser;
 use rayon::prelude::*;
 use yansi::Paint;
 
-use crate::{cmd, errors, model, model::IndexSet};
+use crate::{cmd, cmds, config, errors, git, model, model::IndexSet};
 
 /// Remove unreferenced Git repositories
 #[derive(Parser, Clone, Debug)]
@@ -17,6 +17,21 @@ pub struct PruneOptions {
     /// Set the maximum prune depth
     #[arg(long, short = 'd', default_value_t = -1)]
     max_depth: isize,
+    /// List the repositories that would be pruned without prompting or deleting anything
+    #[arg(long, short = 'n')]
+    dry_run: bool,
+    /// Prune repositories with uncommitted or unpushed work when using "--no-prompt" (DANGER!)
+    #[arg(long)]
+    force_unsafe: bool,
+    /// Output format for "--dry-run" [text, json, yaml]
+    #[arg(
+        long,
+        default_value_t = model::OutputFormat::Text,
+        value_name = "FORMAT",
+        value_parser = model::OutputFormat::parse_from_str,
+        requires = "dry_run",
+    )]
+    format: model::OutputFormat,
     /// Only prune starting at the given depth
     #[arg(long, default_value_t = -1)]
     min_depth: isize,
@@ -29,6 +44,9 @@ pub struct PruneOptions {
     /// Enable deletion [default: deletion is disabled]
     #[arg(long = "rm")]
     remove: bool,
+    /// Move pruned repositories into the trash instead of deleting them
+    #[arg(long)]
+    trash: bool,
     /// Limit pruning to the specified subdirectories
     paths: Vec<String>,
 }
@@ -69,27 +87,25 @@ pub fn main(app_context: &model::ApplicationContext, options: &mut PruneOptions)
 /// PathBufMessage is sent across channels between the TraverseFilesystem,
 /// PromptUser and RemovePaths tasks. The Path variant contains a PathBuf to process and
 /// the Finished variant is used to signal the end of the message stream.
-
-enum PathBufMessage {
+pub(crate) enum PathBufMessage {
     Path(std::path::PathBuf),
     Finished,
 }
 
 /// TraverseFilesystem walks the filesystem and sends a PathBufMessage as it
 /// discovers Git repositories during its traversal.
-
-struct TraverseFilesystem<'a> {
-    min_depth: isize,
-    max_depth: isize,
-    send_repo_path: crossbeam::channel::Sender<PathBufMessage>,
-    root_path: std::path::PathBuf,
-    path_filters: &'a Vec<std::path::PathBuf>,
-    configured_tree_paths: &'a IndexSet<std::path::PathBuf>,
+pub(crate) struct TraverseFilesystem<'a> {
+    pub(crate) min_depth: isize,
+    pub(crate) max_depth: isize,
+    pub(crate) send_repo_path: crossbeam::channel::Sender<PathBufMessage>,
+    pub(crate) root_path: std::path::PathBuf,
+    pub(crate) path_filters: &'a Vec<std::path::PathBuf>,
+    pub(crate) configured_tree_paths: &'a IndexSet<std::path::PathBuf>,
 }
 
 impl TraverseFilesystem<'_> {
     /// Start a parallel traversal over the "paths" Vec.
-    fn traverse(&self) {
+    pub(crate) fn traverse(&self) {
         self.traverse_toplevel(&self.root_path).unwrap_or(());
         self.send_repo_path
             .send(PathBufMessage::Finished)
@@ -225,6 +241,8 @@ struct RemovePaths {
     send_finished_path: crossbeam::channel::Sender<PathBufMessage>,
     /// Dry-run mode does not actually perform deletions.
     dry_run: bool,
+    /// Trash mode moves paths into the trash instead of deleting them.
+    trash: bool,
 }
 
 impl RemovePaths {
@@ -234,23 +252,16 @@ impl RemovePaths {
             match self.recv_remove_path.recv() {
                 Ok(PathBufMessage::Path(pathbuf)) => {
                     // Remove paths from the filesystem and send a completion message.
-                    if !self.dry_run {
+                    // Paths are trashed in order so that the completion message is only
+                    // sent once the path has been moved into the trash.
+                    if self.trash {
+                        if let Err(err) = cmds::trash::trash_path(&pathbuf) {
+                            eprintln!("{} {err}", "error:".red().bold());
+                            continue;
+                        }
+                    } else if !self.dry_run {
                         let pathbuf = pathbuf.to_path_buf();
-                        remove_scope.spawn_fifo(move |_| {
-                            rm_rf::ensure_removed(&pathbuf).unwrap_or(());
-
-                            // Remove empty parent directorires leading up to this path.
-                            let mut parent_option = pathbuf.parent();
-                            while let Some(parent_pathbuf) = parent_option {
-                                if !parent_pathbuf.exists() {
-                                    break;
-                                }
-                                if std::fs::remove_dir(parent_pathbuf).is_err() {
-                                    break;
-                                }
-                                parent_option = parent_pathbuf.parent();
-                            }
-                        });
+                        remove_scope.spawn_fifo(move |_| remove_path(&pathbuf));
                     }
                     self.send_finished_path
                         .send(PathBufMessage::Path(pathbuf))
@@ -267,8 +278,94 @@ impl RemovePaths {
     }
 }
 
+/// Remove a repository and the empty parent directories leading up to it.
+pub(crate) fn remove_path(pathbuf: &std::path::Path) {
+    rm_rf::ensure_removed(pathbuf).unwrap_or(());
+
+    // Remove empty parent directorires leading up to this path.
+    let mut parent_option = pathbuf.parent();
+    while let Some(parent_pathbuf) = parent_option {
+        if !parent_pathbuf.exists() {
+            break;
+        }
+        if std::fs::remove_dir(parent_pathbuf).is_err() {
+            break;
+        }
+        parent_option = parent_pathbuf.parent();
+    }
+}
+
+/// Reasons why deleting a repository would lose work.
+pub(crate) enum Hazard {
+    /// The worktree has uncommitted changes.
+    Dirty,
+    /// The repository has stash entries.
+    Stashes(usize),
+    /// A local branch does not have an upstream branch.
+    NoUpstream(String),
+    /// Local branches have commits that are not reachable from any remote.
+    Unpushed(usize),
+    /// Other worktrees were created from this repository using "git worktree".
+    Worktrees,
+    /// The repository could not be inspected.
+    Unknown,
+}
+
+impl std::fmt::Display for Hazard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hazard::Dirty => write!(f, "uncommitted changes"),
+            Hazard::Stashes(1) => write!(f, "1 stash entry"),
+            Hazard::Stashes(count) => write!(f, "{count} stash entries"),
+            Hazard::NoUpstream(branch) => write!(f, "{branch} has no upstream branch"),
+            Hazard::Unpushed(1) => write!(f, "1 unpushed commit"),
+            Hazard::Unpushed(count) => write!(f, "{count} unpushed commits"),
+            Hazard::Worktrees => write!(f, "dependent worktrees"),
+            Hazard::Unknown => write!(f, "unable to inspect the repository"),
+        }
+    }
+}
+
+/// Inspect a repository for work that would be lost if it were deleted.
+pub(crate) fn hazards(path: &std::path::Path) -> Vec<Hazard> {
+    let mut hazards = Vec::new();
+    let mut is_unknown = false;
+    match git::status(path) {
+        Ok(status) => {
+            if status.is_dirty() {
+                hazards.push(Hazard::Dirty);
+            }
+            if status.stashes > 0 {
+                hazards.push(Hazard::Stashes(status.stashes));
+            }
+        }
+        Err(_) => is_unknown = true,
+    }
+    for (branch, upstream) in git::branch_upstreams(path) {
+        if upstream.is_none() {
+            hazards.push(Hazard::NoUpstream(branch));
+        }
+    }
+    match git::unpushed_commits(path) {
+        Some(0) => (),
+        Some(count) => hazards.push(Hazard::Unpushed(count)),
+        None => is_unknown = true,
+    }
+    // Repositories that cannot be analyzed are assumed to contain unsaved work.
+    if is_unknown {
+        hazards.push(Hazard::Unknown);
+    }
+    if git::worktree_details(path)
+        .is_ok_and(|details| details.tree_type == model::GitTreeType::Parent)
+    {
+        hazards.push(Hazard::Worktrees);
+    }
+
+    hazards
+}
+
 /// Responses from the prompt_for_deletion() return this enum.
-enum PromptResponse {
+pub(crate) enum PromptResponse {
     All,    // Delete all subsequent entries.
     Delete, // Delete the current entry.
     Skip,   // Skip the current entry.
@@ -276,7 +373,11 @@ enum PromptResponse {
 }
 
 /// Read input from stdin for whether or not we should delete the current path.
-fn prompt_for_deletion(pathbuf: &dyn AsRef<std::path::Path>) -> PromptResponse {
+/// Work that would be lost by deleting the repository is displayed before prompting.
+pub(crate) fn prompt_for_deletion(
+    pathbuf: &dyn AsRef<std::path::Path>,
+    hazards: &[Hazard],
+) -> PromptResponse {
     let stdin = std::io::stdin();
     let mut stdout = std::io::stdout();
     let answer;
@@ -291,6 +392,10 @@ fn prompt_for_deletion(pathbuf: &dyn AsRef<std::path::Path>) -> PromptResponse {
         println!();
         // # <path>
         println!("{} {}", "#".cyan(), path_string.blue().bold());
+        // UNSAFE: <hazard>
+        for hazard in hazards {
+            println!("{} {}", "UNSAFE:".red().bold(), hazard.to_string().yellow());
+        }
         // # Delete the "xyz" repository?
         println!(
             "{}",
@@ -356,6 +461,8 @@ struct PromptUser {
     send_remove_path: crossbeam::channel::Sender<PathBufMessage>,
     recv_finished_path: crossbeam::channel::Receiver<PathBufMessage>,
     no_prompt: bool,
+    force_unsafe: bool,
+    trash: bool,
     quit: bool,
 }
 
@@ -385,13 +492,19 @@ impl PromptUser {
     }
 
     fn prompt_pathbuf_for_deletion(&mut self, path: &dyn AsRef<std::path::Path>) {
+        // Repositories with unsaved work are analyzed before anything is removed.
+        let hazards = hazards(path.as_ref());
         if self.no_prompt {
+            if !hazards.is_empty() && !self.force_unsafe {
+                print_skipped_pathbuf(path.as_ref(), &hazards);
+                return;
+            }
             self.send_remove_path
                 .send(PathBufMessage::Path(path.as_ref().to_path_buf()))
                 .unwrap_or(());
             return;
         }
-        match prompt_for_deletion(&path) {
+        match prompt_for_deletion(&path, &hazards) {
             PromptResponse::All => {
                 self.no_prompt = true;
                 self.send_remove_path
@@ -421,20 +534,34 @@ impl PromptUser {
                 printed = true;
                 println!();
             }
-            print_deleted_pathbuf(&pathbuf);
+            self.print_finished_pathbuf(&pathbuf);
         }
     }
 
     /// Block and display all of the remaining "Deleted" messages.
     fn display_finished_blocking(&self) {
         while let Ok(PathBufMessage::Path(pathbuf)) = self.recv_finished_path.recv() {
-            print_deleted_pathbuf(&pathbuf);
+            self.print_finished_pathbuf(&pathbuf);
+        }
+    }
+
+    /// Print a path that was deleted or moved into the trash.
+    fn print_finished_pathbuf(&self, pathbuf: &std::path::Path) {
+        if self.trash {
+            println!(
+                "{} {}: {}",
+                "#".cyan(),
+                "Trashed".green(),
+                pathbuf.to_string_lossy().blue().bold(),
+            );
+        } else {
+            print_deleted_pathbuf(pathbuf);
         }
     }
 }
 
 /// Print a deleted path.
-fn print_deleted_pathbuf(pathbuf: &std::path::Path) {
+pub(crate) fn print_deleted_pathbuf(pathbuf: &std::path::Path) {
     println!(
         "{} {}: {}",
         "#".cyan(),
@@ -443,6 +570,110 @@ fn print_deleted_pathbuf(pathbuf: &std::path::Path) {
     );
 }
 
+/// Print a path that was skipped because it contains unsaved work.
+fn print_skipped_pathbuf(pathbuf: &std::path::Path, hazards: &[Hazard]) {
+    let reasons: Vec<String> = hazards.iter().map(|hazard| hazard.to_string()).collect();
+    println!(
+        "{} {}: {} ({})",
+        "#".cyan(),
+        "Skipped".yellow(),
+        pathbuf.to_string_lossy().blue().bold(),
+        reasons.join(", ").yellow(),
+    );
+}
+
+/// Return the canonical paths of the configured trees.
+pub(crate) fn configured_tree_paths(config: &model::Configuration) -> IndexSet<std::path::PathBuf> {
+    let mut configured_tree_paths = IndexSet::new();
+    for tree in config.trees.values() {
+        if let Some(pathbuf) = tree.canonical_pathbuf() {
+            configured_tree_paths.insert(pathbuf);
+        }
+    }
+
+    configured_tree_paths
+}
+
+/// List the unreferenced repositories along with their safety analysis.
+fn dry_run(config: &model::Configuration, options: &PruneOptions, paths: &[String]) -> Result<i32> {
+    cmd::initialize_threads(options.num_jobs)?;
+    let configured_tree_paths = configured_tree_paths(config);
+    let path_filters: Vec<std::path::PathBuf> = paths
+        .iter()
+        .map(|value| config.relative_pathbuf(value))
+        .collect();
+    let (send_repo_path, recv_repo_path) = crossbeam::channel::unbounded();
+    let traverse_filesystem = TraverseFilesystem {
+        min_depth: options.min_depth,
+        max_depth: options.max_depth,
+        send_repo_path,
+        root_path: config.root_path.to_path_buf(),
+        path_filters: &path_filters,
+        configured_tree_paths: &configured_tree_paths,
+    };
+    traverse_filesystem.traverse();
+    let mut repositories: Vec<std::path::PathBuf> = recv_repo_path
+        .try_iter()
+        .filter_map(|message| match message {
+            PathBufMessage::Path(pathbuf) => Some(pathbuf),
+            PathBufMessage::Finished => None,
+        })
+        .collect();
+    repositories.sort();
+
+    let results: Vec<(std::path::PathBuf, Vec<String>)> = repositories
+        .into_par_iter()
+        .map(|pathbuf| {
+            let reasons = hazards(&pathbuf)
+                .iter()
+                .map(|hazard| hazard.to_string())
+                .collect();
+            (pathbuf, reasons)
+        })
+        .collect();
+
+    if options.format == model::OutputFormat::Text {
+        for (pathbuf, reasons) in &results {
+            if reasons.is_empty() {
+                println!("{} {}", "#".cyan(), pathbuf.to_string_lossy().blue().bold());
+            } else {
+                println!(
+                    "{} {} {}",
+                    "#".cyan(),
+                    pathbuf.to_string_lossy().blue().bold(),
+                    format!("({})", reasons.join(", ")).yellow()
+                );
+            }
+        }
+        return Ok(errors::EX_OK);
+    }
+
+    let mut entries = yaml_rust::yaml::Array::new();
+    for (pathbuf, reasons) in results {
+        let mut entry = yaml_rust::yaml::Hash::new();
+        entry.insert(
+            yaml_rust::Yaml::String(string!("path")),
+            yaml_rust::Yaml::String(pathbuf.to_string_lossy().to_string()),
+        );
+        entry.insert(
+            yaml_rust::Yaml::String(string!("safe")),
+            yaml_rust::Yaml::Boolean(reasons.is_empty()),
+        );
+        entry.insert(
+            yaml_rust::Yaml::String(string!("hazards")),
+            yaml_rust::Yaml::Array(reasons.into_iter().map(yaml_rust::Yaml::String).collect()),
+        );
+        entries.push(yaml_rust::Yaml::Hash(entry));
+    }
+    let doc = yaml_rust::Yaml::Array(entries);
+    match options.format {
+        model::OutputFormat::Json => println!("{}", config::writer::json_string(&doc)),
+        _ => println!("{}", config::writer::yaml_string(&doc)),
+    }
+
+    Ok(errors::EX_OK)
+}
+
 /// Prune the garden config directory to remove trees that are no longer referenced
 /// by the garden file. This can be run when branches or trees have been removed.
 pub fn prune(
@@ -451,8 +682,11 @@ pub fn prune(
     paths: &[String],
 ) -> Result<i32> {
     let exit_status: i32 = 0;
+    if options.dry_run {
+        return dry_run(config, options, paths);
+    }
 
-    if !options.remove {
+    if !options.remove && !options.trash {
         let msg = "NOTE: Safe mode enabled. Repositories will not be deleted.";
         println!("{}", msg.green());
         let msg = "Use '--rm' to enable deletion.";
@@ -493,14 +727,7 @@ pub fn prune(
 
     // Existing trees are never removed. Create an IndexSet containing all of the current
     // tree paths so that we can skip them while traversing.
-    let mut configured_tree_paths = IndexSet::new();
-    {
-        for tree in config.trees.values() {
-            if let Some(pathbuf) = tree.canonical_pathbuf() {
-                configured_tree_paths.insert(pathbuf);
-            }
-        }
-    }
+    let configured_tree_paths = configured_tree_paths(config);
 
     let root_path = config.root_path.to_path_buf();
     let path_filters: Vec<std::path::PathBuf> = paths
@@ -516,6 +743,7 @@ pub fn prune(
                 recv_remove_path,
                 send_finished_path,
                 dry_run: !options.remove,
+                trash: options.trash,
             };
             remove_paths.remove_paths(remove_scope);
         });
@@ -527,6 +755,8 @@ pub fn prune(
                 send_remove_path,
                 recv_finished_path,
                 no_prompt: options.no_prompt,
+                force_unsafe: options.force_unsafe,
+                trash: options.trash,
                 quit,
             };
             prompt_user.prompt_for_deletion();
@@ -0,0 +1,230 @@
+use anyhow::Result;
+use clap::Parser;
+use yaml_rust::Yaml;
+use yansi::Paint;
+
+use crate::{
+    cmd, cmds::prune, config, constants, errors, git, model, model::StringSet, path, query,
+};
+
+/// Remove trees from the garden file
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct RmOptions {
+    /// Delete the tree checkouts from disk
+    #[arg(long)]
+    delete: bool,
+    /// Delete tree checkouts without prompting
+    #[arg(long, requires = "delete")]
+    no_prompt: bool,
+    /// Delete tree checkouts that contain uncommitted or unpushed work (DANGER!)
+    #[arg(long, requires = "delete")]
+    force_unsafe: bool,
+    /// Tree query for the gardens, groups or trees to remove
+    #[arg(required = true)]
+    queries: Vec<String>,
+}
+
+/// Main entry point for the "garden rm" command
+pub fn main(app_context: &model::ApplicationContext, options: &RmOptions) -> Result<()> {
+    let config = app_context.get_root_config();
+    let quiet = app_context.options.quiet;
+
+    // Gather the trees from the root garden file. Grafted trees are not removed.
+    let mut names = StringSet::new();
+    let mut trees: Vec<&model::Tree> = Vec::new();
+    for query in &options.queries {
+        for context in query::resolve_trees(app_context, config, None, query) {
+            if context.config.is_some() {
+                continue;
+            }
+            if let Some(tree) = config.trees.get(&context.tree) {
+                if names.insert(context.tree.to_string()) {
+                    trees.push(tree);
+                }
+            }
+        }
+    }
+    if trees.is_empty() {
+        return Err(errors::GardenError::TreeNotFound {
+            tree: options.queries.join(" "),
+        }
+        .into());
+    }
+
+    // Worktree parents cannot be removed while their worktrees remain configured.
+    for tree in &trees {
+        if let Some(child) = config.trees.values().find(|child| {
+            child.is_worktree
+                && !names.contains(child.get_name())
+                && &worktree_parent(app_context, child) == tree.get_name()
+        }) {
+            return Err(errors::GardenError::ConfigurationError(format!(
+                "{} is the worktree parent of {}",
+                tree.get_name(),
+                child.get_name()
+            ))
+            .into());
+        }
+    }
+
+    let config_path = config.get_path()?;
+    let mut doc = config::reader::read_yaml(config_path)?;
+    let trees_key = Yaml::String(constants::TREES.to_string());
+    for tree in &trees {
+        let is_defined = doc[constants::TREES]
+            .as_hash()
+            .is_some_and(|hash| hash.contains_key(&Yaml::String(tree.get_name().to_string())));
+        if !is_defined {
+            return Err(errors::GardenError::ConfigurationError(format!(
+                "{} is not defined in {}",
+                tree.get_name(),
+                config_path.display()
+            ))
+            .into());
+        }
+    }
+
+    // Check that the checkouts can be deleted safely and prompt for confirmation.
+    // Worktrees are visited before their parents so that a parent can be deleted
+    // along with all of its worktrees.
+    trees.sort_by_key(|tree| !tree.is_worktree);
+    let mut exit_status = errors::EX_OK;
+    let mut no_prompt = options.no_prompt;
+    let mut removed: Vec<&model::Tree> = Vec::new();
+    let mut delete_paths: Vec<(&model::Tree, std::path::PathBuf)> = Vec::new();
+    for tree in trees {
+        let path = tree
+            .pathbuf()
+            .filter(|path| path.exists() || path.is_symlink());
+        let Some(path) = path.filter(|_| options.delete) else {
+            removed.push(tree);
+            continue;
+        };
+        let hazards = if !tree.is_symlink {
+            let mut hazards = prune::hazards(&path);
+            // Worktrees that are being deleted do not prevent their parent from being deleted.
+            let is_deleting_worktrees = git::worktree_paths(&path).iter().all(|worktree| {
+                delete_paths
+                    .iter()
+                    .any(|(_, delete_path)| path::abspath(delete_path) == *worktree)
+            });
+            if is_deleting_worktrees {
+                hazards.retain(|hazard| !matches!(hazard, prune::Hazard::Worktrees));
+            }
+            if !hazards.is_empty() && !options.force_unsafe {
+                let reasons: Vec<String> =
+                    hazards.iter().map(|hazard| hazard.to_string()).collect();
+                eprintln!(
+                    "{} {}: refusing to delete {}: {} (use --force-unsafe to delete it anyway)",
+                    "error:".red().bold(),
+                    tree.get_name(),
+                    path.display(),
+                    reasons.join(", ")
+                );
+                exit_status = errors::EX_ERROR;
+                continue;
+            }
+            hazards
+        } else {
+            Vec::new()
+        };
+        if !no_prompt {
+            match prune::prompt_for_deletion(&path, &hazards) {
+                prune::PromptResponse::All => no_prompt = true,
+                prune::PromptResponse::Delete => (),
+                prune::PromptResponse::Skip => continue,
+                prune::PromptResponse::Quit => return Ok(()),
+            }
+        }
+        removed.push(tree);
+        delete_paths.push((tree, path));
+    }
+
+    // Remove the trees and their references from "groups" and "gardens".
+    if let Yaml::Hash(ref mut doc_hash) = doc {
+        if let Some(Yaml::Hash(ref mut trees)) = doc_hash.get_mut(&trees_key) {
+            for tree in &removed {
+                trees.remove(&Yaml::String(tree.get_name().to_string()));
+            }
+        }
+        let groups_key = Yaml::String(constants::GROUPS.to_string());
+        if let Some(Yaml::Hash(ref mut groups)) = doc_hash.get_mut(&groups_key) {
+            for members in groups.values_mut() {
+                for tree in &removed {
+                    remove_member(members, tree.get_name());
+                }
+            }
+        }
+        let gardens_key = Yaml::String(constants::GARDENS.to_string());
+        if let Some(Yaml::Hash(ref mut gardens)) = doc_hash.get_mut(&gardens_key) {
+            for garden in gardens.values_mut() {
+                if let Yaml::Hash(ref mut garden) = garden {
+                    if let Some(members) = garden.get_mut(&trees_key) {
+                        for tree in &removed {
+                            remove_member(members, tree.get_name());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    config::writer::write_yaml(&doc, config_path)?;
+    if !quiet {
+        for tree in &removed {
+            println!("{} {}", "removed".yellow(), tree.get_name());
+        }
+    }
+
+    // Delete the checkouts once the garden file has been updated.
+    for (tree, path) in &delete_paths {
+        if tree.is_symlink {
+            // The remaining checkouts are still deleted when a symlink cannot be removed.
+            if let Err(err) = std::fs::remove_file(path) {
+                eprintln!(
+                    "{} unable to remove {}: {err}",
+                    "error:".red().bold(),
+                    path.display()
+                );
+                exit_status = errors::EX_IOERR;
+                continue;
+            }
+        } else {
+            prune::remove_path(path);
+        }
+        // Prune the worktree's administrative files from its parent repository.
+        if tree.is_worktree {
+            if let Some(parent_path) = config
+                .trees
+                .get(&worktree_parent(app_context, tree))
+                .and_then(|parent| parent.canonical_pathbuf())
+            {
+                let command = ["git", "worktree", "prune"];
+                cmd::run_command(&command, &parent_path);
+            }
+        }
+        if !quiet {
+            prune::print_deleted_pathbuf(path);
+        }
+    }
+
+    // Return the last non-zero exit status.
+    cmd::result_from_exit_status(exit_status).map_err(|err| err.into())
+}
+
+/// Return the name of the parent tree that a worktree was created from.
+fn worktree_parent(app_context: &model::ApplicationContext, tree: &model::Tree) -> String {
+    let tree_context = model::TreeContext::new(tree.get_name(), None, None, None);
+    let eval_context = model::EvalContext::from_app_context(app_context, &tree_context);
+
+    tree.eval_worktree(&eval_context)
+}
+
+/// Remove a tree name from the list of trees in a group or garden.
+fn remove_member(members: &mut Yaml, name: &str) {
+    match members {
+        Yaml::String(member) if member == name => *members = Yaml::Array(Vec::new()),
+        Yaml::Array(array) => array.retain(|member| member.as_str() != Some(name)),
+        _ => (),
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::Result;
+use clap::Parser;
+use rayon::prelude::*;
+use yansi::Paint;
+
+use crate::{cmd, constants, display, errors, git, model, query};
+
+/// Report the Git status of trees
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct StatusOptions {
+    /// Exit with a non-zero status when any tree has uncommitted changes or errors
+    #[arg(long, default_value_t = false)]
+    check: bool,
+    /// Query Git status using parallel jobs
+    #[arg(
+        long = "jobs",
+        short = 'j',
+        require_equals = false,
+        num_args = 0..=1,
+        default_missing_value = "0",
+        value_name = "JOBS",
+    )]
+    num_jobs: Option<usize>,
+    /// Increase verbosity level (default: 0)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Filter trees by name post-query using a glob pattern
+    #[arg(long, short, default_value = "*")]
+    trees: String,
+    /// Tree query for the gardens, groups or trees to display
+    queries: Vec<String>,
+}
+
+/// The state of a tree as reported by "garden status".
+enum TreeState {
+    /// The tree has not been grown.
+    Missing,
+    /// The tree is a symlink to another tree.
+    Symlink,
+    /// The tree is a bare repository.
+    Bare,
+    /// The tree exists and "git status" was queried.
+    Status(model::GitStatus),
+    /// "git status" failed.
+    Error,
+}
+
+/// Main entry point for the "garden status" command
+pub fn main(app_context: &model::ApplicationContext, options: &mut StatusOptions) -> Result<()> {
+    if options.queries.is_empty() {
+        options.queries.push(string!("@*"));
+    }
+    cmd::initialize_threads_option(options.num_jobs)?;
+    status(app_context, options)
+}
+
+/// Query and display the status of each tree.
+fn status(app_context: &model::ApplicationContext, options: &StatusOptions) -> Result<()> {
+    let config = app_context.get_root_config();
+    let quiet = app_context.options.quiet;
+    let verbose = app_context.options.verbose + options.verbose;
+    if app_context
+        .options
+        .debug_level(constants::DEBUG_LEVEL_STATUS)
+        > 0
+    {
+        debug!("queries: {:?}", options.queries);
+    }
+
+    // Gather trees on the main thread. Only paths are sent to the worker threads.
+    let mut trees: Vec<&model::Tree> = Vec::new();
+    for query in &options.queries {
+        let contexts = query::resolve_and_filter_trees(app_context, config, query, &options.trees);
+        for context in &contexts {
+            let config = match context.config {
+                Some(config_id) => app_context.get_config(config_id),
+                None => config,
+            };
+            if let Some(tree) = config.trees.get(&context.tree) {
+                if tree.pathbuf().is_some() {
+                    trees.push(tree);
+                }
+            }
+        }
+    }
+    let paths: Vec<(std::path::PathBuf, bool, bool)> = trees
+        .iter()
+        .map(|tree| {
+            let path = tree.pathbuf().unwrap_or_default();
+            (path, tree.is_symlink, tree.is_bare_repository)
+        })
+        .collect();
+    let states: Vec<TreeState> = if options.num_jobs.is_some() {
+        paths
+            .par_iter()
+            .map(|(path, is_symlink, is_bare)| tree_state(path, *is_symlink, *is_bare))
+            .collect()
+    } else {
+        paths
+            .iter()
+            .map(|(path, is_symlink, is_bare)| tree_state(path, *is_symlink, *is_bare))
+            .collect()
+    };
+
+    let name_width = trees
+        .iter()
+        .map(|tree| tree.get_name().len())
+        .max()
+        .unwrap_or_default();
+    let branch_width = states
+        .iter()
+        .map(|state| match state {
+            TreeState::Status(status) => status.branch.len(),
+            _ => 0,
+        })
+        .max()
+        .unwrap_or_default();
+
+    // Trees that cannot be queried fail "--check" along with dirty trees.
+    let mut is_failed = false;
+    for (tree, state) in trees.iter().zip(states.iter()) {
+        match state {
+            TreeState::Status(status) => is_failed |= status.is_dirty(),
+            TreeState::Error => is_failed = true,
+            _ => (),
+        }
+        if quiet {
+            continue;
+        }
+        let path = tree.path_as_ref()?;
+        match state {
+            TreeState::Missing => display::print_missing_tree(tree, path, verbose),
+            TreeState::Symlink => display::print_symlink_tree_entry(tree, path, verbose),
+            TreeState::Bare => {
+                println!(
+                    "{} {} {}",
+                    "#".cyan(),
+                    format!("{:<name_width$}", tree.get_name()).blue().bold(),
+                    "(bare)".dim()
+                );
+            }
+            TreeState::Error => {
+                println!(
+                    "{} {} {}",
+                    "#".cyan(),
+                    format!("{:<name_width$}", tree.get_name()).blue().bold(),
+                    "(error)".red()
+                );
+            }
+            TreeState::Status(status) => {
+                print_status(tree, path, status, name_width, branch_width, verbose);
+            }
+        }
+    }
+
+    if options.check && is_failed {
+        return cmd::result_from_exit_status(errors::EX_ERROR).map_err(|err| err.into());
+    }
+
+    Ok(())
+}
+
+/// Query the state of a tree.
+fn tree_state(path: &std::path::Path, is_symlink: bool, is_bare: bool) -> TreeState {
+    if !path.exists() {
+        return TreeState::Missing;
+    }
+    if is_symlink {
+        return TreeState::Symlink;
+    }
+    if is_bare {
+        return TreeState::Bare;
+    }
+    match git::status(path) {
+        Ok(status) => TreeState::Status(status),
+        Err(_) => TreeState::Error,
+    }
+}
+
+/// Print a single aligned status row.
+fn print_status(
+    tree: &model::Tree,
+    path: &str,
+    status: &model::GitStatus,
+    name_width: usize,
+    branch_width: usize,
+    verbose: u8,
+) {
+    let name = format!("{:<name_width$}", tree.get_name());
+    let branch = format!("{:<branch_width$}", status.branch);
+    let tracking = if status.upstream.is_some() {
+        format!("↑{} ↓{}", status.ahead, status.behind)
+    } else {
+        string!("-")
+    };
+    let mut changes = Vec::new();
+    if status.staged > 0 {
+        changes.push(format!("{} staged", status.staged));
+    }
+    if status.modified > 0 {
+        changes.push(format!("{} modified", status.modified));
+    }
+    if status.conflicts > 0 {
+        changes.push(format!("{} conflicts", status.conflicts));
+    }
+    if status.untracked > 0 {
+        changes.push(format!("{} untracked", status.untracked));
+    }
+    let changes = if changes.is_empty() {
+        "clean".green().to_string()
+    } else {
+        changes.join(", ").yellow().to_string()
+    };
+    let mut row = format!(
+        "{} {} {} {:<9} {}",
+        "#".cyan(),
+        name.blue().bold(),
+        branch.green().bold(),
+        tracking,
+        changes,
+    );
+    if status.stashes > 0 {
+        row.push_str(&format!(
+            " {}",
+            format!("{} stashed", status.stashes).cyan()
+        ));
+    }
+    if let Some(pin) = tree.pin() {
+        let pathbuf = std::path::PathBuf::from(path);
+        row.push_str(&format!(" {}", display::display_pin(&pin, Some(&pathbuf))));
+    }
+    if tree.is_worktree {
+        row.push_str(&format!(" {}", "(worktree)".dim()));
+    }
+    if verbose > 0 {
+        row.push_str(&format!(" {}", path.blue()));
+    }
+    println!("{row}");
+}
@@ -0,0 +1,341 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use yaml_rust::{yaml, Yaml};
+use yansi::Paint;
+
+use crate::{cmds::prune, config, errors, git, model, path};
+
+/// Manage repositories that were moved into the trash by "garden prune --trash"
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct TrashOptions {
+    #[command(subcommand)]
+    command: TrashCommand,
+}
+
+/// Trash subcommands
+#[derive(Clone, Debug, Subcommand)]
+pub enum TrashCommand {
+    /// Permanently delete repositories from the trash
+    Empty(TrashEmptyOptions),
+    /// List the repositories in the trash
+    #[command(alias = "ls")]
+    List,
+    /// Restore a repository from the trash into its original location
+    Restore(TrashRestoreOptions),
+}
+
+/// Permanently delete repositories from the trash
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct TrashEmptyOptions {
+    /// Only delete repositories that were trashed longer ago than the specified duration
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    older_than: Option<std::time::Duration>,
+}
+
+/// Restore a repository from the trash into its original location
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct TrashRestoreOptions {
+    /// Trash ID or the original path of the repository to restore
+    entry: String,
+}
+
+/// Trash entries contain a manifest and the trashed repository.
+const MANIFEST: &str = "manifest.yaml";
+const REPOSITORY: &str = "repository";
+/// Manifest keys.
+const HEAD: &str = "head";
+const PATH: &str = "path";
+const TIMESTAMP: &str = "timestamp";
+
+/// A repository stored in the trash.
+struct TrashEntry {
+    /// The trash ID is the name of the entry's directory in the trash.
+    id: String,
+    /// The directory containing the manifest and the trashed repository.
+    dir: std::path::PathBuf,
+    /// The original location of the repository.
+    path: std::path::PathBuf,
+    /// The time when the repository was trashed in RFC 3339 format.
+    timestamp: String,
+    /// The commit that was checked out when the repository was trashed.
+    head: String,
+}
+
+impl TrashEntry {
+    /// Return the time when the repository was trashed.
+    fn time(&self) -> Option<std::time::SystemTime> {
+        humantime::parse_rfc3339(&self.timestamp).ok()
+    }
+}
+
+/// Main entry point for the "garden trash" command
+pub fn main(app_context: &model::ApplicationContext, options: &TrashOptions) -> Result<()> {
+    let quiet = app_context.options.quiet;
+    match &options.command {
+        TrashCommand::Empty(empty_options) => empty(empty_options, quiet),
+        TrashCommand::List => list(),
+        TrashCommand::Restore(restore_options) => restore(restore_options, quiet),
+    }
+}
+
+/// $XDG_DATA_HOME/garden/trash (typically ~/.local/share/garden/trash)
+fn trash_dir() -> std::path::PathBuf {
+    config::xdg_data_dir().join("trash")
+}
+
+/// Move a repository into the trash and return its trash ID.
+pub(crate) fn trash_path(pathbuf: &std::path::Path) -> Result<String, errors::GardenError> {
+    let pathbuf = path::abspath(pathbuf);
+    let head = git::commit_id(&pathbuf, "HEAD").unwrap_or_default();
+    let timestamp = humantime::format_rfc3339_seconds(std::time::SystemTime::now()).to_string();
+    let trash_dir = trash_dir();
+    std::fs::create_dir_all(&trash_dir).map_err(|err| {
+        errors::GardenError::IOError(format!("unable to create {trash_dir:?}: {err}"))
+    })?;
+
+    // Trash IDs are unique. A numeric suffix is added when an ID is already taken.
+    let basename = pathbuf
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let prefix: String = timestamp
+        .chars()
+        .filter(|c| *c != '-' && *c != ':')
+        .collect();
+    let base_id = format!("{prefix}-{basename}");
+    let mut id = base_id.clone();
+    let mut suffix = 1;
+    let entry_dir = loop {
+        let entry_dir = trash_dir.join(&id);
+        match std::fs::create_dir(&entry_dir) {
+            Ok(()) => break entry_dir,
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                id = format!("{base_id}-{suffix}");
+                suffix += 1;
+            }
+            Err(err) => {
+                return Err(errors::GardenError::IOError(format!(
+                    "unable to create {entry_dir:?}: {err}"
+                )));
+            }
+        }
+    };
+
+    let mut manifest = yaml::Hash::new();
+    manifest.insert(
+        Yaml::String(PATH.to_string()),
+        Yaml::String(pathbuf.to_string_lossy().to_string()),
+    );
+    manifest.insert(Yaml::String(TIMESTAMP.to_string()), Yaml::String(timestamp));
+    manifest.insert(Yaml::String(HEAD.to_string()), Yaml::String(head));
+    config::writer::write_yaml(&Yaml::Hash(manifest), entry_dir.join(MANIFEST))?;
+
+    match move_path(&pathbuf, &entry_dir.join(REPOSITORY)) {
+        Ok(()) => (),
+        Err(MoveError::NotMoved(err)) => {
+            rm_rf::ensure_removed(&entry_dir).unwrap_or(());
+            return Err(errors::GardenError::IOError(format!(
+                "unable to move {pathbuf:?} into the trash: {err}"
+            )));
+        }
+        // The trash entry is kept because it holds a complete copy of the repository.
+        Err(MoveError::SourceRemains(err)) => {
+            return Err(errors::GardenError::IOError(format!(
+                "{pathbuf:?} was copied into the trash as {id} but could not be removed: {err}"
+            )));
+        }
+    }
+    // Remove the empty parent directories leading up to the trashed repository.
+    prune::remove_path(&pathbuf);
+
+    Ok(id)
+}
+
+/// Errors returned by move_path().
+enum MoveError {
+    /// The source was not moved and the destination does not exist.
+    NotMoved(std::io::Error),
+    /// The source was copied to the destination but the source could not be removed.
+    SourceRemains(std::io::Error),
+}
+
+/// Move a directory, copying it when it is moved across filesystems.
+fn move_path(source: &std::path::Path, dest: &std::path::Path) -> Result<(), MoveError> {
+    match std::fs::rename(source, dest) {
+        Err(err) if err.kind() == std::io::ErrorKind::CrossesDevices => {
+            if let Err(err) = copy_path(source, dest) {
+                rm_rf::ensure_removed(dest).unwrap_or(());
+                return Err(MoveError::NotMoved(err));
+            }
+            // The copy is complete. It must not be removed if the source cannot be removed.
+            rm_rf::ensure_removed(source)
+                .map_err(|err| MoveError::SourceRemains(std::io::Error::other(err)))
+        }
+        result => result.map_err(MoveError::NotMoved),
+    }
+}
+
+/// Recursively copy a path while preserving symlinks.
+fn copy_path(source: &std::path::Path, dest: &std::path::Path) -> std::io::Result<()> {
+    let file_type = std::fs::symlink_metadata(source)?.file_type();
+    if file_type.is_dir() {
+        std::fs::create_dir(dest)?;
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            copy_path(&entry.path(), &dest.join(entry.file_name()))?;
+        }
+    } else if file_type.is_symlink() {
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(std::fs::read_link(source)?, dest)?;
+        #[cfg(not(unix))]
+        std::fs::copy(source, dest)?;
+    } else {
+        std::fs::copy(source, dest)?;
+    }
+
+    Ok(())
+}
+
+/// Read the trash entries ordered from oldest to newest.
+fn read_entries() -> Result<Vec<TrashEntry>> {
+    let trash_dir = trash_dir();
+    if !trash_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let dir_entries = std::fs::read_dir(&trash_dir).map_err(|err| {
+        errors::GardenError::IOError(format!("unable to read {trash_dir:?}: {err}"))
+    })?;
+    let mut entries = Vec::new();
+    for dir in dir_entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        let Ok(manifest) = config::reader::read_yaml(dir.join(MANIFEST)) else {
+            continue;
+        };
+        let Some(path) = manifest[PATH].as_str() else {
+            continue;
+        };
+        let id = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        entries.push(TrashEntry {
+            id,
+            path: std::path::PathBuf::from(path),
+            timestamp: manifest[TIMESTAMP].as_str().unwrap_or_default().to_string(),
+            head: manifest[HEAD].as_str().unwrap_or_default().to_string(),
+            dir,
+        });
+    }
+    // Trash IDs start with their timestamp so sorting by ID sorts by time.
+    entries.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(entries)
+}
+
+/// List the repositories in the trash.
+fn list() -> Result<()> {
+    let entries = read_entries()?;
+    let width = entries
+        .iter()
+        .map(|entry| entry.id.len())
+        .max()
+        .unwrap_or_default();
+    for entry in &entries {
+        let head = entry.head.get(..10).unwrap_or(&entry.head);
+        println!(
+            "{} {:width$} {} {} {}",
+            "#".cyan(),
+            entry.id.yellow(),
+            entry.timestamp.green(),
+            head,
+            entry.path.display().blue().bold(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Restore a repository from the trash into its original location.
+fn restore(options: &TrashRestoreOptions, quiet: bool) -> Result<()> {
+    let entries = read_entries()?;
+    // The most recently trashed repository is restored when matching by path.
+    let pathbuf = path::abspath(&path::current_dir().join(&options.entry));
+    let entry = entries
+        .iter()
+        .find(|entry| entry.id == options.entry)
+        .or_else(|| entries.iter().rev().find(|entry| entry.path == pathbuf))
+        .ok_or_else(|| {
+            errors::GardenError::ConfigurationError(format!(
+                "{}: not found in the trash",
+                options.entry
+            ))
+        })?;
+    if entry.path.exists() {
+        return Err(errors::GardenError::FileExists(format!(
+            "{} already exists",
+            entry.path.display()
+        ))
+        .into());
+    }
+    if let Some(parent) = entry.path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| {
+            errors::GardenError::OSError(format!("unable to create {parent:?}: {err}"))
+        })?;
+    }
+    match move_path(&entry.dir.join(REPOSITORY), &entry.path) {
+        Ok(()) => rm_rf::ensure_removed(&entry.dir).unwrap_or(()),
+        Err(MoveError::NotMoved(err)) => {
+            return Err(errors::GardenError::IOError(format!(
+                "unable to restore {} to {}: {err}",
+                entry.id,
+                entry.path.display()
+            ))
+            .into());
+        }
+        Err(MoveError::SourceRemains(err)) => {
+            return Err(errors::GardenError::IOError(format!(
+                "{} was restored to {} but could not be removed from the trash: {err}",
+                entry.id,
+                entry.path.display()
+            ))
+            .into());
+        }
+    }
+
+    if !quiet {
+        println!(
+            "{} {} -> {}",
+            "restored".green(),
+            entry.id,
+            entry.path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Permanently delete repositories from the trash.
+fn empty(options: &TrashEmptyOptions, quiet: bool) -> Result<()> {
+    let now = std::time::SystemTime::now();
+    for entry in read_entries()? {
+        if let Some(older_than) = options.older_than {
+            let is_expired = entry
+                .time()
+                .and_then(|time| now.duration_since(time).ok())
+                .is_some_and(|age| age >= older_than);
+            if !is_expired {
+                continue;
+            }
+        }
+        rm_rf::ensure_removed(&entry.dir).map_err(|err| {
+            errors::GardenError::IOError(format!("unable to remove {:?}: {err}", entry.dir))
+        })?;
+        if !quiet {
+            println!("{} {}", "removed".yellow(), entry.id);
+        }
+    }
+
+    Ok(())
+}
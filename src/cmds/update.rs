@@ -0,0 +1,421 @@
+use anyhow::Result;
+use clap::Parser;
+use rayon::prelude::*;
+use yansi::Paint;
+
+use crate::{
+    cmd, display, errors, git, model,
+    model::{IndexMap, IndexSet, StringSet},
+    query,
+};
+
+/// Fetch remotes and fast-forward trees to their upstream branches
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct UpdateOptions {
+    /// Update trees using parallel jobs
+    #[arg(
+        long = "jobs",
+        short = 'j',
+        require_equals = false,
+        num_args = 0..=1,
+        default_missing_value = "0",
+        value_name = "JOBS",
+    )]
+    num_jobs: Option<usize>,
+    /// Increase verbosity level (default: 0)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Filter trees by name post-query using a glob pattern
+    #[arg(long, short, default_value = "*")]
+    trees: String,
+    /// Tree query for the gardens, groups or trees to update
+    #[arg(required = true)]
+    queries: Vec<String>,
+}
+
+/// A checkout that is fast-forwarded to its upstream branch.
+struct Checkout {
+    name: String,
+    path: std::path::PathBuf,
+    branch: String,
+    branches: IndexMap<String, String>,
+    default_remote: String,
+    is_bare: bool,
+}
+
+/// A repository whose remotes are fetched once for all of its checkouts.
+/// Trees created using "git worktree" share a single repository.
+struct Repository {
+    path: std::path::PathBuf,
+    remotes: StringSet,
+    depth: i64,
+    checkouts: Vec<Checkout>,
+}
+
+/// The Git status and upstream branch for a checkout, or the exit status when
+/// "git status" fails. Missing checkouts are represented using None.
+type CheckoutState = Option<Result<(model::GitStatus, Option<String>), i32>>;
+
+/// The outcome of updating a single tree.
+enum UpdateStatus {
+    /// The branch was fast-forwarded by the specified number of commits.
+    Updated(usize),
+    /// The branch is already up to date.
+    UpToDate,
+    /// The branch has local commits that are not upstream.
+    Ahead(usize),
+    /// The remotes were fetched. Bare repositories have no branch to update.
+    Fetched,
+    /// Skipped: the tree has uncommitted changes.
+    Dirty,
+    /// Skipped: the branch and its upstream have diverged.
+    Diverged(usize, usize),
+    /// Skipped: HEAD is detached.
+    Detached,
+    /// Skipped: the branch has no upstream branch.
+    NoUpstream,
+    /// Skipped: the tree has not been grown.
+    Missing,
+    /// A Git command failed with the specified exit status.
+    Failed(i32, String),
+}
+
+impl display::SummaryStatus for UpdateStatus {
+    fn label(&self) -> &'static str {
+        match self {
+            UpdateStatus::Updated(_) => "updated",
+            UpdateStatus::UpToDate => "up-to-date",
+            UpdateStatus::Ahead(_) => "ahead",
+            UpdateStatus::Fetched => "fetched",
+            UpdateStatus::Missing => "missing",
+            UpdateStatus::Failed(_, _) => "failed",
+            UpdateStatus::Dirty
+            | UpdateStatus::Diverged(_, _)
+            | UpdateStatus::Detached
+            | UpdateStatus::NoUpstream => "skipped",
+        }
+    }
+
+    fn details(&self) -> String {
+        match self {
+            UpdateStatus::Updated(count) => commits_string(*count),
+            UpdateStatus::Ahead(count) => format!("{} not upstream", commits_string(*count)),
+            UpdateStatus::Dirty => string!("uncommitted changes"),
+            UpdateStatus::Diverged(ahead, behind) => {
+                format!("diverged: {ahead} ahead, {behind} behind")
+            }
+            UpdateStatus::Detached => string!("detached HEAD"),
+            UpdateStatus::NoUpstream => string!("no upstream branch"),
+            UpdateStatus::Failed(_, message) => message.to_string(),
+            UpdateStatus::UpToDate | UpdateStatus::Fetched | UpdateStatus::Missing => String::new(),
+        }
+    }
+
+    fn paint_label(&self, label: &str) -> String {
+        match self {
+            UpdateStatus::Updated(_) => label.green().bold().to_string(),
+            UpdateStatus::UpToDate | UpdateStatus::Fetched => label.green().to_string(),
+            UpdateStatus::Ahead(_) => label.cyan().to_string(),
+            UpdateStatus::Missing => label.red().dim().to_string(),
+            UpdateStatus::Failed(_, _) => label.red().bold().to_string(),
+            _ => label.yellow().to_string(),
+        }
+    }
+}
+
+/// Main entry point for the "garden update" command
+pub fn main(app_context: &model::ApplicationContext, options: &UpdateOptions) -> Result<()> {
+    cmd::initialize_threads_option(options.num_jobs)?;
+    let quiet = app_context.options.quiet;
+    let verbose = app_context.options.verbose + options.verbose;
+    let repositories = repositories(app_context, options);
+
+    let results: Vec<Vec<(String, UpdateStatus)>> = if options.num_jobs.is_some() {
+        repositories
+            .par_iter()
+            .map(|repository| update_repository(repository, quiet, verbose))
+            .collect()
+    } else {
+        repositories
+            .iter()
+            .map(|repository| update_repository(repository, quiet, verbose))
+            .collect()
+    };
+    let results: Vec<(String, UpdateStatus)> = results.into_iter().flatten().collect();
+
+    if !quiet {
+        display::print_summary(&results);
+    }
+
+    // Return the last non-zero exit status.
+    let mut exit_status = errors::EX_OK;
+    for (_, status) in &results {
+        if let UpdateStatus::Failed(status, _) = status {
+            exit_status = *status;
+        }
+    }
+
+    cmd::result_from_exit_status(exit_status).map_err(|err| err.into())
+}
+
+/// Evaluate the tree queries and group the resulting trees by repository.
+fn repositories(
+    app_context: &model::ApplicationContext,
+    options: &UpdateOptions,
+) -> Vec<Repository> {
+    let config = app_context.get_root_config();
+    let mut repositories: IndexMap<String, Repository> = IndexMap::new();
+    let mut seen: StringSet = IndexSet::new();
+
+    for query in &options.queries {
+        let contexts = query::resolve_and_filter_trees(app_context, config, query, &options.trees);
+        for context in &contexts {
+            let eval_context = model::EvalContext::from_app_context(app_context, context);
+            let tree_config = eval_context.graft_config.unwrap_or(config);
+            let Some(tree) = tree_config.trees.get(&context.tree) else {
+                continue;
+            };
+            let Some(path) = tree.pathbuf() else {
+                continue;
+            };
+            if tree.is_symlink || !seen.insert(path.to_string_lossy().to_string()) {
+                continue;
+            }
+
+            let mut remotes: StringSet = tree.remotes.keys().cloned().collect();
+            // Worktrees fetch the remotes configured in their parent tree.
+            if tree.is_worktree {
+                let worktree = tree.eval_worktree(&eval_context);
+                if let Some(parent_tree) = query::tree_from_name(
+                    tree_config,
+                    &worktree,
+                    context.garden.as_ref(),
+                    context.group.as_ref(),
+                )
+                .and_then(|parent_context| tree_config.trees.get(&parent_context.tree))
+                {
+                    remotes.extend(parent_tree.remotes.keys().cloned());
+                }
+            }
+            let branches = tree
+                .branches
+                .iter()
+                .map(|(branch, expr)| (branch.to_string(), eval_context.tree_variable(expr)))
+                .collect();
+            let checkout = Checkout {
+                name: tree.get_name().to_string(),
+                path: path.clone(),
+                branch: tree.eval_branch(&eval_context),
+                branches,
+                default_remote: tree.default_remote.to_string(),
+                is_bare: tree.is_bare_repository,
+            };
+
+            let shared_path = query::shared_worktree_path(app_context, config, context);
+            let repository =
+                repositories
+                    .entry(shared_path.clone())
+                    .or_insert_with(|| Repository {
+                        path: std::path::PathBuf::from(&shared_path),
+                        remotes: IndexSet::new(),
+                        depth: tree.clone_depth,
+                        checkouts: Vec::new(),
+                    });
+            repository.remotes.extend(remotes);
+            repository.depth = std::cmp::max(repository.depth, tree.clone_depth);
+            repository.checkouts.push(checkout);
+        }
+    }
+
+    repositories.into_values().collect()
+}
+
+/// Fetch the remotes for a repository and fast-forward each of its checkouts.
+fn update_repository(
+    repository: &Repository,
+    quiet: bool,
+    verbose: u8,
+) -> Vec<(String, UpdateStatus)> {
+    let mut results = Vec::with_capacity(repository.checkouts.len());
+    // Find the upstream branch for each checkout before fetching so that we know
+    // which remotes provide upstream branches.
+    let mut upstreams: Vec<CheckoutState> = Vec::with_capacity(repository.checkouts.len());
+    for checkout in &repository.checkouts {
+        if !checkout.path.exists() {
+            upstreams.push(None);
+        } else if checkout.is_bare {
+            upstreams.push(Some(Ok((model::GitStatus::default(), None))));
+        } else {
+            let result = git::status(&checkout.path)
+                .map(|status| {
+                    let upstream = upstream_branch(checkout, &status);
+                    (status, upstream)
+                })
+                .map_err(|err| match err {
+                    errors::CommandError::ExitStatus { status, .. } => status,
+                });
+            upstreams.push(Some(result));
+        }
+    }
+    let upstream_remotes: StringSet = upstreams
+        .iter()
+        .filter_map(|upstream| match upstream {
+            Some(Ok((_, Some(upstream)))) => upstream
+                .split_once('/')
+                .map(|(remote, _)| remote.to_string()),
+            _ => None,
+        })
+        .collect();
+
+    // Fetch from the shared repository path. Fallback to the first existing checkout
+    // when the worktree parent has not been selected or grown.
+    let fetch_path = if repository.path.exists() {
+        Some(repository.path.as_path())
+    } else {
+        repository
+            .checkouts
+            .iter()
+            .map(|checkout| checkout.path.as_path())
+            .find(|path| path.exists())
+    };
+    let mut failed_remotes: IndexMap<String, i32> = IndexMap::new();
+    if let Some(fetch_path) = fetch_path {
+        let mut remotes = repository.remotes.clone();
+        remotes.extend(upstream_remotes.iter().cloned());
+        for remote in &remotes {
+            let status = fetch(
+                fetch_path,
+                remote,
+                repository.depth,
+                upstream_remotes.contains(remote),
+                quiet,
+                verbose,
+            );
+            if status != errors::EX_OK {
+                failed_remotes.insert(remote.to_string(), status);
+            }
+        }
+    }
+
+    // Checkouts are not fast-forwarded when any of their remotes could not be fetched.
+    let fetch_failure = failed_remotes.first();
+    for (checkout, upstream) in repository.checkouts.iter().zip(upstreams) {
+        let status = match upstream {
+            None => UpdateStatus::Missing,
+            Some(Err(status)) => UpdateStatus::Failed(status, string!("git status failed")),
+            Some(Ok((status, upstream))) => match fetch_failure {
+                Some((remote, exit_status)) => {
+                    UpdateStatus::Failed(*exit_status, format!("unable to fetch {remote}"))
+                }
+                None if checkout.is_bare => UpdateStatus::Fetched,
+                None => fast_forward(checkout, &status, upstream, verbose),
+            },
+        };
+        results.push((checkout.name.to_string(), status));
+    }
+
+    results
+}
+
+/// Return the upstream branch for a checkout.
+/// The "branches" configuration takes precedence over the branch's configured upstream.
+/// The configured "branch" falls back to "<default-remote>/<branch>".
+fn upstream_branch(checkout: &Checkout, status: &model::GitStatus) -> Option<String> {
+    if status.is_detached || status.branch.is_empty() {
+        return None;
+    }
+    if let Some(remote_branch) = checkout.branches.get(&status.branch) {
+        if !remote_branch.is_empty() {
+            return Some(remote_branch.to_string());
+        }
+    }
+    if let Some(upstream) = &status.upstream {
+        return Some(upstream.to_string());
+    }
+    if status.branch == checkout.branch {
+        return Some(format!("{}/{}", checkout.default_remote, checkout.branch));
+    }
+
+    None
+}
+
+/// Fetch a remote. Shallow repositories fetch non-upstream remotes using the
+/// configured depth. Upstream remotes are fetched without a depth so that the
+/// fetched commits connect to the existing history and can be fast-forwarded.
+fn fetch(
+    path: &std::path::Path,
+    remote: &str,
+    depth: i64,
+    is_upstream: bool,
+    quiet: bool,
+    verbose: u8,
+) -> i32 {
+    let depth_opt = format!("--depth={depth}");
+    let mut command = vec!["git", "fetch"];
+    if quiet || verbose == 0 {
+        command.push("--quiet");
+    }
+    if depth > 0 && !is_upstream {
+        command.push(&depth_opt);
+    }
+    command.push(remote);
+    if verbose > 1 {
+        display::print_quoted_command(&command);
+    }
+
+    cmd::run_command(&command, path)
+}
+
+/// Fast-forward a checkout to its upstream branch when it is clean and has not diverged.
+fn fast_forward(
+    checkout: &Checkout,
+    status: &model::GitStatus,
+    upstream: Option<String>,
+    verbose: u8,
+) -> UpdateStatus {
+    if status.is_detached {
+        return UpdateStatus::Detached;
+    }
+    let Some(upstream) = upstream else {
+        return UpdateStatus::NoUpstream;
+    };
+    if !git::rev_exists(&checkout.path, &upstream) {
+        return UpdateStatus::NoUpstream;
+    }
+    let Some((ahead, behind)) = git::ahead_behind(&checkout.path, &upstream) else {
+        return UpdateStatus::Failed(errors::EX_ERROR, format!("unable to compare {upstream}"));
+    };
+    if behind == 0 {
+        if ahead > 0 {
+            return UpdateStatus::Ahead(ahead);
+        }
+        return UpdateStatus::UpToDate;
+    }
+    if ahead > 0 {
+        return UpdateStatus::Diverged(ahead, behind);
+    }
+    if status.is_dirty() {
+        return UpdateStatus::Dirty;
+    }
+
+    let command = ["git", "merge", "--quiet", "--ff-only", upstream.as_str()];
+    if verbose > 1 {
+        display::print_quoted_command(&command);
+    }
+    let exit_status = cmd::run_command(&command, &checkout.path);
+    if exit_status != errors::EX_OK {
+        return UpdateStatus::Failed(exit_status, format!("unable to merge {upstream}"));
+    }
+
+    UpdateStatus::Updated(behind)
+}
+
+/// Return a string describing a number of commits.
+fn commits_string(count: usize) -> String {
+    if count == 1 {
+        string!("1 commit")
+    } else {
+        format!("{count} commits")
+    }
+}
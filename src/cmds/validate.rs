@@ -0,0 +1,702 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use clap::Parser;
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser as YamlParser};
+use yaml_rust::scanner::Marker;
+use yaml_rust::{Yaml, YamlLoader};
+
+use crate::{constants, errors, eval, model, query, syntax};
+
+/// Check garden files for errors
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct ValidateOptions {}
+
+/// Keys allowed at the top level of a garden file.
+const TOP_LEVEL_KEYS: &[&str] = &[
+    constants::COMMANDS,
+    constants::ENVIRONMENT,
+    constants::GARDEN,
+    constants::GARDENS,
+    constants::GRAFTS,
+    constants::GROUPS,
+    constants::TEMPLATES,
+    constants::TREES,
+    constants::VARIABLES,
+];
+
+/// Keys allowed in the "garden" block.
+const GARDEN_KEYS: &[&str] = &[
+    constants::CACHE,
+    constants::CACHE_MODE,
+    constants::INCLUDES,
+    constants::INTERACTIVE_SHELL,
+    constants::ROOT,
+    constants::SHELL,
+    constants::SHELL_ERREXIT,
+    constants::SHELL_WORDSPLIT,
+    constants::TREE_BRANCHES,
+    constants::URL_REWRITES,
+];
+
+/// Keys allowed in tree and template entries.
+const TREE_KEYS: &[&str] = &[
+    constants::BARE,
+    constants::BRANCH,
+    constants::BRANCHES,
+    constants::COMMANDS,
+    constants::COMMIT,
+    constants::DEFAULT_REMOTE,
+    constants::DEPENDS,
+    constants::DEPTH,
+    constants::DESCRIPTION,
+    constants::ENVIRONMENT,
+    constants::EXTEND,
+    constants::FILTER,
+    constants::GITCONFIG,
+    constants::LFS,
+    constants::LINKS,
+    constants::PATH,
+    constants::REMOTES,
+    constants::REPLACE,
+    constants::SINGLE_BRANCH,
+    constants::SPARSE_CHECKOUT,
+    constants::SUBMODULES,
+    constants::SYMLINK,
+    constants::TAG,
+    constants::TEMPLATES,
+    constants::URL,
+    constants::VARIABLES,
+    constants::WORKTREE,
+];
+
+/// Keys allowed in garden entries.
+const GARDEN_ENTRY_KEYS: &[&str] = &[
+    constants::COMMANDS,
+    constants::ENVIRONMENT,
+    constants::GITCONFIG,
+    constants::GROUPS,
+    constants::TREES,
+    constants::VARIABLES,
+];
+
+/// Keys allowed in graft entries.
+const GRAFT_KEYS: &[&str] = &[constants::CONFIG, constants::ROOT];
+
+/// Tree fields that contain a single expression or a list of expressions.
+const TREE_EXPRESSION_KEYS: &[&str] = &[
+    constants::BRANCH,
+    constants::COMMIT,
+    constants::DEFAULT_REMOTE,
+    constants::LINKS,
+    constants::PATH,
+    constants::SYMLINK,
+    constants::TAG,
+    constants::URL,
+    constants::WORKTREE,
+];
+
+/// Tree fields that contain a map of expressions.
+const TREE_EXPRESSION_MAP_KEYS: &[&str] = &[
+    constants::BRANCHES,
+    constants::ENVIRONMENT,
+    constants::GITCONFIG,
+    constants::REMOTES,
+    constants::VARIABLES,
+];
+
+/// Garden fields that contain a map of expressions.
+const GARDEN_EXPRESSION_MAP_KEYS: &[&str] = &[
+    constants::ENVIRONMENT,
+    constants::GITCONFIG,
+    constants::VARIABLES,
+];
+
+/// A problem found in a garden file.
+struct Problem {
+    path: std::path::PathBuf,
+    line: Option<usize>,
+    message: String,
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+/// A parsed garden file along with the line numbers of its keys.
+struct SourceFile {
+    path: std::path::PathBuf,
+    doc: Yaml,
+    lines: HashMap<Vec<String>, usize>,
+}
+
+impl SourceFile {
+    /// Return a problem located at the specified key.
+    fn problem(&self, keys: &[&str], message: &str) -> Problem {
+        let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+        Problem {
+            path: self.path.clone(),
+            line: self.lines.get(&keys).copied(),
+            message: format!("{}: {}", keys.join("."), message),
+        }
+    }
+
+    /// Return true when the file defines the specified key.
+    fn contains(&self, keys: &[&str]) -> bool {
+        let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+        self.lines.contains_key(&keys)
+    }
+}
+
+/// A frame in the stack of YAML collections being parsed.
+enum Frame {
+    /// A mapping and the key whose value is currently being parsed.
+    Mapping(Option<String>),
+    /// A sequence and the index of the entry currently being parsed.
+    Sequence(usize),
+}
+
+/// Record the line number of every mapping key in a YAML document.
+#[derive(Default)]
+struct KeyLines {
+    documents: usize,
+    frames: Vec<Frame>,
+    lines: HashMap<Vec<String>, usize>,
+}
+
+impl KeyLines {
+    /// Return the keys leading to the value currently being parsed.
+    fn keys(&self) -> Vec<String> {
+        self.frames
+            .iter()
+            .filter_map(|frame| match frame {
+                Frame::Mapping(key) => key.clone(),
+                Frame::Sequence(idx) => Some(idx.to_string()),
+            })
+            .collect()
+    }
+
+    /// Advance the current collection once a value has been parsed.
+    fn end_value(&mut self) {
+        match self.frames.last_mut() {
+            Some(Frame::Mapping(key)) => *key = None,
+            Some(Frame::Sequence(idx)) => *idx += 1,
+            None => (),
+        }
+    }
+
+    /// Record a scalar value. Scalars are keys when a mapping is expecting a key.
+    fn scalar(&mut self, value: String, marker: Marker) {
+        if let Some(Frame::Mapping(None)) = self.frames.last() {
+            let mut keys = self.keys();
+            keys.push(value.clone());
+            self.lines.entry(keys).or_insert(marker.line());
+            self.frames.pop();
+            self.frames.push(Frame::Mapping(Some(value)));
+        } else {
+            self.end_value();
+        }
+    }
+}
+
+impl MarkedEventReceiver for KeyLines {
+    fn on_event(&mut self, event: Event, marker: Marker) {
+        match event {
+            Event::DocumentStart => self.documents += 1,
+            // Only the first document is used by garden.
+            _ if self.documents > 1 => (),
+            Event::MappingStart(..) => self.frames.push(Frame::Mapping(None)),
+            Event::SequenceStart(..) => self.frames.push(Frame::Sequence(0)),
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.frames.pop();
+                self.end_value();
+            }
+            Event::Scalar(value, ..) => self.scalar(value, marker),
+            Event::Alias(_) => self.scalar(String::new(), marker),
+            _ => (),
+        }
+    }
+}
+
+/// Main entry point for the "garden validate" command.
+pub fn main(app_context: &model::ApplicationContext, _options: &ValidateOptions) -> Result<()> {
+    let mut problems = Vec::new();
+    validate_config(app_context, app_context.get_root_id(), &mut problems)?;
+    for problem in &problems {
+        println!("{problem}");
+    }
+    match problems.len() {
+        0 => Ok(()),
+        1 => Err(errors::GardenError::ConfigurationError("1 problem found".into()).into()),
+        count => {
+            Err(errors::GardenError::ConfigurationError(format!("{count} problems found")).into())
+        }
+    }
+}
+
+/// Validate a configuration, its includes and its grafts.
+fn validate_config(
+    app_context: &model::ApplicationContext,
+    config_id: model::ConfigId,
+    problems: &mut Vec<Problem>,
+) -> Result<(), errors::GardenError> {
+    let config = app_context.get_config(config_id);
+    let path = match config.path.as_ref() {
+        Some(path) if path.exists() => path.to_path_buf(),
+        _ => {
+            return Err(errors::GardenError::ConfigurationError(
+                "unable to find a garden file".into(),
+            ))
+        }
+    };
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    let mut config_problems = Vec::new();
+    read_source_files(
+        app_context,
+        config,
+        &path,
+        None,
+        &mut files,
+        &mut visited,
+        &mut config_problems,
+    );
+
+    let variables = defined_variables(app_context, config);
+    for file in &files {
+        check_keys(file, &mut config_problems);
+        check_references(app_context, config, file, &mut config_problems);
+        check_variables(file, &variables, &mut config_problems);
+    }
+    check_tree_paths(config, &files, &mut config_problems);
+    check_dependencies(config, &files, &mut config_problems);
+
+    // Report problems in the order that they appear in each file.
+    config_problems.sort_by_key(|problem| {
+        let file_idx = files.iter().position(|file| file.path == problem.path);
+        (file_idx, problem.line)
+    });
+    problems.append(&mut config_problems);
+
+    for graft in config.grafts.values() {
+        if let Some(graft_id) = graft.get_id() {
+            validate_config(app_context, graft_id, problems)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a garden file and the files that it includes.
+fn read_source_files(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    path: &std::path::Path,
+    include_path: Option<&std::path::Path>,
+    files: &mut Vec<SourceFile>,
+    visited: &mut HashSet<std::path::PathBuf>,
+    problems: &mut Vec<Problem>,
+) {
+    let canonical_path = path.canonicalize().unwrap_or(path.to_path_buf());
+    if !visited.insert(canonical_path) {
+        return;
+    }
+    let problem = |message: String| Problem {
+        path: path.to_path_buf(),
+        line: None,
+        message,
+    };
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) => {
+            problems.push(problem(err.to_string()));
+            return;
+        }
+    };
+    let doc = match YamlLoader::load_from_str(&content) {
+        Ok(mut docs) if !docs.is_empty() => docs.swap_remove(0),
+        Ok(_) => return,
+        Err(err) => {
+            problems.push(problem(err.to_string()));
+            return;
+        }
+    };
+    let mut key_lines = KeyLines::default();
+    let mut parser = YamlParser::new_from_str(&content);
+    if let Err(err) = parser.load(&mut key_lines, false) {
+        problems.push(problem(err.to_string()));
+    }
+    let file = SourceFile {
+        path: path.to_path_buf(),
+        doc,
+        lines: key_lines.lines,
+    };
+
+    let mut includes = Vec::new();
+    for expr in yaml_strings(&file.doc[constants::GARDEN][constants::INCLUDES]) {
+        let Some(include) =
+            config.eval_config_pathbuf_from_include(app_context, include_path, expr)
+        else {
+            continue;
+        };
+        if include.exists() {
+            includes.push(include);
+        } else {
+            let message = format!("include not found: {expr}");
+            problems.push(file.problem(&[constants::GARDEN, constants::INCLUDES], &message));
+        }
+    }
+    files.push(file);
+
+    for include in &includes {
+        read_source_files(
+            app_context,
+            config,
+            include,
+            Some(include),
+            files,
+            visited,
+            problems,
+        );
+    }
+}
+
+/// Report unknown keys.
+fn check_keys(file: &SourceFile, problems: &mut Vec<Problem>) {
+    check_hash_keys(file, &file.doc, &[], TOP_LEVEL_KEYS, problems);
+    check_hash_keys(
+        file,
+        &file.doc[constants::GARDEN],
+        &[constants::GARDEN],
+        GARDEN_KEYS,
+        problems,
+    );
+    let sections = [
+        (constants::GARDENS, GARDEN_ENTRY_KEYS),
+        (constants::GRAFTS, GRAFT_KEYS),
+        (constants::TEMPLATES, TREE_KEYS),
+        (constants::TREES, TREE_KEYS),
+    ];
+    for (section, known_keys) in sections {
+        for (name, value) in yaml_entries(&file.doc[section]) {
+            check_hash_keys(file, value, &[section, name], known_keys, problems);
+        }
+    }
+}
+
+/// Report keys in a hash that are not in the list of known keys.
+fn check_hash_keys(
+    file: &SourceFile,
+    yaml: &Yaml,
+    parent_keys: &[&str],
+    known_keys: &[&str],
+    problems: &mut Vec<Problem>,
+) {
+    for (key, _) in yaml_entries(yaml) {
+        if !known_keys.contains(&key) {
+            let keys = [parent_keys, &[key]].concat();
+            problems.push(file.problem(&keys, "unknown key"));
+        }
+    }
+}
+
+/// Report references to templates, trees and groups that do not exist.
+fn check_references(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    file: &SourceFile,
+    problems: &mut Vec<Problem>,
+) {
+    let doc = &file.doc;
+    for (name, value) in yaml_entries(&doc[constants::TEMPLATES]) {
+        let keys = [constants::TEMPLATES, name, constants::EXTEND];
+        for template in yaml_strings(&value[constants::EXTEND]) {
+            if !config.templates.contains_key(template) {
+                problems.push(file.problem(&keys, &format!("unknown template: {template}")));
+            }
+        }
+    }
+    for (name, value) in yaml_entries(&doc[constants::TREES]) {
+        let keys = [constants::TREES, name, constants::TEMPLATES];
+        for template in yaml_strings(&value[constants::TEMPLATES]) {
+            if !config.templates.contains_key(template) {
+                problems.push(file.problem(&keys, &format!("unknown template: {template}")));
+            }
+        }
+        let keys = [constants::TREES, name, constants::EXTEND];
+        if let Some(base) = value[constants::EXTEND].as_str() {
+            if !config.trees.contains_key(base) {
+                problems.push(file.problem(&keys, &format!("unknown tree: {base}")));
+            }
+        }
+        let keys = [constants::TREES, name, constants::WORKTREE];
+        if let Some(expr) = value[constants::WORKTREE].as_str() {
+            let parent = eval::value(app_context, config, expr);
+            if !parent.is_empty() && !config.trees.contains_key(&parent) {
+                problems.push(file.problem(&keys, &format!("unknown tree: {parent}")));
+            }
+        }
+        let keys = [constants::TREES, name, constants::DEPENDS];
+        for dependency in yaml_strings(&value[constants::DEPENDS]) {
+            if !config.trees.contains_key(dependency) {
+                problems.push(file.problem(&keys, &format!("unknown tree: {dependency}")));
+            }
+        }
+    }
+    for (name, value) in yaml_entries(&doc[constants::GROUPS]) {
+        for tree in yaml_strings(value) {
+            if !matches_tree(app_context, config, tree) {
+                let message = format!("no trees match: {tree}");
+                problems.push(file.problem(&[constants::GROUPS, name], &message));
+            }
+        }
+    }
+    for (name, value) in yaml_entries(&doc[constants::GARDENS]) {
+        let keys = [constants::GARDENS, name, constants::GROUPS];
+        for group in yaml_strings(&value[constants::GROUPS]) {
+            if !matches_group(app_context, config, group) {
+                problems.push(file.problem(&keys, &format!("no groups match: {group}")));
+            }
+        }
+        let keys = [constants::GARDENS, name, constants::TREES];
+        for tree in yaml_strings(&value[constants::TREES]) {
+            if !matches_tree(app_context, config, tree) {
+                problems.push(file.problem(&keys, &format!("no trees match: {tree}")));
+            }
+        }
+    }
+}
+
+/// Return true when a tree name, pattern or path matches at least one tree.
+fn matches_tree(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    tree: &str,
+) -> bool {
+    if syntax::is_graft(tree) {
+        return match config.get_graft_id(tree) {
+            Ok((graft_id, remainder)) => {
+                matches_tree(app_context, app_context.get_config(graft_id), remainder)
+            }
+            Err(_) => false,
+        };
+    }
+    let matches_name = match glob::Pattern::new(tree) {
+        Ok(pattern) => config.trees.keys().any(|name| pattern.matches(name)),
+        Err(_) => false,
+    };
+
+    matches_name || query::tree_from_path(config, tree).is_some()
+}
+
+/// Return true when a group name or pattern matches at least one group.
+fn matches_group(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    group: &str,
+) -> bool {
+    if syntax::is_graft(group) {
+        return match config.get_graft_id(group) {
+            Ok((graft_id, remainder)) => {
+                matches_group(app_context, app_context.get_config(graft_id), remainder)
+            }
+            Err(_) => false,
+        };
+    }
+    match glob::Pattern::new(group) {
+        Ok(pattern) => config.groups.keys().any(|name| pattern.matches(name)),
+        Err(_) => false,
+    }
+}
+
+/// Return the names of all of the variables that can be referenced by expressions.
+fn defined_variables(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+) -> HashSet<String> {
+    let mut names: HashSet<String> = std::env::vars_os()
+        .map(|(name, _)| name.to_string_lossy().to_string())
+        .collect();
+    names.insert(string!(constants::TREE_NAME));
+    names.insert(string!(constants::TREE_PATH));
+
+    let environment_names = |environment: &Vec<model::MultiVariable>| {
+        environment
+            .iter()
+            .map(|var| var.get_name().trim_end_matches(['+', '=']).to_string())
+            .collect::<Vec<_>>()
+    };
+    let mut current_config = Some(config);
+    while let Some(cfg) = current_config {
+        names.extend(cfg.variables.keys().cloned());
+        names.extend(cfg.override_variables.keys().cloned());
+        names.extend(environment_names(&cfg.environment));
+        for tree in cfg.trees.values() {
+            names.extend(tree.variables.keys().cloned());
+            names.extend(environment_names(&tree.environment));
+        }
+        for template in cfg.templates.values() {
+            names.extend(template.tree.variables.keys().cloned());
+        }
+        for garden in cfg.gardens.values() {
+            names.extend(garden.variables.keys().cloned());
+            names.extend(environment_names(&garden.environment));
+        }
+        current_config = cfg.parent_id.map(|id| app_context.get_config(id));
+    }
+
+    names
+}
+
+/// Report `${variable}` references to variables that are not defined.
+fn check_variables(file: &SourceFile, variables: &HashSet<String>, problems: &mut Vec<Problem>) {
+    let doc = &file.doc;
+    let mut expressions: Vec<(Vec<&str>, &str)> = Vec::new();
+    let garden = &doc[constants::GARDEN];
+    for key in [constants::ROOT, constants::INCLUDES] {
+        for expr in yaml_strings(&garden[key]) {
+            expressions.push((vec![constants::GARDEN, key], expr));
+        }
+    }
+    for key in [constants::VARIABLES, constants::ENVIRONMENT] {
+        collect_map_expressions(&doc[key], &[key], &mut expressions);
+    }
+    for (name, value) in yaml_entries(&doc[constants::GRAFTS]) {
+        for expr in yaml_strings(value) {
+            expressions.push((vec![constants::GRAFTS, name], expr));
+        }
+        for key in [constants::CONFIG, constants::ROOT] {
+            for expr in yaml_strings(&value[key]) {
+                expressions.push((vec![constants::GRAFTS, name, key], expr));
+            }
+        }
+    }
+    for section in [constants::TEMPLATES, constants::TREES] {
+        for (name, value) in yaml_entries(&doc[section]) {
+            // Trees and templates can be specified using a single URL string.
+            for expr in yaml_strings(value) {
+                expressions.push((vec![section, name], expr));
+            }
+            for key in TREE_EXPRESSION_KEYS {
+                for expr in yaml_strings(&value[*key]) {
+                    expressions.push((vec![section, name, key], expr));
+                }
+            }
+            for key in TREE_EXPRESSION_MAP_KEYS {
+                collect_map_expressions(&value[*key], &[section, name, key], &mut expressions);
+            }
+        }
+    }
+    for (name, value) in yaml_entries(&doc[constants::GARDENS]) {
+        for key in GARDEN_EXPRESSION_MAP_KEYS {
+            let keys = [constants::GARDENS, name, key];
+            collect_map_expressions(&value[*key], &keys, &mut expressions);
+        }
+    }
+
+    for (keys, expr) in &expressions {
+        for name in syntax::variable_references(expr) {
+            // Graft variables are resolved through the graft's configuration.
+            if syntax::is_graft(name) || variables.contains(name) {
+                continue;
+            }
+            problems.push(file.problem(keys, &format!("undefined variable: {name}")));
+        }
+    }
+}
+
+/// Collect the expressions from a hash whose values are strings or lists of strings.
+fn collect_map_expressions<'a>(
+    yaml: &'a Yaml,
+    parent_keys: &[&'a str],
+    expressions: &mut Vec<(Vec<&'a str>, &'a str)>,
+) {
+    for (name, value) in yaml_entries(yaml) {
+        for expr in yaml_strings(value) {
+            expressions.push(([parent_keys, &[name]].concat(), expr));
+        }
+    }
+}
+
+/// Report trees that are configured to use the same path.
+fn check_tree_paths(
+    config: &model::Configuration,
+    files: &[SourceFile],
+    problems: &mut Vec<Problem>,
+) {
+    let mut paths: HashMap<&str, &str> = HashMap::new();
+    for (name, tree) in &config.trees {
+        let Ok(path) = tree.path_as_ref() else {
+            continue;
+        };
+        match paths.get(path.as_str()) {
+            Some(other) => {
+                let message = format!("path is also used by {other}: {path}");
+                problems.push(locate(files, &[constants::TREES, name], &message));
+            }
+            None => {
+                paths.insert(path, name);
+            }
+        }
+    }
+}
+
+/// Report cycles in the "depends" graph.
+fn check_dependencies(
+    config: &model::Configuration,
+    files: &[SourceFile],
+    problems: &mut Vec<Problem>,
+) {
+    let mut checked = HashSet::new();
+    for name in config.trees.keys() {
+        let mut stack = Vec::new();
+        if let Err(errors::GardenError::ConfigurationError(message)) =
+            query::check_dependency_cycles(config, name, &mut stack, &mut checked)
+        {
+            let keys = [constants::TREES, name, constants::DEPENDS];
+            problems.push(locate(files, &keys, &message));
+            // Every tree in the cycle would report the same cycle.
+            break;
+        }
+    }
+}
+
+/// Return a problem located in the first file that defines the specified key.
+fn locate(files: &[SourceFile], keys: &[&str], message: &str) -> Problem {
+    files
+        .iter()
+        .find(|file| file.contains(keys))
+        .or(files.first())
+        .map(|file| file.problem(keys, message))
+        .unwrap_or_else(|| Problem {
+            path: std::path::PathBuf::from(constants::GARDEN_CONFIG),
+            line: None,
+            message: message.to_string(),
+        })
+}
+
+/// Return the string-keyed entries of a `Yaml::Hash`.
+fn yaml_entries(yaml: &Yaml) -> Vec<(&str, &Yaml)> {
+    match yaml {
+        Yaml::Hash(hash) => hash
+            .iter()
+            .filter_map(|(key, value)| key.as_str().map(|key| (key, value)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Return the strings from a `Yaml::String` or `Yaml::Array<Yaml::String>`.
+fn yaml_strings(yaml: &Yaml) -> Vec<&str> {
+    match yaml {
+        Yaml::String(value) => vec![value.as_str()],
+        Yaml::Array(values) => values.iter().filter_map(Yaml::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
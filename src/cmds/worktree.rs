@@ -0,0 +1,391 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand, ValueHint};
+use yaml_rust::{yaml, Yaml};
+
+use crate::{cmd, config, constants, display, errors, git, model, model::IndexMap, path, query};
+
+/// Add, remove and list worktree trees
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct WorktreeOptions {
+    #[command(subcommand)]
+    command: WorktreeCommand,
+}
+
+/// Worktree subcommands
+#[derive(Clone, Debug, Subcommand)]
+pub enum WorktreeCommand {
+    /// Create a worktree for a branch and add it to the garden file
+    Add(WorktreeAddOptions),
+    /// List parent trees and their worktrees
+    #[command(alias = "ls")]
+    List(WorktreeListOptions),
+    /// Remove a worktree and its entry in the garden file
+    #[command(alias = "rm")]
+    Remove(WorktreeRemoveOptions),
+}
+
+/// Create a worktree for a branch and add it to the garden file
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct WorktreeAddOptions {
+    /// Name of the new tree [default: "<parent-tree>-<branch>"]
+    #[arg(long, short)]
+    name: Option<String>,
+    /// Path for the new worktree [default: the tree name]
+    #[arg(long, short, value_hint = ValueHint::DirPath)]
+    path: Option<String>,
+    /// Tree to create the worktree from
+    parent: String,
+    /// Branch to checkout in the worktree
+    branch: String,
+}
+
+/// List parent trees and their worktrees
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct WorktreeListOptions {
+    /// Tree query for the gardens, groups or trees to display
+    queries: Vec<String>,
+}
+
+/// Remove a worktree and its entry in the garden file
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct WorktreeRemoveOptions {
+    /// Remove worktrees that have uncommitted changes
+    #[arg(long, short)]
+    force: bool,
+    /// Worktree tree to remove
+    tree: String,
+}
+
+/// Main entry point for the "garden worktree" command
+pub fn main(app_context: &model::ApplicationContext, options: &WorktreeOptions) -> Result<()> {
+    match &options.command {
+        WorktreeCommand::Add(add) => add_worktree(app_context, add),
+        WorktreeCommand::List(list) => {
+            let mut queries = list.queries.clone();
+            if queries.is_empty() {
+                queries.push(string!("@*"));
+            }
+            list_worktrees(app_context, &queries);
+            Ok(())
+        }
+        WorktreeCommand::Remove(remove) => remove_worktree(app_context, remove),
+    }
+}
+
+/// Run "git worktree add" and record a new "worktree" tree in the garden file.
+fn add_worktree(
+    app_context: &model::ApplicationContext,
+    options: &WorktreeAddOptions,
+) -> Result<()> {
+    let config = app_context.get_root_config();
+    let verbose = app_context.options.verbose;
+    let parent_tree =
+        config
+            .trees
+            .get(&options.parent)
+            .ok_or_else(|| errors::GardenError::TreeNotFound {
+                tree: options.parent.to_string(),
+            })?;
+    if parent_tree.is_worktree || parent_tree.is_symlink {
+        return Err(errors::GardenError::ConfigurationError(format!(
+            "{} cannot be used as a worktree parent",
+            options.parent
+        ))
+        .into());
+    }
+    let parent_path = match parent_tree.canonical_pathbuf() {
+        Some(parent_path) => parent_path,
+        None => {
+            return Err(errors::GardenError::WorktreeParentNotPlantedError {
+                parent: parent_tree.pathbuf().unwrap_or_default(),
+                tree: options.parent.to_string(),
+            }
+            .into())
+        }
+    };
+
+    let name = match &options.name {
+        Some(name) => name.to_string(),
+        None => format!("{}-{}", options.parent, options.branch.replace('/', "-")),
+    };
+    if config.trees.contains_key(&name) {
+        return Err(errors::GardenError::ConfigurationError(format!(
+            "tree already exists: {name}"
+        ))
+        .into());
+    }
+
+    // Worktrees are created relative to the garden root by default.
+    let root = config.root_path.canonicalize().map_err(|err| {
+        errors::GardenError::ConfigurationError(format!(
+            "unable to canonicalize config root: {err:?}"
+        ))
+    })?;
+    let tree_path = match &options.path {
+        Some(raw_path) => {
+            let pathbuf = std::path::PathBuf::from(raw_path);
+            if pathbuf.is_absolute() {
+                pathbuf
+            } else {
+                path::current_dir().join(pathbuf)
+            }
+        }
+        None => root.join(&name),
+    };
+    if tree_path.exists() {
+        return Err(errors::GardenError::FileExists(format!(
+            "{} already exists",
+            tree_path.display()
+        ))
+        .into());
+    }
+    // Canonicalize the parent directory so that the path can be made relative to the root.
+    let tree_path = match (tree_path.parent(), tree_path.file_name()) {
+        (Some(dirname), Some(basename)) if dirname.exists() => {
+            path::abspath(dirname).join(basename)
+        }
+        _ => tree_path,
+    };
+
+    // Record the new tree. The garden file is read and validated before the
+    // worktree is created so that errors leave nothing behind.
+    let branch = options.branch.as_str();
+    let mut entry = yaml::Hash::new();
+    entry.insert(
+        Yaml::String(constants::WORKTREE.to_string()),
+        Yaml::String(options.parent.to_string()),
+    );
+    entry.insert(
+        Yaml::String(constants::BRANCH.to_string()),
+        Yaml::String(branch.to_string()),
+    );
+    let relative_path = path::strip_prefix_into_string(&root, &tree_path)?;
+    if relative_path != name {
+        entry.insert(
+            Yaml::String(constants::PATH.to_string()),
+            Yaml::String(relative_path),
+        );
+    }
+    let config_path = config.get_path()?;
+    let mut doc = config::reader::read_yaml(config_path)?;
+    config::reader::add_section(constants::TREES, &mut doc)?;
+    let trees_key = Yaml::String(constants::TREES.to_string());
+    match doc {
+        Yaml::Hash(ref mut doc_hash) => match doc_hash.get_mut(&trees_key) {
+            Some(Yaml::Hash(ref mut trees)) => {
+                trees.insert(Yaml::String(name.to_string()), Yaml::Hash(entry));
+            }
+            _ => {
+                return Err(errors::GardenError::ConfigurationError(format!(
+                    "invalid trees section in {}",
+                    config_path.display()
+                ))
+                .into());
+            }
+        },
+        _ => {
+            return Err(errors::GardenError::ConfigurationError(format!(
+                "invalid garden file: {}",
+                config_path.display()
+            ))
+            .into());
+        }
+    }
+
+    // git worktree add [--track -b <branch>] <path> [<branch> | <remote>/<branch>]
+    let tree_path_str = tree_path.to_string_lossy().to_string();
+    let remote_branch = format!("{}/{branch}", parent_tree.default_remote);
+    let mut command = vec!["git", "worktree", "add"];
+    // Only the new tree name is printed to stdout by default.
+    if verbose == 0 {
+        command.push("--quiet");
+    }
+    if git::branches(&parent_path)
+        .iter()
+        .any(|name| name == branch)
+    {
+        command.push(&tree_path_str);
+        command.push(branch);
+    } else if git::rev_exists(&parent_path, &remote_branch) {
+        command.extend(["--track", "-b", branch, &tree_path_str, &remote_branch]);
+    } else {
+        command.extend(["-b", branch, &tree_path_str]);
+    }
+    if verbose > 1 {
+        display::print_command_vec(&command);
+    }
+    let exit_status = cmd::run_command(&command, &parent_path);
+    if exit_status != errors::EX_OK {
+        return Err(errors::GardenError::WorktreeGitCheckoutError {
+            tree: name,
+            status: exit_status,
+        }
+        .into());
+    }
+
+    // Remove the new worktree when the garden file cannot be written.
+    if let Err(err) = config::writer::write_yaml(&doc, config_path) {
+        let command = ["git", "worktree", "remove", "--force", &tree_path_str];
+        if verbose > 1 {
+            display::print_command_vec(&command);
+        }
+        cmd::run_command(&command, &parent_path);
+        return Err(err.into());
+    }
+
+    println!("{name}");
+
+    Ok(())
+}
+
+/// Run "git worktree remove" and remove the tree from the garden file.
+fn remove_worktree(
+    app_context: &model::ApplicationContext,
+    options: &WorktreeRemoveOptions,
+) -> Result<()> {
+    let config = app_context.get_root_config();
+    let verbose = app_context.options.verbose;
+    let tree_context =
+        query::tree_from_name(config, &options.tree, None, None).ok_or_else(|| {
+            errors::GardenError::TreeNotFound {
+                tree: options.tree.to_string(),
+            }
+        })?;
+    let eval_context = model::EvalContext::from_app_context(app_context, &tree_context);
+    let Some(tree) = config.trees.get(&tree_context.tree) else {
+        return Err(errors::GardenError::TreeNotFound {
+            tree: options.tree.to_string(),
+        }
+        .into());
+    };
+    if !tree.is_worktree {
+        return Err(errors::GardenError::ConfigurationError(format!(
+            "{} is not a worktree",
+            options.tree
+        ))
+        .into());
+    }
+    let parent_name = tree.eval_worktree(&eval_context);
+    let parent_path = config
+        .trees
+        .get(&parent_name)
+        .and_then(|parent_tree| parent_tree.canonical_pathbuf())
+        .ok_or_else(|| errors::GardenError::WorktreeNotFound {
+            worktree: parent_name.to_string(),
+            tree: options.tree.to_string(),
+        })?;
+
+    // Remove the tree from the garden file before removing the worktree so that
+    // trees defined in other files are rejected before anything is removed.
+    let config_path = config.get_path()?;
+    let mut doc = config::reader::read_yaml(config_path)?;
+    let trees_key = Yaml::String(constants::TREES.to_string());
+    let removed = match doc {
+        Yaml::Hash(ref mut doc_hash) => match doc_hash.get_mut(&trees_key) {
+            Some(Yaml::Hash(ref mut trees)) => trees
+                .remove(&Yaml::String(tree.get_name().to_string()))
+                .is_some(),
+            _ => false,
+        },
+        _ => false,
+    };
+    if !removed {
+        return Err(errors::GardenError::ConfigurationError(format!(
+            "{} is not defined in {}",
+            options.tree,
+            config_path.display()
+        ))
+        .into());
+    }
+
+    // git worktree remove [--force] <path>
+    if let Some(tree_path) = tree.pathbuf().filter(|tree_path| tree_path.exists()) {
+        let tree_path_str = tree_path.to_string_lossy().to_string();
+        let mut command = vec!["git", "worktree", "remove"];
+        if options.force {
+            command.push("--force");
+        }
+        command.push(&tree_path_str);
+        if verbose > 1 {
+            display::print_command_vec(&command);
+        }
+        cmd::result_from_exit_status(cmd::run_command(&command, &parent_path))?;
+    } else {
+        // The worktree has already been removed from disk.
+        let command = ["git", "worktree", "prune"];
+        cmd::result_from_exit_status(cmd::run_command(&command, &parent_path))?;
+    }
+
+    config::writer::write_yaml(&doc, config_path)?;
+
+    Ok(())
+}
+
+/// Display parent trees along with their worktrees.
+fn list_worktrees(app_context: &model::ApplicationContext, queries: &[String]) {
+    let config = app_context.get_root_config();
+    let verbose = app_context.options.verbose;
+    // Map parent repository paths to the parent tree and its child worktrees.
+    let mut parents: IndexMap<std::path::PathBuf, (Option<&model::Tree>, Vec<&model::Tree>)> =
+        IndexMap::new();
+    for query in queries {
+        for context in query::resolve_trees(app_context, config, None, query) {
+            let tree_config = match context.config {
+                Some(config_id) => app_context.get_config(config_id),
+                None => config,
+            };
+            let Some(tree) = tree_config.trees.get(&context.tree) else {
+                continue;
+            };
+            let Some(path) = tree.canonical_pathbuf() else {
+                continue;
+            };
+            let Ok(details) = git::worktree_details(&path) else {
+                continue;
+            };
+            match details.tree_type {
+                model::GitTreeType::Parent => {
+                    parents.entry(path).or_default().0 = Some(tree);
+                }
+                model::GitTreeType::Worktree(parent_path) => {
+                    let children = &mut parents.entry(parent_path).or_default().1;
+                    if !children
+                        .iter()
+                        .any(|child| child.get_name() == tree.get_name())
+                    {
+                        children.push(tree);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    for (parent_path, (parent, children)) in &parents {
+        let parent = parent.or_else(|| {
+            query::tree_name_from_abspath(config, parent_path)
+                .and_then(|name| config.trees.get(&name))
+        });
+        match parent {
+            Some(parent) => {
+                let path = parent.path_as_ref().map(String::as_str).unwrap_or_default();
+                println!(
+                    "{}",
+                    display::display_tree(parent, path, true, false, verbose)
+                );
+            }
+            None => println!("# {}", parent_path.display()),
+        }
+        for child in children {
+            let path = child.path_as_ref().map(String::as_str).unwrap_or_default();
+            println!(
+                "  {}",
+                display::display_tree(child, path, true, false, verbose)
+            );
+        }
+    }
+}
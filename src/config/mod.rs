@@ -18,7 +18,6 @@ use crate::{errors, model, model::IndexSet, path};
 ///  Traversal continues up file system until the root is reached.
 ///  GARDEN_CEILING_DIRS and GIT_CEILING_DIRS can be used to define
 ///  directories into which garden should not traverse.
-
 pub(crate) fn search_path() -> Vec<std::path::PathBuf> {
     // Result: Vec<PathBuf> in priority order
     let mut paths: Vec<std::path::PathBuf> = Vec::new();
@@ -106,6 +105,52 @@ pub fn xdg_dir() -> std::path::PathBuf {
     home_config_dir
 }
 
+/// $XDG_CACHE_HOME/garden (typically ~/.cache/garden)
+pub fn xdg_cache_dir() -> std::path::PathBuf {
+    let mut home_cache_dir;
+
+    #[cfg(unix)]
+    if let Ok(xdg_dirs) = xdg::BaseDirectories::new() {
+        home_cache_dir = xdg_dirs.get_cache_home();
+    } else {
+        home_cache_dir = path::home_dir();
+        home_cache_dir.push(".cache")
+    }
+    #[cfg(not(unix))]
+    {
+        home_cache_dir = path::home_dir();
+        home_cache_dir.push(".cache")
+    }
+
+    home_cache_dir.push("garden");
+
+    home_cache_dir
+}
+
+/// $XDG_DATA_HOME/garden (typically ~/.local/share/garden)
+pub fn xdg_data_dir() -> std::path::PathBuf {
+    let mut home_data_dir;
+
+    #[cfg(unix)]
+    if let Ok(xdg_dirs) = xdg::BaseDirectories::new() {
+        home_data_dir = xdg_dirs.get_data_home();
+    } else {
+        home_data_dir = path::home_dir();
+        home_data_dir.push(".local");
+        home_data_dir.push("share");
+    }
+    #[cfg(not(unix))]
+    {
+        home_data_dir = path::home_dir();
+        home_data_dir.push(".local");
+        home_data_dir.push("share");
+    }
+
+    home_data_dir.push("garden");
+
+    home_data_dir
+}
+
 /// Parse and apply configuration from a YAML/JSON string
 pub fn parse(
     app_context: &model::ApplicationContext,
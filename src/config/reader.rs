@@ -1,7 +1,7 @@
 use yaml_rust::{yaml, Yaml, YamlLoader};
 
 use crate::{
-    constants, errors, eval, model,
+    config, constants, errors, eval, model,
     model::{IndexMap, StringSet},
     syntax,
 };
@@ -116,6 +116,40 @@ fn parse_recursive(
         );
     }
 
+    // garden.cache
+    match &doc[constants::GARDEN][constants::CACHE] {
+        Yaml::Boolean(true) => {
+            config.cache = config::xdg_cache_dir().to_string_lossy().to_string();
+        }
+        Yaml::Boolean(false) => config.cache.clear(),
+        Yaml::String(value) => config.cache.clone_from(value),
+        _ => (),
+    }
+    // garden.cache-mode
+    let mut cache_mode = String::new();
+    if get_str(
+        &doc[constants::GARDEN][constants::CACHE_MODE],
+        &mut cache_mode,
+    ) {
+        config.cache_dissociate = cache_mode == constants::CACHE_MODE_DISSOCIATE;
+    }
+    if config_verbose > 0 && !config.cache.is_empty() {
+        debug!("config: {} = {}", constants::GARDEN_CACHE, config.cache);
+    }
+
+    // garden.url-rewrites
+    if get_variables_map(
+        &doc[constants::GARDEN][constants::URL_REWRITES],
+        &mut config.url_rewrites,
+    ) && config_verbose > 0
+    {
+        debug!(
+            "config: {} = {:?}",
+            constants::GARDEN_URL_REWRITES,
+            config.url_rewrites
+        );
+    }
+
     // GARDEN_ROOT and GARDEN_CONFIG_DIR are relative to the root configuration.
     // Referencing these variables from garden files included using garden.includes
     // resolves to the root config's location, not the included location.
@@ -363,6 +397,19 @@ fn get_bool(yaml: &Yaml, value: &mut bool) -> bool {
     }
 }
 
+/// Read the "submodules" setting from a boolean or "recursive".
+fn get_submodules(yaml: &Yaml, value: &mut model::Submodules) -> bool {
+    match yaml {
+        Yaml::Boolean(true) => *value = model::Submodules::Update,
+        Yaml::Boolean(false) => *value = model::Submodules::None,
+        Yaml::String(yaml_string) if yaml_string == constants::SUBMODULES_RECURSIVE => {
+            *value = model::Submodules::Recursive;
+        }
+        _ => return false,
+    }
+    true
+}
+
 /// Extract a `StringSet` from `Yaml::String` or `Yaml::Array<Yaml::String>`.
 /// Return `false` when `yaml` is not `Yaml::String` or `Yaml::Array<Yaml::String>`.
 /// This function promotes a scalar `Yaml::String` into a `StringSet`
@@ -737,12 +784,23 @@ fn get_tree_fields(value: &Yaml, tree: &mut model::Tree) {
 
     get_variable(&value[constants::BRANCH], &mut tree.branch);
     get_variables_map(&value[constants::BRANCHES], &mut tree.branches);
+    get_variable(&value[constants::TAG], &mut tree.tag);
+    get_variable(&value[constants::COMMIT], &mut tree.commit);
     get_variable(&value[constants::SYMLINK], &mut tree.symlink);
     get_variable(&value[constants::WORKTREE], &mut tree.worktree);
+    get_indexset_str(&value[constants::DEPENDS], &mut tree.depends);
 
     get_i64(&value[constants::DEPTH], &mut tree.clone_depth);
+    get_str(&value[constants::FILTER], &mut tree.clone_filter);
+    // "sparse-checkout" replaces the paths inherited from templates.
+    let mut sparse_checkout = StringSet::new();
+    if get_indexset_str(&value[constants::SPARSE_CHECKOUT], &mut sparse_checkout) {
+        tree.sparse_checkout = sparse_checkout;
+    }
     get_bool(&value[constants::BARE], &mut tree.is_bare_repository);
     get_bool(&value[constants::SINGLE_BRANCH], &mut tree.is_single_branch);
+    get_bool(&value[constants::LFS], &mut tree.is_lfs);
+    get_submodules(&value[constants::SUBMODULES], &mut tree.submodules);
 
     // Load the URL and store it in the "origin" remote.
     {
@@ -5,19 +5,11 @@ use yaml_rust::{Yaml, YamlEmitter};
 use crate::errors;
 
 /// Write a Yaml object to a file
-
 pub fn write_yaml<P>(doc: &Yaml, path: P) -> Result<(), errors::GardenError>
 where
     P: std::convert::AsRef<std::path::Path> + std::fmt::Debug,
 {
-    // Emit the YAML configuration into a string
-    let mut out_str = String::new();
-    {
-        let mut emitter = YamlEmitter::new(&mut out_str);
-        emitter.multiline_strings(true);
-        emitter.dump(doc).unwrap_or(()); // dump the YAML object to a String
-    }
-    out_str += "\n";
+    let out_str = yaml_string(doc) + "\n";
 
     let mut file = std::fs::File::create(&path).map_err(|io_err| {
         errors::GardenError::CreateConfigurationError {
@@ -38,3 +30,48 @@ where
             err: sync_err,
         })
 }
+
+/// Emit a Yaml object into a string.
+pub(crate) fn yaml_string(doc: &Yaml) -> String {
+    let mut out_str = String::new();
+    {
+        let mut emitter = YamlEmitter::new(&mut out_str);
+        emitter.multiline_strings(true);
+        emitter.dump(doc).unwrap_or(()); // dump the YAML object to a String
+    }
+
+    out_str
+}
+
+/// Emit a Yaml object into a pretty-printed JSON string.
+pub(crate) fn json_string(doc: &Yaml) -> String {
+    serde_json::to_string_pretty(&json_value(doc)).unwrap_or_default()
+}
+
+/// Convert a Yaml object into a serde_json::Value.
+fn json_value(doc: &Yaml) -> serde_json::Value {
+    match doc {
+        Yaml::String(value) => serde_json::Value::String(value.clone()),
+        Yaml::Real(value) => value
+            .parse::<f64>()
+            .map(serde_json::Value::from)
+            .unwrap_or_else(|_| serde_json::Value::String(value.clone())),
+        Yaml::Integer(value) => serde_json::Value::from(*value),
+        Yaml::Boolean(value) => serde_json::Value::Bool(*value),
+        Yaml::Array(values) => serde_json::Value::Array(values.iter().map(json_value).collect()),
+        Yaml::Hash(hash) => {
+            let mut map = serde_json::Map::new();
+            for (key, value) in hash {
+                let key = match key {
+                    Yaml::String(key) | Yaml::Real(key) => key.clone(),
+                    Yaml::Integer(key) => key.to_string(),
+                    Yaml::Boolean(key) => key.to_string(),
+                    _ => continue,
+                };
+                map.insert(key, json_value(value));
+            }
+            serde_json::Value::Object(map)
+        }
+        _ => serde_json::Value::Null,
+    }
+}
@@ -10,9 +10,22 @@ pub const BRANCHES: &str = "branches";
 /// The "config" key in a graft definition defines the path to a garden config file.
 pub const CONFIG: &str = "config";
 
+/// The "cache" key in the garden block enables the mirror repository cache.
+/// The value is either a boolean or the path to the cache directory.
+pub const CACHE: &str = "cache";
+
+/// The "cache-mode" key in the garden block controls how cached mirrors are used.
+pub const CACHE_MODE: &str = "cache-mode";
+
+/// The "cache-mode: dissociate" value copies objects out of the cached mirrors.
+pub(crate) const CACHE_MODE_DISSOCIATE: &str = "dissociate";
+
 /// The "commands" section in a configuration block defines custom commands.
 pub const COMMANDS: &str = "commands";
 
+/// The "commit" key records the locked commit in "garden.lock" entries and pins trees to a commit.
+pub const COMMIT: &str = "commit";
+
 /// The "cmd" debug level for "garden cmd".
 pub const DEBUG_LEVEL_CMD: &str = "cmd";
 
@@ -25,9 +38,15 @@ pub const DEBUG_LEVEL_EXEC: &str = "exec";
 /// The "list" debug level for "garden ls".
 pub const DEBUG_LEVEL_LIST: &str = "list";
 
+/// The "status" debug level for "garden status".
+pub const DEBUG_LEVEL_STATUS: &str = "status";
+
 /// The "default-remote" key in a tree block defines the default remote.
 pub const DEFAULT_REMOTE: &str = "default-remote";
 
+/// The "depends" key in a tree block lists the trees that must be processed first.
+pub const DEPENDS: &str = "depends";
+
 /// The "depth" key in a tree block defines the git clone depth.
 pub const DEPTH: &str = "depth";
 
@@ -54,6 +73,9 @@ pub const ENVIRONMENT: &str = "environment";
 /// tree or template block.
 pub const EXTEND: &str = "extend";
 
+/// The "filter" key in a tree block defines a partial clone filter, eg. "blob:none".
+pub const FILTER: &str = "filter";
+
 /// The "garden" section of the configuration defines global settings.
 pub const GARDEN: &str = "garden";
 
@@ -71,6 +93,9 @@ pub const GARDEN_CMD_VERBOSE: &str = "GARDEN_CMD_VERBOSE";
 /// The default "garden.yaml" configuration file.
 pub const GARDEN_CONFIG: &str = "garden.yaml";
 
+/// The "garden.lock" file records the commits for each tree.
+pub const GARDEN_LOCK: &str = "garden.lock";
+
 /// Builtin variable for the "garden.yaml" configuration directory.
 pub const GARDEN_CONFIG_DIR: &str = "GARDEN_CONFIG_DIR";
 
@@ -81,11 +106,14 @@ pub const GARDEN_CONFIG_DIR_EXPR: &str = "${GARDEN_CONFIG_DIR}";
 pub const GARDEN_ROOT: &str = "GARDEN_ROOT";
 
 /// Command-line defines for overriding configurable behavior.
+pub(crate) const GARDEN_CACHE: &str = "garden.cache";
+pub(crate) const GARDEN_CACHE_MODE: &str = "garden.cache-mode";
 pub(crate) const GARDEN_INTERACTIVE_SHELL: &str = "garden.interactive-shell";
 pub(crate) const GARDEN_SHELL: &str = "garden.shell";
 pub(crate) const GARDEN_SHELL_ERREXIT: &str = "garden.shell-errexit";
 pub(crate) const GARDEN_SHELL_WORDSPLIT: &str = "garden.shell-wordsplit";
 pub(crate) const GARDEN_TREE_BRANCHES: &str = "garden.tree-branches";
+pub(crate) const GARDEN_URL_REWRITES: &str = "garden.url-rewrites";
 
 /// The "gitconfig" section in a tree block defines local ".git/config"
 /// settings that are applied when a tree is grown.
@@ -106,6 +134,9 @@ pub const INCLUDES: &str = "includes";
 /// command used by interactive "garden shell" sessions.
 pub const INTERACTIVE_SHELL: &str = "interactive-shell";
 
+/// The "lfs" key in a tree block installs Git LFS and fetches LFS files.
+pub const LFS: &str = "lfs";
+
 /// The "links" key in a tree block defines URLs displayed by "garden ls".
 pub const LINKS: &str = "links";
 
@@ -175,9 +206,23 @@ pub(crate) const SHELL_ZSH: &str = "zsh";
 /// are cloned and fetched by default.
 pub const SINGLE_BRANCH: &str = "single-branch";
 
+/// The "sparse-checkout" key in a tree block lists the cone-mode paths
+/// that are checked out when a sparse checkout is used.
+pub const SPARSE_CHECKOUT: &str = "sparse-checkout";
+
+/// The "submodules" key in a tree block initializes and updates submodules.
+/// Submodules are updated recursively when set to "recursive".
+pub const SUBMODULES: &str = "submodules";
+
+/// The "submodules: recursive" value updates nested submodules.
+pub(crate) const SUBMODULES_RECURSIVE: &str = "recursive";
+
 /// The "symlink" key in a tree block creates a symlink.
 pub const SYMLINK: &str = "symlink";
 
+/// The "tag" key in a tree block pins the tree to a tag.
+pub const TAG: &str = "tag";
+
 /// The "templates" section defines tree templates that can be used when
 /// defining tree entries.
 pub const TEMPLATES: &str = "templates";
@@ -199,6 +244,9 @@ pub const TREES: &str = "trees";
 /// The "url" key in a tree block defines the "git clone" URL to clone.
 pub const URL: &str = "url";
 
+/// The "url-rewrites" key in the garden block maps URL prefixes to replacements.
+pub const URL_REWRITES: &str = "url-rewrites";
+
 /// The "variables" section in a configuration block defines expression
 /// variables that can be references using "${variable}" expressions in
 /// "environment", "commands" and "variables" blocks. Variables
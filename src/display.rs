@@ -1,4 +1,4 @@
-use crate::{eval, git, model};
+use crate::{cmd, eval, git, model};
 use yansi::Paint;
 
 pub(crate) fn display_missing_tree(
@@ -30,57 +30,75 @@ pub(crate) fn display_missing_tree(
     }
 }
 
+/// Return the header displayed for a tree.
+/// The tree's HEAD is only compared against its pin when `check_pin` is true.
 pub(crate) fn display_tree(
     tree: &model::Tree,
     path_str: &str,
     tree_branches: bool,
+    check_pin: bool,
     verbose: u8,
 ) -> String {
-    if verbose > 0 {
-        if tree_branches {
-            if let Some(path) = tree.canonical_pathbuf() {
-                if let Some(branch) = git::branch(&path) {
-                    return format!(
-                        "{} {} {}{}{} {}",
-                        "#".cyan(),
-                        tree.get_name().blue().bold(),
-                        "[".blue(),
-                        branch.green().bold(),
-                        "]".blue(),
-                        path_str.blue()
-                    );
-                }
-            }
+    let mut display = format!("{} {}", "#".cyan(), tree.get_name().blue().bold());
+    let path = tree.canonical_pathbuf();
+    if tree_branches {
+        if let Some(branch) = path.as_ref().and_then(|path| git::branch(path)) {
+            display.push_str(&format!(
+                " {}{}{}",
+                "[".blue(),
+                branch.green().bold(),
+                "]".blue()
+            ));
         }
-        format!(
-            "{} {} {}",
-            "#".cyan(),
-            tree.get_name().blue().bold(),
-            path_str.blue()
-        )
+    }
+    if let Some(pin) = tree.pin() {
+        let path = path.as_deref().filter(|_| check_pin);
+        display.push_str(&format!(" {}", display_pin(&pin, path)));
+    }
+    if verbose > 0 {
+        display.push_str(&format!(" {}", path_str.blue()));
+    }
+
+    display
+}
+
+/// Return a "(tag: name)" or "(commit: id)" label for a pinned tree.
+/// When a path is specified, trees whose HEAD does not match the pin are flagged.
+pub(crate) fn display_pin(pin: &model::Pin, path: Option<&std::path::Path>) -> String {
+    if path.is_none_or(|path| git::head_matches(path, &pin.rev())) {
+        format!("({pin})").yellow().to_string()
     } else {
-        if tree_branches {
-            if let Some(path) = tree.canonical_pathbuf() {
-                if let Some(branch) = git::branch(&path) {
-                    return format!(
-                        "{} {} {}{}{}",
-                        "#".cyan(),
-                        tree.get_name().blue().bold(),
-                        "[".blue(),
-                        branch.green().bold(),
-                        "]".blue()
-                    );
-                }
-            }
-        }
-        format!("{} {}", "#".cyan(), tree.get_name().blue().bold())
+        format!("({pin}, HEAD does not match)")
+            .red()
+            .bold()
+            .to_string()
     }
 }
 
+/// Return a colored "[tree]" prefix for tagging lines of command output.
+/// Colors are derived from the tree name so that each tree is displayed consistently.
+pub(crate) fn tree_prefix(name: &str) -> String {
+    const COLORS: [yansi::Color; 6] = [
+        yansi::Color::Cyan,
+        yansi::Color::Green,
+        yansi::Color::Yellow,
+        yansi::Color::Blue,
+        yansi::Color::Magenta,
+        yansi::Color::Red,
+    ];
+    let hash = name.bytes().fold(0usize, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte as usize)
+    });
+    let color = COLORS[hash % COLORS.len()];
+
+    format!("[{name}]").fg(color).bold().to_string()
+}
+
 /// Print a tree if it exists, otherwise print a missing tree
 pub(crate) fn print_tree(
     tree: &model::Tree,
     tree_branches: bool,
+    check_pin: bool,
     verbose: u8,
     quiet: bool,
     force: bool,
@@ -94,7 +112,7 @@ pub(crate) fn print_tree(
             return false;
         }
 
-        print_tree_details(tree, tree_branches, verbose, quiet);
+        print_tree_details(tree, tree_branches, check_pin, verbose, quiet);
         return true;
     }
     if !quiet {
@@ -111,6 +129,7 @@ pub(crate) fn print_tree(
 pub(crate) fn print_tree_details(
     tree: &model::Tree,
     tree_branches: bool,
+    check_pin: bool,
     verbose: u8,
     quiet: bool,
 ) {
@@ -118,7 +137,10 @@ pub(crate) fn print_tree_details(
         return;
     }
     if let Ok(path) = tree.path_as_ref() {
-        eprintln!("{}", display_tree(tree, path, tree_branches, verbose));
+        eprintln!(
+            "{}",
+            display_tree(tree, path, tree_branches, check_pin, verbose)
+        );
     }
 }
 
@@ -168,6 +190,7 @@ pub(crate) fn print_tree_extended_details(
     context: &model::TreeContext,
     tree: &model::Tree,
     display_worktrees: bool,
+    verbose: u8,
 ) {
     let config = match context.config {
         Some(config_id) => app_context.get_config(config_id),
@@ -176,6 +199,12 @@ pub(crate) fn print_tree_extended_details(
     if !tree.description.is_empty() {
         println!("{}", tree.description.green());
     }
+    if !tree.depends.is_empty() {
+        println!("{}", "depends:".blue());
+        for dep in &tree.depends {
+            println!("  {} {}", "-".blue(), dep.yellow());
+        }
+    }
     if tree.is_worktree && !display_worktrees {
         return;
     }
@@ -190,7 +219,20 @@ pub(crate) fn print_tree_extended_details(
                 context.garden.as_ref(),
                 remote,
             );
-            println!("  {}{} {}", name.blue(), ":".blue(), value.yellow());
+            // Display the configured URL when it was changed by "garden.url-rewrites".
+            let url =
+                model::EvalContext::from_app_context(app_context, context).rewrite_url(&value);
+            if verbose > 0 && url != value {
+                println!(
+                    "  {}{} {} {}",
+                    name.blue(),
+                    ":".blue(),
+                    url.yellow(),
+                    format!("(configured: {value})").dim()
+                );
+            } else {
+                println!("  {}{} {}", name.blue(), ":".blue(), url.yellow());
+            }
         }
     }
     if !tree.links.is_empty() {
@@ -245,3 +287,52 @@ pub(crate) fn print_command_string_vec(command: &[String]) {
     let str_vec: Vec<&str> = command.iter().map(String::as_str).collect();
     print_command_vec(&str_vec);
 }
+
+/// Print a command from a list of arguments. Each argument is shell quoted.
+pub(crate) fn print_quoted_command(command: &[&str]) {
+    println!(
+        "{} {}",
+        ":".cyan(),
+        cmd::shell_quote_command(command).green()
+    );
+}
+
+/// Per-tree outcomes that are displayed in a summary table.
+pub(crate) trait SummaryStatus {
+    /// Return the status label displayed in the summary table.
+    fn label(&self) -> &'static str;
+
+    /// Return the details displayed alongside the label.
+    fn details(&self) -> String;
+
+    /// Return the colored status label. Labels are displayed in yellow by default.
+    fn paint_label(&self, label: &str) -> String {
+        label.yellow().to_string()
+    }
+}
+
+/// Print a summary table. Each tree is displayed on its own aligned row.
+pub(crate) fn print_summary<S: SummaryStatus>(results: &[(String, S)]) {
+    let name_width = results
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default();
+    let label_width = results
+        .iter()
+        .map(|(_, status)| status.label().len())
+        .max()
+        .unwrap_or_default();
+    for (name, status) in results {
+        let name = format!("{name:<name_width$}");
+        let label = format!("{:<label_width$}", status.label());
+        let row = format!(
+            "{} {} {} {}",
+            "#".cyan(),
+            name.blue().bold(),
+            status.paint_label(&label),
+            status.details()
+        );
+        println!("{}", row.trim_end());
+    }
+}
@@ -85,12 +85,8 @@ pub fn branches(path: &std::path::Path) -> Vec<String> {
 
 /// Return the current branch name for the specified repository path.
 pub(crate) fn branch(path: &std::path::Path) -> Option<String> {
-    let cmd = ["git", "symbolic-ref", "--quiet", "--short", "HEAD"];
-    let exec = cmd::exec_in_dir(&cmd, &path);
-    if let Ok(output) = cmd::stdout_to_string(exec) {
-        if !output.is_empty() {
-            return Some(output);
-        }
+    if let Some(branch) = symbolic_branch(path) {
+        return Some(branch);
     }
     // Detached head? Show an abbreviated commit ID. This respects `git config core.abbrev`.
     let cmd = ["git", "rev-parse", "--short", "HEAD"];
@@ -104,6 +100,41 @@ pub(crate) fn branch(path: &std::path::Path) -> Option<String> {
     None
 }
 
+/// Return the names of the remotes configured in the specified repository path.
+pub(crate) fn remotes(path: &std::path::Path) -> Vec<String> {
+    let cmd = ["git", "remote"];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    match cmd::stdout_to_string(exec) {
+        Ok(output) => output
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_string())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Return the checked-out branch name. Returns None when HEAD is detached.
+pub(crate) fn symbolic_branch(path: &std::path::Path) -> Option<String> {
+    let cmd = ["git", "symbolic-ref", "--quiet", "--short", "HEAD"];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    match cmd::stdout_to_string(exec) {
+        Ok(output) if !output.is_empty() => Some(output),
+        _ => None,
+    }
+}
+
+/// Return the full commit ID for the specified revision.
+pub(crate) fn commit_id(path: &std::path::Path, rev: &str) -> Option<String> {
+    let rev = format!("{rev}^{{commit}}");
+    let cmd = ["git", "rev-parse", "--quiet", "--verify", rev.as_str()];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    match cmd::stdout_to_string(exec) {
+        Ok(output) if !output.is_empty() => Some(output),
+        _ => None,
+    }
+}
+
 /// Return the root of the current repository when inside a Git repository.
 pub(crate) fn current_worktree_path(
     path: &std::path::Path,
@@ -129,3 +160,170 @@ pub(crate) fn name_from_url_or_path(url: &str, path: &std::path::Path) -> String
         .map(|basename| basename.to_string_lossy().to_string())
         .unwrap_or(string!(constants::DOT))
 }
+
+/// Return the "git status" details for the specified worktree path.
+pub(crate) fn status(path: &std::path::Path) -> Result<model::GitStatus, errors::CommandError> {
+    let cmd = [
+        "git",
+        "status",
+        "--porcelain=v2",
+        "--branch",
+        "--show-stash",
+        "--untracked-files=normal",
+    ];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    let output = cmd::stdout_to_string(exec)?;
+    let mut status = model::GitStatus::default();
+    let mut oid = String::new();
+
+    for line in output.lines() {
+        if let Some(value) = line.strip_prefix("# branch.oid ") {
+            oid = value.to_string();
+        } else if let Some(value) = line.strip_prefix("# branch.head ") {
+            if value == "(detached)" {
+                status.is_detached = true;
+            } else {
+                status.branch = value.to_string();
+            }
+        } else if let Some(value) = line.strip_prefix("# branch.upstream ") {
+            status.upstream = Some(value.to_string());
+        } else if let Some(value) = line.strip_prefix("# branch.ab ") {
+            for count in value.split_whitespace() {
+                if let Some(ahead) = count.strip_prefix('+') {
+                    status.ahead = ahead.parse().unwrap_or_default();
+                } else if let Some(behind) = count.strip_prefix('-') {
+                    status.behind = behind.parse().unwrap_or_default();
+                }
+            }
+        } else if let Some(value) = line.strip_prefix("# stash ") {
+            status.stashes = value.parse().unwrap_or_default();
+        } else if line.starts_with("1 ") || line.starts_with("2 ") {
+            // "<1|2> <XY> ..." where X is the index status and Y is the worktree status.
+            let mut flags = line[2..].chars();
+            if flags.next().is_some_and(|flag| flag != '.') {
+                status.staged += 1;
+            }
+            if flags.next().is_some_and(|flag| flag != '.') {
+                status.modified += 1;
+            }
+        } else if line.starts_with("u ") {
+            status.conflicts += 1;
+        } else if line.starts_with("? ") {
+            status.untracked += 1;
+        }
+    }
+    // Detached HEAD reports an abbreviated commit ID in place of the branch name.
+    if status.is_detached {
+        status.branch = branch(path).unwrap_or(oid);
+    }
+
+    Ok(status)
+}
+
+/// Return the number of commits that HEAD is (ahead, behind) relative to an upstream revision.
+pub(crate) fn ahead_behind(path: &std::path::Path, upstream: &str) -> Option<(usize, usize)> {
+    let range = format!("HEAD...{upstream}");
+    let cmd = ["git", "rev-list", "--left-right", "--count", range.as_str()];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    let output = cmd::stdout_to_string(exec).ok()?;
+    let (ahead, behind) = output.split_once(char::is_whitespace)?;
+
+    Some((ahead.trim().parse().ok()?, behind.trim().parse().ok()?))
+}
+
+/// Return the number of commits in the specified revision range.
+pub(crate) fn count_commits(path: &std::path::Path, range: &str) -> Option<usize> {
+    let cmd = ["git", "rev-list", "--count", range];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+
+    cmd::stdout_to_string(exec).ok()?.trim().parse().ok()
+}
+
+/// Return true when the specified revision exists in the repository.
+pub(crate) fn rev_exists(path: &std::path::Path, rev: &str) -> bool {
+    commit_id(path, rev).is_some()
+}
+
+/// Return the values for a multi-valued "git config" variable.
+pub(crate) fn config_values(path: &std::path::Path, name: &str) -> Vec<String> {
+    let cmd = ["git", "config", "--get-all", name];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    match cmd::stdout_to_string(exec) {
+        Ok(output) => output.lines().map(|line| line.to_string()).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Return the paths of the linked worktrees that were created from a repository.
+pub(crate) fn worktree_paths(path: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let cmd = ["git", "worktree", "list", "--porcelain"];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    match cmd::stdout_to_string(exec) {
+        // The first worktree is the repository's main worktree.
+        Ok(output) => output
+            .lines()
+            .filter_map(|line| line.strip_prefix("worktree "))
+            .skip(1)
+            .map(|worktree| path::abspath(&std::path::PathBuf::from(worktree)))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Return true when HEAD points to the same commit as the specified revision.
+pub(crate) fn head_matches(path: &std::path::Path, rev: &str) -> bool {
+    match (commit_id(path, "HEAD"), commit_id(path, rev)) {
+        (Some(head), Some(commit)) => head == commit,
+        _ => false,
+    }
+}
+
+/// Return the local branches along with their upstream branches, if configured.
+pub(crate) fn branch_upstreams(path: &std::path::Path) -> Vec<(String, Option<String>)> {
+    let cmd = [
+        "git",
+        "for-each-ref",
+        "--format=%(refname:short) %(upstream:short)",
+        "refs/heads",
+    ];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    match cmd::stdout_to_string(exec) {
+        Ok(output) => output
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let branch = parts.next()?.to_string();
+                let upstream = parts.next().map(|upstream| upstream.to_string());
+                Some((branch, upstream))
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Return the number of commits on HEAD and local branches that are not reachable from any remote.
+pub(crate) fn unpushed_commits(path: &std::path::Path) -> Option<usize> {
+    let mut cmd = vec!["git", "rev-list", "--count"];
+    // Commits on a detached HEAD are counted. Repositories without commits have no HEAD.
+    if rev_exists(path, "HEAD") {
+        cmd.push("HEAD");
+    }
+    cmd.extend(["--branches", "--not", "--remotes"]);
+    let exec = cmd::exec_in_dir(&cmd, &path);
+
+    cmd::stdout_to_string(exec).ok()?.trim().parse().ok()
+}
+
+/// Return the refs contained in a bundle.
+pub(crate) fn bundle_heads(path: &std::path::Path, bundle: &std::path::Path) -> Vec<String> {
+    let bundle = bundle.to_string_lossy();
+    let cmd = ["git", "bundle", "list-heads", bundle.as_ref()];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    match cmd::stdout_to_string(exec) {
+        Ok(output) => output
+            .lines()
+            .filter_map(|line| line.split_once(' ').map(|(_, refname)| refname.to_string()))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
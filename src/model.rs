@@ -175,7 +175,7 @@ impl MultiVariable {
         }
     }
 
-    pub fn iter(&self) -> std::slice::Iter<Variable> {
+    pub fn iter(&self) -> std::slice::Iter<'_, Variable> {
         self.variables.iter()
     }
 }
@@ -193,16 +193,23 @@ pub struct Tree {
     pub variables: VariableMap,
     pub branch: Variable,
     pub(crate) branches: VariableMap,
+    pub(crate) tag: Variable,
+    pub(crate) commit: Variable,
     pub worktree: Variable,
     #[derivative(Default(value = r#""origin".to_string()"#))]
     pub(crate) default_remote: String,
     pub(crate) clone_depth: i64,
+    pub(crate) clone_filter: String,
+    pub(crate) depends: StringSet,
     pub(crate) is_single_branch: bool,
+    pub(crate) is_lfs: bool,
+    pub(crate) submodules: Submodules,
     pub is_symlink: bool,
     pub is_bare_repository: bool,
     pub is_worktree: bool,
     pub(crate) description: String,
     pub(crate) links: Vec<Variable>,
+    pub(crate) sparse_checkout: StringSet,
 
     name: String,
     path: Variable,
@@ -311,6 +318,7 @@ impl Tree {
         collections::append_map(&mut self.variables, &tree.variables);
         collections::append_map(&mut self.remotes, &tree.remotes);
         collections::append_set(&mut self.templates, &tree.templates);
+        collections::append_set(&mut self.depends, &tree.depends);
 
         // "environment" follow last-set-wins semantics.
         self.environment.append(&mut tree.environment.clone());
@@ -318,12 +326,24 @@ impl Tree {
         if tree.clone_depth > 0 {
             self.clone_depth = tree.clone_depth;
         }
+        if !tree.clone_filter.is_empty() {
+            self.clone_filter = tree.clone_filter.to_string();
+        }
+        if !tree.sparse_checkout.is_empty() {
+            self.sparse_checkout = tree.sparse_checkout.clone();
+        }
         if tree.is_bare_repository {
             self.is_bare_repository = tree.is_bare_repository;
         }
         if tree.is_single_branch {
             self.is_single_branch = tree.is_single_branch;
         }
+        if tree.is_lfs {
+            self.is_lfs = tree.is_lfs;
+        }
+        if tree.submodules != Submodules::None {
+            self.submodules = tree.submodules;
+        }
         if tree.is_worktree {
             self.is_worktree = tree.is_worktree;
         }
@@ -333,6 +353,12 @@ impl Tree {
         if !tree.branch.is_empty() {
             self.branch = tree.branch.clone();
         }
+        if !tree.tag.is_empty() {
+            self.tag = tree.tag.clone();
+        }
+        if !tree.commit.is_empty() {
+            self.commit = tree.commit.clone();
+        }
         if !tree.symlink.is_empty() {
             self.symlink = tree.symlink.clone();
         }
@@ -387,7 +413,20 @@ impl Tree {
         )
     }
 
+    /// Return the tag or commit that the tree is pinned to.
+    /// "commit" takes precedence over "tag" when both are configured.
+    pub(crate) fn pin(&self) -> Option<Pin> {
+        if let Some(commit) = self.commit.get_value().filter(|value| !value.is_empty()) {
+            return Some(Pin::Commit(commit.to_string()));
+        }
+        self.tag
+            .get_value()
+            .filter(|value| !value.is_empty())
+            .map(|tag| Pin::Tag(tag.to_string()))
+    }
+
     // Return the resolved "url" field for the default remote.
+    // The "garden.url-rewrites" rules are applied to the URL.
     pub(crate) fn eval_url(&self, eval_context: &EvalContext) -> Option<String> {
         self.get_url(
             eval_context.app_context,
@@ -395,6 +434,7 @@ impl Tree {
             eval_context.graft_config,
             eval_context.tree_context,
         )
+        .map(|url| eval_context.rewrite_url(&url))
     }
 
     // Return the resolved "url" field for the default remote.
@@ -598,6 +638,12 @@ pub struct Configuration {
     pub(crate) shell_exit_on_error: bool,
     pub(crate) shell_word_split: bool,
     pub(crate) tree_branches: bool,
+    /// The "garden.cache" directory expression. Caching is disabled when empty.
+    pub(crate) cache: String,
+    /// Use "git clone --dissociate" when cloning from cached mirrors.
+    pub(crate) cache_dissociate: bool,
+    /// URL prefixes and their replacements from "garden.url-rewrites".
+    pub(crate) url_rewrites: VariableMap,
     pub(crate) parent_id: Option<ConfigId>,
     id: Option<ConfigId>,
 }
@@ -654,6 +700,7 @@ impl Configuration {
             self.root.set_value(value);
         }
         self.update_tree_paths(app_context); // Resolve tree paths
+        self.update_tree_pins(app_context); // Resolve "tag" and "commit" pins
         self.synthesize_default_tree(); // Synthesize a tree if no trees exist.
                                         // Reset variables
         self.reset();
@@ -789,9 +836,29 @@ impl Configuration {
                 constants::GARDEN_TREE_BRANCHES => {
                     set_bool(name.as_str(), &expr, &mut self.tree_branches);
                 }
+                constants::GARDEN_CACHE => {
+                    // "garden -D garden.cache=true" enables the default cache directory.
+                    self.cache = match syntax::string_to_bool(&expr) {
+                        Some(true) => config::xdg_cache_dir().to_string_lossy().to_string(),
+                        Some(false) => String::new(),
+                        None => expr,
+                    };
+                }
+                constants::GARDEN_CACHE_MODE => {
+                    self.cache_dissociate = expr == constants::CACHE_MODE_DISSOCIATE;
+                }
                 _ => {
-                    self.override_variables
-                        .insert(name, Variable::new(expr, None));
+                    // "garden -D garden.url-rewrites.<prefix>=<replacement>"
+                    if let Some(prefix) = name
+                        .strip_prefix(constants::GARDEN_URL_REWRITES)
+                        .and_then(|prefix| prefix.strip_prefix('.'))
+                    {
+                        self.url_rewrites
+                            .insert(prefix.to_string(), Variable::new(expr, None));
+                    } else {
+                        self.override_variables
+                            .insert(name, Variable::new(expr, None));
+                    }
                 }
             }
         }
@@ -865,6 +932,30 @@ impl Configuration {
         }
     }
 
+    /// Evaluate the "tag" and "commit" fields for each tree.
+    /// Pins are evaluated once when the configuration is read, like tree paths.
+    fn update_tree_pins(&mut self, app_context: &ApplicationContext) {
+        let mut pin_values = Vec::new();
+        for (name, tree) in &self.trees {
+            if !tree.tag.is_empty() || !tree.commit.is_empty() {
+                pin_values.push((
+                    name.clone(),
+                    tree.tag.get_expr().clone(),
+                    tree.commit.get_expr().clone(),
+                ));
+            }
+        }
+
+        for (name, tag, commit) in &pin_values {
+            let tag = eval::value(app_context, self, tag);
+            let commit = eval::value(app_context, self, commit);
+            if let Some(tree) = self.trees.get_mut(name) {
+                tree.tag.set_value(tag);
+                tree.commit.set_value(commit);
+            }
+        }
+    }
+
     /// Create an implicit "." tree when no trees exist.
     fn synthesize_default_tree(&mut self) {
         if !self.commands.is_empty() && self.trees.is_empty() {
@@ -879,6 +970,38 @@ impl Configuration {
         }
     }
 
+    /// Apply the longest matching "garden.url-rewrites" prefix to a URL.
+    pub(crate) fn rewrite_url(&self, app_context: &ApplicationContext, url: &str) -> String {
+        let rewrite = self
+            .url_rewrites
+            .iter()
+            .filter(|(prefix, _)| !prefix.is_empty() && url.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len());
+        match rewrite {
+            Some((prefix, replacement)) => {
+                let replacement = eval::value(app_context, self, replacement.get_expr());
+                format!("{replacement}{}", &url[prefix.len()..])
+            }
+            None => url.to_string(),
+        }
+    }
+
+    /// Return the "garden.cache" directory when the mirror cache is enabled.
+    pub(crate) fn cache_path(
+        &self,
+        app_context: &ApplicationContext,
+    ) -> Option<std::path::PathBuf> {
+        if self.cache.is_empty() {
+            return None;
+        }
+        let value = eval::value(app_context, self, &self.cache);
+        if value.is_empty() {
+            return None;
+        }
+
+        Some(std::path::PathBuf::from(self.tree_path(&value)))
+    }
+
     /// Return a path string relative to the garden root
     pub(crate) fn tree_path(&self, path: &str) -> String {
         if std::path::PathBuf::from(path).is_absolute() {
@@ -1209,6 +1332,20 @@ impl EvalContext<'_> {
         EvalContext::new(app_context, config, graft_config, tree_context)
     }
 
+    /// Apply the "garden.url-rewrites" from the tree's configuration to a URL.
+    /// Trees from grafts use the graft's rewrites. The root configuration's rewrites,
+    /// including "-D garden.url-rewrites.<prefix>" overrides, are used when none match.
+    pub(crate) fn rewrite_url(&self, url: &str) -> String {
+        if let Some(graft_config) = self.graft_config {
+            let rewritten_url = graft_config.rewrite_url(self.app_context, url);
+            if rewritten_url != url {
+                return rewritten_url;
+            }
+        }
+
+        self.config.rewrite_url(self.app_context, url)
+    }
+
     /// Evaluate a tree variable.
     pub(crate) fn tree_value(&self, value: &str) -> String {
         eval::tree_value(
@@ -1318,6 +1455,48 @@ impl TreeQuery {
     }
 }
 
+/// Tree query predicates select trees by their current state on disk.
+#[derive(Clone, Debug)]
+pub enum TreePredicate {
+    /// "ahead()" matches trees with commits that have not been pushed upstream.
+    Ahead,
+    /// "behind()" matches trees that are behind their upstream branch.
+    Behind,
+    /// "branch(<glob>)" matches trees whose checked-out branch matches the pattern.
+    Branch(glob::Pattern),
+    /// "clean()" matches existing trees without uncommitted changes.
+    Clean,
+    /// "dirty()" matches trees with uncommitted changes.
+    Dirty,
+    /// "exists()" matches trees that exist on disk.
+    Exists,
+    /// "has-remote(<name>)" matches trees with a configured remote matching the pattern.
+    HasRemote(glob::Pattern),
+    /// "missing()" matches trees that do not exist on disk.
+    Missing,
+}
+
+impl_display_brief!(TreePredicate);
+
+impl TreePredicate {
+    /// Parse a tree query predicate. Returns None when the query is not a predicate.
+    pub fn new(query: &str) -> Option<Self> {
+        let (name, argument) = syntax::split_predicate(query)?;
+        let pattern = || glob::Pattern::new(argument).ok();
+        match (name, argument.is_empty()) {
+            ("ahead", true) => Some(Self::Ahead),
+            ("behind", true) => Some(Self::Behind),
+            ("branch", false) => pattern().map(Self::Branch),
+            ("clean", true) => Some(Self::Clean),
+            ("dirty", true) => Some(Self::Dirty),
+            ("exists", true) => Some(Self::Exists),
+            ("has-remote", false) => pattern().map(Self::HasRemote),
+            ("missing", true) => Some(Self::Missing),
+            _ => None,
+        }
+    }
+}
+
 #[derive(
     Clone,
     Debug,
@@ -1386,6 +1565,65 @@ impl ColorMode {
     }
 }
 
+/// Output formats for commands that can emit structured documents.
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    strum_macros::VariantNames,
+)]
+#[strum(ascii_case_insensitive, serialize_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// JSON document
+    Json,
+    /// YAML document
+    Yaml,
+}
+
+impl OutputFormat {
+    /// Parse an output format from a string using strum's from_str().
+    pub fn parse_from_str(string: &str) -> Result<OutputFormat, String> {
+        OutputFormat::from_str(string).map_err(|_| format!("choices are {:?}", Self::VARIANTS))
+    }
+}
+
+/// Output modes for commands that run in multiple trees.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    strum_macros::VariantNames,
+)]
+#[strum(ascii_case_insensitive, serialize_all = "kebab-case")]
+pub enum OutputMode {
+    /// Commands write directly to the terminal
+    #[default]
+    Stream,
+    /// Each line of output is prefixed with the tree name
+    Prefix,
+    /// Output is buffered and displayed once each tree finishes
+    Group,
+}
+
+impl OutputMode {
+    /// Parse an output mode from a string using strum's from_str().
+    pub fn parse_from_str(string: &str) -> Result<OutputMode, String> {
+        OutputMode::from_str(string).map_err(|_| format!("choices are {:?}", Self::VARIANTS))
+    }
+}
+
 #[derive(Debug)]
 pub struct ApplicationContext {
     pub options: cli::MainOptions,
@@ -1573,6 +1811,46 @@ impl ApplicationContext {
     }
 }
 
+/// Represent the "submodules" setting for a tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Submodules {
+    /// Submodules are not initialized by "garden grow".
+    #[default]
+    None,
+    /// Submodules are initialized and updated.
+    Update,
+    /// Submodules are initialized and updated recursively.
+    Recursive,
+}
+
+/// Represent a tag or commit that a tree is pinned to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Pin {
+    /// A tree pinned using "tag: <name>".
+    Tag(String),
+    /// A tree pinned using "commit: <commit-id>".
+    Commit(String),
+}
+
+impl Pin {
+    /// Return the revision that is fetched and checked out for the pin.
+    pub(crate) fn rev(&self) -> String {
+        match self {
+            Pin::Tag(tag) => format!("refs/tags/{tag}"),
+            Pin::Commit(commit) => commit.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Pin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pin::Tag(tag) => write!(f, "{}: {tag}", constants::TAG),
+            Pin::Commit(commit) => write!(f, "{}: {commit}", constants::COMMIT),
+        }
+    }
+}
+
 /// Represent the different types of Git worktree.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GitTreeType {
@@ -1596,3 +1874,37 @@ pub struct GitTreeDetails {
 }
 
 impl_display!(GitTreeDetails);
+
+/// Represent the "git status" details for a worktree.
+#[derive(Clone, Debug, Default)]
+pub struct GitStatus {
+    /// The current branch name or an abbreviated commit ID when detached.
+    pub branch: String,
+    /// The upstream branch, if configured.
+    pub upstream: Option<String>,
+    /// Number of commits ahead of the upstream branch.
+    pub ahead: usize,
+    /// Number of commits behind the upstream branch.
+    pub behind: usize,
+    /// Number of files with staged changes.
+    pub staged: usize,
+    /// Number of files with unstaged changes.
+    pub modified: usize,
+    /// Number of untracked files.
+    pub untracked: usize,
+    /// Number of files with unresolved merge conflicts.
+    pub conflicts: usize,
+    /// Number of stash entries.
+    pub stashes: usize,
+    /// True when HEAD is detached.
+    pub is_detached: bool,
+}
+
+impl_display!(GitStatus);
+
+impl GitStatus {
+    /// Return true when the worktree has staged, modified, conflicted or untracked files.
+    pub fn is_dirty(&self) -> bool {
+        self.staged > 0 || self.modified > 0 || self.untracked > 0 || self.conflicts > 0
+    }
+}
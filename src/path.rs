@@ -25,9 +25,9 @@ pub fn abspath(path: &std::path::Path) -> std::path::PathBuf {
 /// Return the basename of a path-like string.
 pub(crate) fn str_basename(path: &str) -> &str {
     let basename = if path.contains('/') {
-        path.split('/').last().unwrap_or(path)
+        path.split('/').next_back().unwrap_or(path)
     } else if path.contains('\\') {
-        path.split('\\').last().unwrap_or(path)
+        path.split('\\').next_back().unwrap_or(path)
     } else {
         path
     };
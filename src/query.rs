@@ -1,4 +1,6 @@
-use crate::{constants, errors, eval, model, path, query, syntax};
+use std::collections::{HashMap, HashSet};
+
+use crate::{constants, errors, eval, git, model, path, query, syntax};
 
 /// Resolve a tree query into a `Vec<garden::model::TreeContext>`.
 ///
@@ -8,13 +10,37 @@ use crate::{constants, errors, eval, model, path, query, syntax};
 ///
 /// Returns:
 /// - `Vec<garden::model::TreeContext>`
-
 pub fn resolve_trees(
     app_context: &model::ApplicationContext,
     config: &model::Configuration,
     graft_config: Option<&model::Configuration>,
     query: &str,
 ) -> Vec<model::TreeContext> {
+    if !syntax::is_compound_query(query) {
+        return resolve_query(app_context, config, graft_config, query);
+    }
+    // Gardens, groups and trees whose names contain query operators are matched
+    // literally. The query is only parsed as a compound query when it matches nothing.
+    let result = resolve_query(app_context, config, graft_config, query);
+    if !result.is_empty() {
+        return result;
+    }
+
+    resolve_compound_trees(app_context, config, graft_config, query)
+}
+
+/// Resolve a single tree query that does not contain query operators.
+fn resolve_query(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    graft_config: Option<&model::Configuration>,
+    query: &str,
+) -> Vec<model::TreeContext> {
+    if let Some(predicate) = model::TreePredicate::new(query) {
+        let mut result = all_trees(config, graft_config);
+        result.retain(|context| matches_predicate(app_context, context, &predicate));
+        return result;
+    }
     let mut result = Vec::new();
     let tree_query = model::TreeQuery::new(query);
     let pattern = &tree_query.pattern;
@@ -101,6 +127,176 @@ pub fn resolve_trees(
     result
 }
 
+/// Resolve a compound tree query into a de-duplicated `Vec<garden::model::TreeContext>`.
+///
+/// Comma-separated queries are combined into a union. Queries prefixed with "!" or "-"
+/// remove trees from the union built up to that point, e.g. ":all,-%legacy".
+/// Queries joined with "&" resolve to the trees found in all of the joined queries.
+/// Trees are returned in the order that they are first encountered.
+fn resolve_compound_trees(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    graft_config: Option<&model::Configuration>,
+    query: &str,
+) -> Vec<model::TreeContext> {
+    let mut result: Vec<model::TreeContext> = Vec::new();
+    for term in query.split(',').map(str::trim) {
+        if term.is_empty() {
+            continue;
+        }
+        if let Some(excluded_term) = syntax::trim_query_exclusion(term) {
+            // Predicates are only evaluated against the trees that remain.
+            if let Some(predicate) = model::TreePredicate::new(excluded_term) {
+                result.retain(|context| !matches_predicate(app_context, context, &predicate));
+                continue;
+            }
+            let excluded = resolve_intersection(app_context, config, graft_config, excluded_term);
+            result.retain(|context| !contains_tree(app_context, &excluded, context));
+            continue;
+        }
+        for context in resolve_intersection(app_context, config, graft_config, term) {
+            let config_id = context_config_id(app_context, &context);
+            let is_duplicate = result.iter().any(|existing| {
+                existing.tree == context.tree
+                    && existing.garden == context.garden
+                    && context_config_id(app_context, existing) == config_id
+            });
+            if !is_duplicate {
+                result.push(context);
+            }
+        }
+    }
+
+    result
+}
+
+/// Resolve an "&"-separated tree query into the trees found in all of its queries.
+/// Queries prefixed with "!" or "-" are removed from the result.
+/// Predicates are evaluated last so that only the remaining trees are inspected.
+fn resolve_intersection(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    graft_config: Option<&model::Configuration>,
+    query: &str,
+) -> Vec<model::TreeContext> {
+    let mut result: Option<Vec<model::TreeContext>> = None;
+    let mut excluded = Vec::new();
+    // Predicates are paired with the value that they must evaluate to.
+    let mut predicates: Vec<(model::TreePredicate, bool)> = Vec::new();
+    for term in query.split('&').map(str::trim) {
+        if term.is_empty() {
+            continue;
+        }
+        let excluded_term = syntax::trim_query_exclusion(term);
+        if let Some(predicate) = model::TreePredicate::new(excluded_term.unwrap_or(term)) {
+            predicates.push((predicate, excluded_term.is_none()));
+            continue;
+        }
+        if let Some(excluded_term) = excluded_term {
+            excluded.append(&mut resolve_trees(
+                app_context,
+                config,
+                graft_config,
+                excluded_term,
+            ));
+            continue;
+        }
+        let contexts = resolve_trees(app_context, config, graft_config, term);
+        result = Some(match result {
+            Some(current) => current
+                .into_iter()
+                .filter(|context| contains_tree(app_context, &contexts, context))
+                .collect(),
+            None => contexts,
+        });
+    }
+    // Predicates without other queries select from all trees.
+    let mut result = match result {
+        Some(result) => result,
+        None if !predicates.is_empty() => all_trees(config, graft_config),
+        None => Vec::new(),
+    };
+    result.retain(|context| !contains_tree(app_context, &excluded, context));
+    result.retain(|context| {
+        predicates
+            .iter()
+            .all(|(predicate, value)| matches_predicate(app_context, context, predicate) == *value)
+    });
+
+    result
+}
+
+/// Return tree contexts for all of the trees in the current configuration.
+fn all_trees(
+    config: &model::Configuration,
+    graft_config: Option<&model::Configuration>,
+) -> Vec<model::TreeContext> {
+    let pattern = glob::Pattern::new("*").unwrap_or_default();
+    trees(graft_config.unwrap_or(config), &pattern)
+}
+
+/// Evaluate a tree query predicate against the tree's current state on disk.
+fn matches_predicate(
+    app_context: &model::ApplicationContext,
+    context: &model::TreeContext,
+    predicate: &model::TreePredicate,
+) -> bool {
+    let config = app_context.get_config(context_config_id(app_context, context));
+    let Some(path) = config
+        .trees
+        .get(&context.tree)
+        .and_then(|tree| tree.pathbuf())
+    else {
+        return false;
+    };
+    let exists = path.exists();
+    match predicate {
+        model::TreePredicate::Exists => exists,
+        model::TreePredicate::Missing => !exists,
+        _ if !exists => false,
+        model::TreePredicate::Branch(pattern) => git::symbolic_branch(&path)
+            .map(|branch| pattern.matches(&branch))
+            .unwrap_or(false),
+        model::TreePredicate::HasRemote(pattern) => git::remotes(&path)
+            .iter()
+            .any(|remote| pattern.matches(remote)),
+        model::TreePredicate::Ahead => git::status(&path)
+            .map(|status| status.ahead > 0)
+            .unwrap_or(false),
+        model::TreePredicate::Behind => git::status(&path)
+            .map(|status| status.behind > 0)
+            .unwrap_or(false),
+        model::TreePredicate::Clean => git::status(&path)
+            .map(|status| !status.is_dirty())
+            .unwrap_or(false),
+        model::TreePredicate::Dirty => git::status(&path)
+            .map(|status| status.is_dirty())
+            .unwrap_or(false),
+    }
+}
+
+/// Return true when the contexts contain the same tree as the specified context.
+/// Trees are compared by name and configuration regardless of their garden or group.
+fn contains_tree(
+    app_context: &model::ApplicationContext,
+    contexts: &[model::TreeContext],
+    context: &model::TreeContext,
+) -> bool {
+    let config_id = context_config_id(app_context, context);
+    contexts.iter().any(|entry| {
+        entry.tree == context.tree && context_config_id(app_context, entry) == config_id
+    })
+}
+
+/// Return the configuration ID for a context. Contexts without a configuration
+/// belong to the root configuration.
+fn context_config_id(
+    app_context: &model::ApplicationContext,
+    context: &model::TreeContext,
+) -> model::ConfigId {
+    context.config.unwrap_or_else(|| app_context.get_root_id())
+}
+
 /// Resolve a tree query into a filtered `Vec<garden::model::TreeContext>`.
 ///
 /// Parameters:
@@ -132,7 +328,6 @@ pub(crate) fn resolve_and_filter_trees(
 /// Parameters:
 /// - config: `&garden::model::Configuration`
 /// - pattern: `&glob::Pattern`
-
 fn garden_trees(
     app_context: &model::ApplicationContext,
     config: &model::Configuration,
@@ -252,7 +447,6 @@ pub fn trees_from_group(
 /// - tree: Tree name `&str`
 /// - garden_name: optional name of the garden in which to operate.
 /// - group: optional name of the group in which to operate.
-
 pub fn tree_from_name(
     config: &model::Configuration,
     tree_name: &str,
@@ -284,7 +478,6 @@ pub fn tree_from_name(
 /// - config: `&garden::model::Configuration`
 /// - tree: Tree name pattern `&str`
 /// - garden_name: `Option<garden::model::GardenName>`
-
 pub fn trees_from_pattern(
     app_context: &model::ApplicationContext,
     config: &model::Configuration,
@@ -413,7 +606,6 @@ fn tree_from_pathbuf(
 }
 
 /// Return the name of an existing tree from the specified path.
-
 pub fn tree_name_from_path(
     config: &model::Configuration,
     path: &std::path::Path,
@@ -422,7 +614,6 @@ pub fn tree_name_from_path(
 }
 
 /// Return the name of an existing tree from an absolute path.
-
 pub(crate) fn tree_name_from_abspath(
     config: &model::Configuration,
     path: &std::path::Path,
@@ -451,7 +642,6 @@ pub(crate) fn tree_name_from_abspath(
 }
 
 /// Returns tree contexts matching the specified pattern
-
 fn trees(config: &model::Configuration, pattern: &glob::Pattern) -> Vec<model::TreeContext> {
     let mut result = Vec::new();
     for (tree_name, tree) in &config.trees {
@@ -470,7 +660,6 @@ fn trees(config: &model::Configuration, pattern: &glob::Pattern) -> Vec<model::T
 
 /// Return a Result<garden::model::TreeContext, garden::errors::GardenError>
 /// when the tree and optional garden are present.
-
 pub fn tree_context(
     app_context: &model::ApplicationContext,
     config: &model::Configuration,
@@ -600,3 +789,164 @@ pub(crate) fn shared_worktree_path(
 
     tree.get_name().to_string()
 }
+
+/// Return the fully-qualified name for a tree context.
+/// Grafted trees are returned using their namespaced "graft::tree" names.
+pub(crate) fn qualified_tree_name(
+    app_context: &model::ApplicationContext,
+    context: &model::TreeContext,
+) -> String {
+    let mut name = context.tree.to_string();
+    let mut config_id = context.config;
+    while let Some(id) = config_id {
+        let Some(parent_id) = app_context.get_config(id).parent_id else {
+            break;
+        };
+        let parent = app_context.get_config(parent_id);
+        if let Some(graft) = parent
+            .grafts
+            .values()
+            .find(|graft| graft.get_id() == Some(id))
+        {
+            name = format!("{}::{}", graft.get_name(), name);
+        }
+        config_id = Some(parent_id);
+    }
+
+    name
+}
+
+/// Order tree contexts so that trees are visited after the trees they depend upon.
+///
+/// Returns the ordered contexts along with the indexes of the contexts that each
+/// context depends upon. Dependencies that were not resolved by the query are
+/// followed transitively but are otherwise not included in the results.
+pub(crate) fn sort_by_dependencies(
+    app_context: &model::ApplicationContext,
+    contexts: Vec<model::TreeContext>,
+) -> Result<(Vec<model::TreeContext>, Vec<Vec<usize>>), errors::GardenError> {
+    // Index the contexts by configuration and tree name.
+    let mut indexes: HashMap<(model::ConfigId, String), Vec<usize>> = HashMap::new();
+    for (idx, context) in contexts.iter().enumerate() {
+        indexes
+            .entry((
+                context_config_id(app_context, context),
+                context.tree.to_string(),
+            ))
+            .or_default()
+            .push(idx);
+    }
+    let mut dependencies: Vec<Vec<usize>> = Vec::with_capacity(contexts.len());
+    // Trees that were already checked for cycles are not traversed again.
+    let mut checked: HashMap<model::ConfigId, HashSet<String>> = HashMap::new();
+    for (idx, context) in contexts.iter().enumerate() {
+        let config_id = context_config_id(app_context, context);
+        let config = app_context.get_config(config_id);
+        check_dependency_cycles(
+            config,
+            &context.tree,
+            &mut Vec::new(),
+            checked.entry(config_id).or_default(),
+        )?;
+        // Walk the dependency graph until a tree from the query is found.
+        let mut deps: Vec<usize> = Vec::new();
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut pending: Vec<&str> = match config.trees.get(&context.tree) {
+            Some(tree) => tree.depends.iter().map(String::as_str).collect(),
+            None => Vec::new(),
+        };
+        while let Some(name) = pending.pop() {
+            if !visited.insert(name) {
+                continue;
+            }
+            if let Some(dep_indexes) = indexes.get(&(config_id, name.to_string())) {
+                deps.extend(dep_indexes.iter().filter(|dep_idx| **dep_idx != idx));
+                continue;
+            }
+            if let Some(tree) = config.trees.get(name) {
+                pending.extend(tree.depends.iter().map(String::as_str));
+            }
+        }
+        deps.sort_unstable();
+        deps.dedup();
+        dependencies.push(deps);
+    }
+
+    // Visit dependencies depth-first so that the original order is retained
+    // for trees that do not depend on each other.
+    let mut visited = vec![false; contexts.len()];
+    let mut order = Vec::with_capacity(contexts.len());
+    for idx in 0..contexts.len() {
+        visit_dependencies(idx, &dependencies, &mut visited, &mut order);
+    }
+
+    // Remap the dependency indexes onto the sorted order.
+    let mut positions = vec![0; contexts.len()];
+    for (position, idx) in order.iter().enumerate() {
+        positions[*idx] = position;
+    }
+    let sorted_dependencies = order
+        .iter()
+        .map(|idx| {
+            dependencies[*idx]
+                .iter()
+                .map(|dep_idx| positions[*dep_idx])
+                .collect()
+        })
+        .collect();
+    let mut contexts: Vec<Option<model::TreeContext>> = contexts.into_iter().map(Some).collect();
+    let sorted_contexts = order
+        .iter()
+        .filter_map(|idx| contexts[*idx].take())
+        .collect();
+
+    Ok((sorted_contexts, sorted_dependencies))
+}
+
+/// Append a context index to the order after appending its dependencies.
+fn visit_dependencies(
+    idx: usize,
+    dependencies: &[Vec<usize>],
+    visited: &mut [bool],
+    order: &mut Vec<usize>,
+) {
+    if visited[idx] {
+        return;
+    }
+    visited[idx] = true;
+    for dep_idx in &dependencies[idx] {
+        visit_dependencies(*dep_idx, dependencies, visited, order);
+    }
+    order.push(idx);
+}
+
+/// Return an error naming the cycle when a tree's dependencies form a cycle.
+pub(crate) fn check_dependency_cycles(
+    config: &model::Configuration,
+    name: &str,
+    stack: &mut Vec<String>,
+    checked: &mut HashSet<String>,
+) -> Result<(), errors::GardenError> {
+    if checked.contains(name) {
+        return Ok(());
+    }
+    if let Some(start) = stack.iter().position(|entry| entry == name) {
+        let mut cycle = stack[start..].to_vec();
+        cycle.push(name.to_string());
+        return Err(errors::GardenError::ConfigurationError(format!(
+            "dependency cycle: {}",
+            cycle.join(" -> ")
+        )));
+    }
+    let Some(tree) = config.trees.get(name) else {
+        return Ok(());
+    };
+    stack.push(name.to_string());
+    for dep in &tree.depends {
+        check_dependency_cycles(config, dep, stack, checked)?;
+    }
+    stack.pop();
+    checked.insert(name.to_string());
+
+    Ok(())
+}
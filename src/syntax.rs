@@ -46,6 +46,63 @@ pub(crate) fn is_graft(string: &str) -> bool {
     string.contains("::")
 }
 
+/// Return true if `string` contains the "," union, "&" intersection or "!" and "-"
+/// exclusion operators. Queries containing operators are only parsed as compound queries
+/// when the query as a whole does not match the name of a garden, group or tree.
+#[inline]
+pub(crate) fn is_compound_query(string: &str) -> bool {
+    string.contains(',') || string.contains('&') || trim_query_exclusion(string).is_some()
+}
+
+/// Trim the "!" or "-" exclusion prefix from a tree query.
+/// Returns None when the query is not an exclusion.
+#[inline]
+pub(crate) fn trim_query_exclusion(string: &str) -> Option<&str> {
+    string
+        .strip_prefix('!')
+        .or_else(|| string.strip_prefix('-'))
+}
+
+/// Split a `name(argument)` tree query predicate into its name and argument.
+/// Returns None when the query is not a predicate.
+#[inline]
+pub(crate) fn split_predicate(string: &str) -> Option<(&str, &str)> {
+    let (name, argument) = string.strip_suffix(')')?.split_once('(')?;
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_lowercase() || c == '-') {
+        return None;
+    }
+
+    Some((name, argument.trim()))
+}
+
+/// Return the names of the `${name}` variables referenced by `string`.
+/// Escaped `$${name}` references and `${name:-default}` expressions are skipped.
+pub(crate) fn variable_references(string: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut remainder = string;
+    while let Some(idx) = remainder.find('$') {
+        let after = &remainder[idx + 1..];
+        if let Some(escaped) = after.strip_prefix('$') {
+            remainder = escaped;
+            continue;
+        }
+        let Some(expr) = after.strip_prefix('{') else {
+            remainder = after;
+            continue;
+        };
+        let Some(end) = expr.find('}') else {
+            break;
+        };
+        let name = &expr[..end];
+        if !name.is_empty() && !name.contains(":-") {
+            result.push(name);
+        }
+        remainder = &expr[end + 1..];
+    }
+
+    result
+}
+
 /// Return true if `string` is a candidate for evaluation.
 /// Returns true for strings with ${vars}  and "$ exec" expressions.
 #[inline]
@@ -244,6 +301,52 @@ mod tests {
         assert!(!super::is_group("group"), "group is not a group");
     }
 
+    #[test]
+    fn is_compound_query() {
+        assert!(
+            super::is_compound_query(":all,-%legacy"),
+            "unions are compound"
+        );
+        assert!(
+            super::is_compound_query("%a&%b"),
+            "intersections are compound"
+        );
+        assert!(super::is_compound_query("!tree"), "exclusions are compound");
+        assert!(
+            !super::is_compound_query("tree-name"),
+            "tree-name is not compound"
+        );
+        assert!(!super::is_compound_query("@tree"), "@tree is not compound");
+    }
+
+    #[test]
+    fn split_predicate() {
+        assert_eq!(super::split_predicate("dirty()"), Some(("dirty", "")));
+        assert_eq!(
+            super::split_predicate("branch(feature/*)"),
+            Some(("branch", "feature/*"))
+        );
+        assert_eq!(
+            super::split_predicate("has-remote( upstream )"),
+            Some(("has-remote", "upstream"))
+        );
+        assert_eq!(super::split_predicate("dirty"), None);
+        assert_eq!(super::split_predicate("@tree(1)"), None);
+    }
+
+    #[test]
+    fn variable_references() {
+        assert_eq!(
+            super::variable_references("${GARDEN_ROOT}/${name}"),
+            vec!["GARDEN_ROOT", "name"]
+        );
+        assert_eq!(
+            super::variable_references("$${escaped} ${graft::value}"),
+            vec!["graft::value"]
+        );
+        assert!(super::variable_references("$ echo ${value:-default} $HOME").is_empty());
+    }
+
     #[test]
     fn is_tree() {
         assert!(super::is_tree("@tree"), "@tree is a tree");
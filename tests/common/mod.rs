@@ -154,6 +154,35 @@ pub fn exec_garden(args: &[&str]) -> Result<()> {
     Ok(())
 }
 
+/// Execute the "garden" command with the specified arguments and environment variables.
+pub fn exec_garden_env(args: &[&str], env: &[(&str, &str)]) -> Result<()> {
+    let mut argv: Vec<&str> = vec!["garden"];
+    argv.extend(args);
+    display::print_command_vec(&argv);
+
+    let mut exec = Command::cargo_bin("garden").expect("garden not found");
+    exec.args(args);
+    exec.envs(env.iter().copied());
+
+    assert!(exec.status().expect("garden returned an error").success());
+    Ok(())
+}
+
+/// Execute the "garden" command with the specified arguments and return its exit status.
+pub fn exec_garden_code(args: &[&str]) -> i32 {
+    let mut argv: Vec<&str> = vec!["garden"];
+    argv.extend(args);
+    display::print_command_vec(&argv);
+
+    let mut exec = Command::cargo_bin("garden").expect("garden not found");
+    exec.args(args);
+
+    exec.status()
+        .expect("garden could not be executed")
+        .code()
+        .unwrap_or(errors::EX_ERROR)
+}
+
 /// Execute a command and ensure that exit status 0 is returned.
 /// Return the captured stdout value as a string.
 pub fn garden_capture(args: &[&str]) -> String {
@@ -172,6 +201,42 @@ pub fn garden_capture(args: &[&str]) -> String {
     utf8_result.unwrap().trim_end().into()
 }
 
+/// Execute a garden command and return the captured stderr value as a string.
+pub fn garden_capture_stderr(args: &[&str]) -> String {
+    let mut argv: Vec<&str> = vec!["garden"];
+    argv.extend(args);
+    display::print_command_vec(&argv);
+
+    let mut exec = Command::cargo_bin("garden").expect("garden not found");
+    exec.args(args);
+    let capture = exec.output();
+    assert!(capture.is_ok());
+
+    let utf8_result = String::from_utf8(capture.unwrap().stderr);
+    assert!(utf8_result.is_ok());
+
+    utf8_result.unwrap().trim_end().into()
+}
+
+/// Execute a garden command with additional environment variables and return
+/// the captured stdout value as a string.
+pub fn garden_capture_env(args: &[&str], env: &[(&str, &str)]) -> String {
+    let mut argv: Vec<&str> = vec!["garden"];
+    argv.extend(args);
+    display::print_command_vec(&argv);
+
+    let mut exec = Command::cargo_bin("garden").expect("garden not found");
+    exec.args(args);
+    exec.envs(env.iter().copied());
+    let capture = exec.output();
+    assert!(capture.is_ok());
+
+    let utf8_result = String::from_utf8(capture.unwrap().stdout);
+    assert!(utf8_result.is_ok());
+
+    utf8_result.unwrap().trim_end().into()
+}
+
 /// Execute a command and ensure that the exit status is returned.
 pub fn assert_cmd_status(cmd: &[&str], directory: &str, status: i32) {
     display::print_command_vec(cmd);
@@ -1,10 +1,11 @@
 pub mod common;
 use common::{
-    assert_cmd, assert_cmd_capture, assert_ref, assert_ref_missing, exec_garden, garden_capture,
+    assert_cmd, assert_cmd_capture, assert_cmd_status, assert_ref, assert_ref_missing, exec_garden,
+    exec_garden_code, exec_garden_env, garden_capture, garden_capture_env, garden_capture_stderr,
     BareRepoFixture,
 };
 
-use garden::git;
+use garden::{errors, git};
 
 use anyhow::Result;
 use function_name::named;
@@ -2387,3 +2388,2042 @@ fn cmd_prune_depth() -> Result<()> {
 
     Ok(())
 }
+
+/// "garden status" reports the branch and changes for each tree.
+#[test]
+#[named]
+fn status_reports_changes() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    exec_garden(&[
+        "--chdir",
+        &fixture.root(),
+        "--config",
+        "tests/data/garden.yaml",
+        "grow",
+        "example/tree",
+    ])?;
+    let worktree = fixture.worktree("example/tree/repo");
+
+    let output = garden_capture(&[
+        "--chdir",
+        &fixture.root(),
+        "--config",
+        "tests/data/garden.yaml",
+        "status",
+        "example/tree",
+        "example/shallow",
+    ]);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("# example/tree "));
+    assert!(lines[0].contains(" default "));
+    assert!(lines[0].contains("↑0 ↓0"));
+    assert!(lines[0].ends_with("clean"));
+    assert_eq!(lines[1], "#- example/shallow");
+
+    // Clean trees pass "garden status --check".
+    let status = exec_garden_code(&[
+        "--chdir",
+        &fixture.root(),
+        "--config",
+        "tests/data/garden.yaml",
+        "status",
+        "--check",
+        "example/tree",
+    ]);
+    assert_eq!(status, 0);
+
+    // Trees whose status cannot be read fail "garden status --check".
+    std::fs::write(format!("{worktree}/.git/index"), "corrupt")?;
+    let status = exec_garden_code(&[
+        "--chdir",
+        &fixture.root(),
+        "--config",
+        "tests/data/garden.yaml",
+        "status",
+        "--check",
+        "example/tree",
+    ]);
+    assert_ne!(status, 0);
+    std::fs::remove_file(format!("{worktree}/.git/index"))?;
+    assert_cmd(&["git", "reset", "--quiet"], &worktree);
+
+    // Untracked files and stashes are reported.
+    std::fs::write(format!("{worktree}/untracked.txt"), "untracked\n")?;
+    std::fs::write(format!("{worktree}/stashed.txt"), "stashed\n")?;
+    assert_cmd(&["git", "add", "stashed.txt"], &worktree);
+    assert_cmd(&["git", "stash", "--quiet"], &worktree);
+
+    let output = garden_capture(&[
+        "--chdir",
+        &fixture.root(),
+        "--config",
+        "tests/data/garden.yaml",
+        "status",
+        "--jobs",
+        "2",
+        "example/tree",
+    ]);
+    assert!(output.contains("1 untracked"));
+    assert!(output.contains("1 stashed"));
+
+    // Dirty trees fail "garden status --check".
+    let status = exec_garden_code(&[
+        "--chdir",
+        &fixture.root(),
+        "--config",
+        "tests/data/garden.yaml",
+        "status",
+        "--check",
+        "example/tree",
+    ]);
+    assert_ne!(status, 0);
+
+    Ok(())
+}
+
+/// "garden ls --format json|yaml" emits structured documents with evaluated values.
+#[test]
+#[named]
+fn list_structured_formats() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    exec_garden(&[
+        "--chdir",
+        &fixture.root(),
+        "--config",
+        "tests/data/garden.yaml",
+        "grow",
+        "example/tree",
+    ])?;
+    let root = std::fs::canonicalize(fixture.root_pathbuf())?;
+    let root = root.to_string_lossy();
+
+    let output = garden_capture(&[
+        "--chdir",
+        &fixture.root(),
+        "--config",
+        "tests/data/garden.yaml",
+        "ls",
+        "--format",
+        "json",
+        "example/tree",
+        "example/shallow",
+        "link",
+    ]);
+    let doc: serde_json::Value = serde_json::from_str(&output)?;
+    let trees = doc["trees"].as_array().unwrap();
+    assert_eq!(trees.len(), 3);
+    assert_eq!(trees[0]["name"], "example/tree");
+    assert_eq!(trees[0]["path"], format!("{root}/example/tree/repo"));
+    assert_eq!(trees[0]["exists"], true);
+    assert_eq!(
+        trees[0]["remotes"]["origin"],
+        format!("file://{root}/repos/example.git")
+    );
+    assert_eq!(
+        trees[0]["remotes"]["publish"],
+        "git@github.com:user/example.git"
+    );
+    assert_eq!(trees[1]["name"], "example/shallow");
+    assert_eq!(trees[1]["exists"], false);
+    assert_eq!(trees[2]["symlink"], format!("{root}/example/tree/repo"));
+    assert_eq!(doc["groups"]["trees"][0], "tree1");
+    assert_eq!(
+        doc["gardens"]["grafted-garden"]["trees"][0],
+        "graft::prebuilt"
+    );
+
+    // Worktree parents are reported using their evaluated tree names.
+    let output = garden_capture(&[
+        "--chdir",
+        &fixture.root(),
+        "--config",
+        "tests/data/worktree.yaml",
+        "ls",
+        "--format",
+        "yaml",
+        "dev",
+    ]);
+    let docs = yaml_rust::YamlLoader::load_from_str(&output)?;
+    let tree = &docs[0]["trees"][0];
+    assert_eq!(tree["name"].as_str(), Some("dev"));
+    assert_eq!(tree["branch"].as_str(), Some("dev"));
+    assert_eq!(tree["worktree"].as_str(), Some("default"));
+    assert_eq!(tree["exists"].as_bool(), Some(false));
+    assert_eq!(
+        tree["commands"]["echo"][0].as_str(),
+        Some("echo ${TREE_NAME} \"$@\"")
+    );
+
+    Ok(())
+}
+
+/// "garden update" fetches and fast-forwards clean trees and skips dirty and detached trees.
+#[test]
+#[named]
+fn update_fast_forwards_clean_trees() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    exec_garden(&[
+        "--chdir",
+        &fixture.root(),
+        "--config",
+        "tests/data/garden.yaml",
+        "grow",
+        "example/shallow",
+        "example/default-remote-url",
+    ])?;
+    let shallow = fixture.worktree("example/tree/shallow");
+    let default_remote = fixture.worktree("example/tree/default-remote");
+
+    // Add a new commit to the upstream repository.
+    let repo = fixture.path("repos/example.git");
+    let tree = assert_cmd_capture(&["git", "rev-parse", "default^{tree}"], &repo);
+    let commit = assert_cmd_capture(
+        &[
+            "git",
+            "commit-tree",
+            "-m",
+            "commit 3",
+            "-p",
+            "default",
+            &tree,
+        ],
+        &repo,
+    );
+    assert_cmd(&["git", "update-ref", "refs/heads/default", &commit], &repo);
+
+    // Dirty trees are skipped.
+    std::fs::write(format!("{shallow}/untracked.txt"), "untracked\n")?;
+
+    let output = garden_capture(&[
+        "--chdir",
+        &fixture.root(),
+        "--config",
+        "tests/data/garden.yaml",
+        "update",
+        "--jobs",
+        "2",
+        "example/shallow",
+        "example/default-remote-url",
+        "example/single-branch",
+    ]);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("# example/shallow "));
+    assert!(lines[0].ends_with("skipped uncommitted changes"));
+    assert!(lines[1].starts_with("# example/default-remote-url "));
+    assert!(lines[1].ends_with("updated 1 commit"));
+    assert!(lines[2].starts_with("# example/single-branch "));
+    assert!(lines[2].ends_with("missing"));
+
+    let head = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &default_remote);
+    assert_eq!(head, commit);
+    let head = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &shallow);
+    assert_ne!(head, commit);
+
+    // Detached trees are skipped. Up-to-date trees are reported.
+    assert_cmd(&["git", "checkout", "--quiet", "--detach"], &shallow);
+    let output = garden_capture(&[
+        "--chdir",
+        &fixture.root(),
+        "--config",
+        "tests/data/garden.yaml",
+        "sync",
+        "example/shallow",
+        "example/default-remote-url",
+    ]);
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines[0].contains(" skipped "));
+    assert!(lines[0].ends_with("detached HEAD"));
+    assert!(lines[1].ends_with("up-to-date"));
+
+    Ok(())
+}
+
+/// "garden lock" records commits in "garden.lock" and "--locked" restores them.
+#[test]
+#[named]
+fn lock_and_checkout_locked_commits() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+grafts:
+  graft: graft.yaml
+trees:
+  example:
+    url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+"#,
+    )?;
+    std::fs::write(
+        format!("{root}/graft.yaml"),
+        r#"
+trees:
+  grafted:
+    url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+"#,
+    )?;
+    exec_garden(&["--chdir", &root, "grow", "example", "graft::grafted"])?;
+    let example = fixture.worktree("example");
+    let grafted = fixture.worktree("grafted");
+    let locked_commit = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &example);
+
+    // garden.lock contains grafted trees using their namespaced names.
+    exec_garden(&["--chdir", &root, "lock", "example", "graft::grafted"])?;
+    let lockfile = fixture.path("garden.lock");
+    let docs = yaml_rust::YamlLoader::load_from_str(&std::fs::read_to_string(&lockfile)?)?;
+    let trees = &docs[0]["trees"];
+    assert_eq!(
+        trees["example"]["commit"].as_str(),
+        Some(locked_commit.as_str())
+    );
+    assert_eq!(trees["example"]["branch"].as_str(), Some("default"));
+    assert!(trees["example"]["url"]
+        .as_str()
+        .unwrap_or_default()
+        .ends_with("/repos/example.git"));
+    assert_eq!(
+        trees["graft::grafted"]["commit"].as_str(),
+        Some(locked_commit.as_str())
+    );
+
+    // Move the trees away from the locked commits.
+    assert_cmd(&["git", "reset", "--quiet", "--hard", "HEAD~1"], &example);
+    assert_cmd(
+        &["git", "checkout", "--quiet", "--detach", "HEAD~1"],
+        &grafted,
+    );
+    // "garden checkout" only checks out locked commits.
+    let args = ["--chdir", &root, "checkout", "example"];
+    assert_ne!(exec_garden_code(&args), errors::EX_OK);
+    exec_garden(&[
+        "--chdir",
+        &root,
+        "checkout",
+        "--locked",
+        "example",
+        "graft::grafted",
+    ])?;
+
+    // The locked commit is checked out on a detached HEAD when the branch has moved.
+    let head = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &example);
+    assert_eq!(head, locked_commit);
+    assert_cmd_status(&["git", "symbolic-ref", "--quiet", "HEAD"], &example, 1);
+    // The locked branch is checked out when it points to the locked commit.
+    let head = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &grafted);
+    assert_eq!(head, locked_commit);
+    let branch = assert_cmd_capture(&["git", "symbolic-ref", "--short", "HEAD"], &grafted);
+    assert_eq!(branch, "default");
+
+    // "garden grow --locked" grows missing trees and checks out the locked commit.
+    std::fs::remove_dir_all(&example)?;
+    let repo = fixture.path("repos/example.git");
+    let tree = assert_cmd_capture(&["git", "rev-parse", "default^{tree}"], &repo);
+    let commit = assert_cmd_capture(
+        &[
+            "git",
+            "commit-tree",
+            "-m",
+            "commit 3",
+            "-p",
+            "default",
+            &tree,
+        ],
+        &repo,
+    );
+    assert_cmd(&["git", "update-ref", "refs/heads/default", &commit], &repo);
+    exec_garden(&["--chdir", &root, "grow", "--locked", "example"])?;
+    let head = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &example);
+    assert_eq!(head, locked_commit);
+
+    Ok(())
+}
+
+/// "garden cmd" visits trees in dependency order and detects cycles.
+#[test]
+#[named]
+fn cmd_dependency_order() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+trees:
+  app:
+    path: ${GARDEN_CONFIG_DIR}
+    depends: [lib, tool]
+  lib:
+    path: ${GARDEN_CONFIG_DIR}
+    depends: base
+  tool:
+    path: ${GARDEN_CONFIG_DIR}
+  base:
+    path: ${GARDEN_CONFIG_DIR}
+commands:
+  name: echo ${TREE_NAME}
+  check: test ${TREE_NAME} != lib && echo ${TREE_NAME}
+"#,
+    )?;
+    std::fs::write(
+        format!("{root}/cycle.yaml"),
+        r#"
+trees:
+  one:
+    path: ${GARDEN_CONFIG_DIR}
+    depends: two
+  two:
+    path: ${GARDEN_CONFIG_DIR}
+    depends: three
+  three:
+    path: ${GARDEN_CONFIG_DIR}
+    depends: one
+"#,
+    )?;
+
+    // Dependencies are visited before the trees that depend upon them.
+    let output = garden_capture(&["--chdir", &root, "--quiet", "cmd", "@*", "name"]);
+    assert_eq!(output, "base\nlib\ntool\napp");
+
+    // Unselected dependencies are followed transitively for ordering.
+    let output = garden_capture(&["--chdir", &root, "--quiet", "cmd", "app", "name"]);
+    assert_eq!(output, "app");
+    let output = garden_capture(&["--chdir", &root, "--quiet", "ls", "--graph"]);
+    assert!(output.contains("# base\n# lib\n  -> base\n# tool\n# app\n  -> lib\n  -> tool"));
+
+    // Every tree is visited in parallel mode once its dependencies have finished.
+    let output = garden_capture(&[
+        "--chdir", &root, "--quiet", "cmd", "--jobs", "2", "@*", "name",
+    ]);
+    let lines: Vec<&str> = output.lines().collect();
+    let position = |name: &str| lines.iter().position(|line| *line == name);
+    assert_eq!(lines.len(), 4);
+    assert!(position("base") < position("lib"));
+    assert!(position("lib") < position("app"));
+    assert!(position("tool") < position("app"));
+
+    // Trees are skipped in parallel mode when a dependency fails.
+    let output = garden_capture(&[
+        "--chdir", &root, "--quiet", "cmd", "--jobs", "2", "@*", "check",
+    ]);
+    let mut lines: Vec<&str> = output.lines().collect();
+    lines.sort();
+    assert_eq!(lines, vec!["base", "tool"]);
+    let status = exec_garden_code(&[
+        "--chdir", &root, "--quiet", "cmd", "--jobs", "2", "@*", "check",
+    ]);
+    assert_ne!(status, errors::EX_OK);
+
+    // Cycles are reported as configuration errors.
+    let status = exec_garden_code(&[
+        "--chdir",
+        &root,
+        "--config",
+        "cycle.yaml",
+        "cmd",
+        "one",
+        "name",
+    ]);
+    assert_eq!(status, errors::EX_CONFIG);
+
+    Ok(())
+}
+
+/// Names that contain query operators are matched before compound queries are parsed.
+#[test]
+#[named]
+fn query_operator_names() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+trees:
+  -tool:
+    path: ${GARDEN_CONFIG_DIR}
+  web,api:
+    path: ${GARDEN_CONFIG_DIR}
+  web:
+    path: ${GARDEN_CONFIG_DIR}
+  api:
+    path: ${GARDEN_CONFIG_DIR}
+groups:
+  web&api: [web, api]
+commands:
+  name: echo ${TREE_NAME}
+"#,
+    )?;
+
+    // Names containing operators are matched literally.
+    let output = garden_capture(&["--chdir", &root, "--quiet", "cmd", "web,api", "name"]);
+    assert_eq!(output, "web,api");
+    let output = garden_capture(&["--chdir", &root, "--quiet", "cmd", "@-tool", "name"]);
+    assert_eq!(output, "-tool");
+    let output = garden_capture(&["--chdir", &root, "--quiet", "cmd", "%web&api", "name"]);
+    assert_eq!(output, "web\napi");
+
+    // Queries that do not match a name are parsed as compound queries.
+    let output = garden_capture(&["--chdir", &root, "--quiet", "cmd", "@web,@api", "name"]);
+    assert_eq!(output, "web\napi");
+    let output = garden_capture(&["--chdir", &root, "--quiet", "cmd", "@*,!@-tool", "name"]);
+    assert_eq!(output, "web,api\nweb\napi");
+
+    Ok(())
+}
+
+/// Tree query predicates select trees by their state on disk.
+#[test]
+#[named]
+fn query_predicates() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+trees:
+  clean:
+    url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+    remotes:
+      upstream: file://${GARDEN_CONFIG_DIR}/repos/example.git
+  dirty:
+    url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+  feature:
+    url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+    branch: dev
+  missing:
+    url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+groups:
+  main: [clean, dirty]
+commands:
+  name: echo ${TREE_NAME}
+"#,
+    )?;
+    exec_garden(&["--chdir", &root, "grow", "clean", "dirty", "feature"])?;
+    std::fs::write(format!("{root}/dirty/untracked.txt"), "untracked\n")?;
+    let names = |query: &str| -> String {
+        garden_capture(&["--chdir", &root, "--quiet", "cmd", "--force", query, "name"])
+            .lines()
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    assert_eq!(names("dirty()"), "dirty");
+    assert_eq!(names("clean()"), "clean feature");
+    assert_eq!(names("missing()"), "missing");
+    assert_eq!(names("exists()&!dirty()"), "clean feature");
+    assert_eq!(names("branch(dev)"), "feature");
+    assert_eq!(names("branch(def*)"), "clean dirty");
+    assert_eq!(names("has-remote(upstream)"), "clean");
+    assert_eq!(names("%main,-dirty()"), "clean");
+    assert_eq!(names("%main&dirty(),@missing"), "dirty missing");
+
+    // Add an upstream commit and a local commit to test ahead() and behind().
+    let repo = fixture.path("repos/example.git");
+    let tree = assert_cmd_capture(&["git", "rev-parse", "default^{tree}"], &repo);
+    let commit = assert_cmd_capture(
+        &[
+            "git",
+            "commit-tree",
+            "-m",
+            "upstream",
+            "-p",
+            "default",
+            &tree,
+        ],
+        &repo,
+    );
+    assert_cmd(&["git", "update-ref", "refs/heads/default", &commit], &repo);
+    assert_cmd(
+        &["git", "fetch", "--quiet", "origin"],
+        &fixture.path("clean"),
+    );
+    assert_cmd(
+        &[
+            "git",
+            "-c",
+            "user.name=A U Thor",
+            "-c",
+            "user.email=author@example.com",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "-m",
+            "local",
+        ],
+        &fixture.worktree("dirty"),
+    );
+    assert_eq!(names("behind()"), "clean");
+    assert_eq!(names("ahead()"), "dirty");
+
+    Ok(())
+}
+
+/// "garden cmd --output" prefixes or groups the output from each tree.
+#[test]
+#[named]
+fn cmd_output_modes() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+trees:
+  one:
+    path: ${GARDEN_CONFIG_DIR}
+  two:
+    path: ${GARDEN_CONFIG_DIR}
+commands:
+  lines: |
+    echo ${TREE_NAME}-stdout
+    sleep 0.1
+    echo ${TREE_NAME}-stderr >&2
+    echo ${TREE_NAME}-done
+"#,
+    )?;
+
+    // Each line is tagged with the tree name.
+    let output = garden_capture(&[
+        "--chdir",
+        &root,
+        "--quiet",
+        "cmd",
+        "--output=prefix",
+        "@*",
+        "lines",
+    ]);
+    assert_eq!(
+        output,
+        "[one] one-stdout\n[one] one-done\n[two] two-stdout\n[two] two-done"
+    );
+
+    // Output from each tree is displayed together when running in parallel.
+    let output = garden_capture(&[
+        "--chdir", &root, "--quiet", "cmd", "--jobs", "2", "--output", "group", "@*", "lines",
+    ]);
+    let lines: Vec<&str> = output.lines().collect();
+    let mut blocks: Vec<String> = lines.chunks(2).map(|block| block.join(" ")).collect();
+    blocks.sort();
+    assert_eq!(blocks, vec!["one-stdout one-done", "two-stdout two-done"]);
+
+    Ok(())
+}
+
+/// "garden cmd --report" writes the results for each tree and command.
+#[test]
+#[named]
+fn cmd_report() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+trees:
+  one:
+    path: ${GARDEN_CONFIG_DIR}
+  two:
+    path: ${GARDEN_CONFIG_DIR}
+commands:
+  check: test ${TREE_NAME} != one
+  ok: "true"
+"#,
+    )?;
+    let status = exec_garden_code(&[
+        "--chdir",
+        &root,
+        "cmd",
+        "--keep-going",
+        "--report",
+        "report.json",
+        "@*",
+        "check",
+        "ok",
+    ]);
+    assert_eq!(status, errors::EX_ERROR);
+
+    // Failures are listed last.
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(fixture.path("report.json"))?)?;
+    let results = report["results"].as_array().unwrap();
+    let entries: Vec<(&str, &str, i64)> = results
+        .iter()
+        .map(|result| {
+            (
+                result["tree"].as_str().unwrap_or_default(),
+                result["command"].as_str().unwrap_or_default(),
+                result["status"].as_i64().unwrap_or_default(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("one", "ok", 0),
+            ("two", "check", 0),
+            ("two", "ok", 0),
+            ("one", "check", 1)
+        ]
+    );
+    for result in results {
+        assert!(result["start"].as_str().unwrap_or_default().ends_with('Z'));
+        assert!(result["end"].as_str().unwrap_or_default().ends_with('Z'));
+        assert!(result["duration"].as_f64().is_some());
+    }
+
+    // The summary is displayed when a command fails or when "--summary" is used.
+    let output = garden_capture_stderr(&["--chdir", &root, "cmd", "--keep-going", "@*", "check"]);
+    assert!(output.contains("exit 1"));
+    let summary_rows = |output: &str| output.lines().filter(|line| line.contains(" ok ")).count();
+    let output = garden_capture_stderr(&["--chdir", &root, "cmd", "@*", "ok"]);
+    assert_eq!(summary_rows(&output), 0);
+    let output = garden_capture_stderr(&["--chdir", &root, "cmd", "--summary", "@*", "ok"]);
+    assert_eq!(summary_rows(&output), 2);
+
+    Ok(())
+}
+
+/// "garden validate" reports problems with their file and line number.
+#[test]
+#[named]
+fn validate() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+garden:
+  includes: include.yaml
+variables:
+  src: ${GARDEN_CONFIG_DIR}/src
+trees:
+  one:
+    path: ${src}/one
+    templates: missing-template
+  two:
+    url: https://example.com/two
+groups:
+  all: [one, two]
+gardens:
+  main:
+    groups: all
+"#,
+    )?;
+    std::fs::write(
+        format!("{root}/include.yaml"),
+        r#"
+trees:
+  three:
+    path: ${src}/one
+    urll: https://example.com/three
+    depends: [two, missing-tree]
+    variables:
+      value: ${undefined_variable}
+"#,
+    )?;
+    assert_eq!(
+        exec_garden_code(&["--chdir", &root, "validate"]),
+        errors::EX_CONFIG
+    );
+    let output = garden_capture(&["--chdir", &root, "validate"]);
+    let path = std::fs::canonicalize(&root)?.to_string_lossy().to_string();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            format!("{path}/garden.yaml:7: trees.one: path is also used by three: {path}/src/one"),
+            format!("{path}/garden.yaml:9: trees.one.templates: unknown template: missing-template"),
+            format!("{path}/include.yaml:5: trees.three.urll: unknown key"),
+            format!("{path}/include.yaml:6: trees.three.depends: unknown tree: missing-tree"),
+            format!(
+                "{path}/include.yaml:8: trees.three.variables.value: undefined variable: undefined_variable"
+            ),
+        ]
+    );
+
+    // Valid configurations exit successfully.
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+garden:
+  includes: include.yaml
+variables:
+  src: ${GARDEN_CONFIG_DIR}/src
+trees:
+  one:
+    path: ${src}/one
+  two:
+    url: https://example.com/two
+groups:
+  all: [one, two]
+gardens:
+  main:
+    groups: all
+"#,
+    )?;
+    std::fs::write(
+        format!("{root}/include.yaml"),
+        r#"
+trees:
+  three:
+    path: ${src}/three
+    depends: two
+"#,
+    )?;
+    exec_garden(&["--chdir", &root, "validate"])?;
+
+    Ok(())
+}
+
+/// "garden grow --jobs" grows trees in parallel and grows worktree parents first.
+#[test]
+#[named]
+fn grow_parallel() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+variables:
+  url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+trees:
+  one: ${url}
+  two: ${url}
+  parent:
+    url: ${url}
+    gitconfig:
+      garden.grown: true
+  dev:
+    worktree: parent
+    branch: dev
+  feature:
+    worktree: parent
+    branch: feature
+    branches:
+      feature: origin/dev
+  three: ${url}
+groups:
+  g1: three
+  g2: [three, dev]
+gardens:
+  all:
+    groups: [g1, g2]
+"#,
+    )?;
+    // The worktree's parent is grown even when it is not part of the query.
+    exec_garden(&["--chdir", &root, "grow", "dev", "--jobs"])?;
+    assert_ref(&fixture.worktree("parent"), "default");
+    assert_ref(&fixture.worktree("dev"), "dev");
+
+    // Trees that are reachable through multiple groups are only grown once.
+    exec_garden(&["--chdir", &root, "grow", "-j", "8", "all"])?;
+    assert_ref(&fixture.worktree("three"), "default");
+
+    exec_garden(&["--chdir", &root, "grow", "-j", "2", "@*"])?;
+    assert_ref(&fixture.worktree("one"), "default");
+    assert_ref(&fixture.worktree("two"), "default");
+    assert_ref(&fixture.worktree("feature"), "feature");
+
+    let cmd = ["git", "config", "garden.grown"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("feature"));
+    assert_eq!(output, "true");
+
+    let cmd = ["git", "worktree", "list", "--porcelain"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("parent"));
+    let worktrees = output
+        .lines()
+        .filter(|line| line.starts_with("worktree "))
+        .count();
+    assert_eq!(worktrees, 3);
+
+    Ok(())
+}
+
+/// "garden grow" creates partial clones and reconciles sparse checkouts.
+#[test]
+#[named]
+fn grow_filter_and_sparse_checkout() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    // Create a repository with several directories.
+    let source = format!("{root}/source");
+    std::fs::create_dir_all(&source)?;
+    assert_cmd(
+        &["git", "init", "--quiet", "--initial-branch=main"],
+        &source,
+    );
+    for name in ["one", "two", "three"] {
+        std::fs::create_dir_all(format!("{source}/{name}"))?;
+        std::fs::write(format!("{source}/{name}/file.txt"), name)?;
+    }
+    std::fs::write(format!("{source}/README"), "readme")?;
+    assert_cmd(&["git", "add", "."], &source);
+    assert_cmd(
+        &[
+            "git",
+            "-c",
+            "user.name=Garden",
+            "-c",
+            "user.email=garden-tools@crates.io",
+            "commit",
+            "--quiet",
+            "-m",
+            "initial commit",
+        ],
+        &source,
+    );
+    assert_cmd(
+        &["git", "config", "uploadpack.allowFilter", "true"],
+        &source,
+    );
+
+    let config = r#"
+trees:
+  sparse:
+    url: file://${GARDEN_CONFIG_DIR}/source
+    filter: blob:none
+    sparse-checkout:
+      - one
+      - two
+"#;
+    std::fs::write(format!("{root}/garden.yaml"), config)?;
+    exec_garden(&["--chdir", &root, "grow", "sparse"])?;
+
+    let cmd = ["git", "config", "remote.origin.partialclonefilter"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("sparse"));
+    assert_eq!(output, "blob:none");
+
+    let cmd = ["git", "sparse-checkout", "list"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("sparse"));
+    assert_eq!(output, "one\ntwo");
+    fixture.path("sparse/README");
+    fixture.path("sparse/one/file.txt");
+    fixture.path("sparse/two/file.txt");
+    assert!(!std::path::Path::new(&format!("{root}/sparse/three")).exists());
+
+    // Re-running "garden grow" reconciles the sparse-checkout paths.
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        config.replace("      - two\n", "      - three\n"),
+    )?;
+    exec_garden(&["--chdir", &root, "grow", "sparse"])?;
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("sparse"));
+    assert_eq!(output, "one\nthree");
+    fixture.path("sparse/three/file.txt");
+    assert!(!std::path::Path::new(&format!("{root}/sparse/two")).exists());
+
+    Ok(())
+}
+
+/// "garden grow" initializes submodules and reports a missing git-lfs.
+#[test]
+#[named]
+fn grow_submodules_and_lfs() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    let git_commit = [
+        "git",
+        "-c",
+        "user.name=Garden",
+        "-c",
+        "user.email=garden-tools@crates.io",
+        "-c",
+        "protocol.file.allow=always",
+    ];
+    // Create a repository containing a submodule that contains a nested submodule.
+    for name in ["nested", "sub", "super"] {
+        let path = format!("{root}/{name}");
+        std::fs::create_dir_all(&path)?;
+        assert_cmd(&["git", "init", "--quiet", "--initial-branch=main"], &path);
+        std::fs::write(format!("{path}/README"), name)?;
+        assert_cmd(&["git", "add", "README"], &path);
+    }
+    let nested_url = format!(
+        "file://{}",
+        std::fs::canonicalize(format!("{root}/nested"))?.display()
+    );
+    let sub_url = format!(
+        "file://{}",
+        std::fs::canonicalize(format!("{root}/sub"))?.display()
+    );
+    let mut cmd = git_commit.to_vec();
+    cmd.extend(["commit", "--quiet", "-m", "nested"]);
+    assert_cmd(&cmd, &format!("{root}/nested"));
+
+    let mut cmd = git_commit.to_vec();
+    cmd.extend(["submodule", "--quiet", "add", &nested_url, "nested"]);
+    assert_cmd(&cmd, &format!("{root}/sub"));
+    let mut cmd = git_commit.to_vec();
+    cmd.extend(["commit", "--quiet", "-m", "sub"]);
+    assert_cmd(&cmd, &format!("{root}/sub"));
+
+    let mut cmd = git_commit.to_vec();
+    cmd.extend(["submodule", "--quiet", "add", &sub_url, "sub"]);
+    assert_cmd(&cmd, &format!("{root}/super"));
+    let mut cmd = git_commit.to_vec();
+    cmd.extend(["commit", "--quiet", "-m", "super"]);
+    assert_cmd(&cmd, &format!("{root}/super"));
+
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+variables:
+  url: file://${GARDEN_CONFIG_DIR}/super
+trees:
+  one:
+    url: ${url}
+    submodules: true
+  recursive:
+    url: ${url}
+    submodules: recursive
+  lfs:
+    url: ${url}
+    lfs: true
+"#,
+    )?;
+    // Allow submodules to be cloned from file:// URLs.
+    let env = [
+        ("GIT_CONFIG_COUNT", "1"),
+        ("GIT_CONFIG_KEY_0", "protocol.file.allow"),
+        ("GIT_CONFIG_VALUE_0", "always"),
+    ];
+    exec_garden_env(&["--chdir", &root, "grow", "one", "recursive"], &env)?;
+    fixture.path("one/sub/README");
+    assert!(!std::path::Path::new(&format!("{root}/one/sub/nested/README")).exists());
+    fixture.path("recursive/sub/README");
+    fixture.path("recursive/sub/nested/README");
+
+    // Missing git-lfs is reported as an error.
+    if which("git-lfs").is_err() {
+        let status = exec_garden_code(&["--chdir", &root, "grow", "lfs"]);
+        assert_eq!(status, errors::EX_UNAVAILABLE);
+    } else {
+        exec_garden(&["--chdir", &root, "grow", "lfs"])?;
+        let cmd = ["git", "config", "filter.lfs.process"];
+        assert_cmd(&cmd, &fixture.worktree("lfs"));
+    }
+
+    Ok(())
+}
+
+/// "garden branch" creates, switches, lists and deletes branches across trees.
+#[test]
+#[named]
+fn branch_start_switch_list_delete() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+variables:
+  url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+trees:
+  one:
+    url: ${url}
+    branch: default
+  two:
+    url: ${url}
+    branch: dev
+groups:
+  all: [one, two]
+"#,
+    )?;
+    exec_garden(&["--chdir", &root, "grow", "all"])?;
+
+    // "garden branch start" creates the branch from the configured branch.
+    exec_garden(&["--chdir", &root, "branch", "start", "topic", "all"])?;
+    assert_ref(&fixture.worktree("one"), "topic");
+    assert_ref(&fixture.worktree("two"), "topic");
+    let cmd = ["git", "symbolic-ref", "--short", "HEAD"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("one"));
+    assert_eq!(output, "topic");
+    let cmd = ["git", "config", "branch.topic.merge"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("two"));
+    assert_eq!(output, "refs/heads/topic");
+
+    // "garden branch list" reports the number of commits ahead.
+    let cmd = [
+        "git",
+        "-c",
+        "user.name=Garden",
+        "-c",
+        "user.email=garden-tools@crates.io",
+        "commit",
+        "--quiet",
+        "--allow-empty",
+        "-m",
+        "topic",
+    ];
+    assert_cmd(&cmd, &fixture.worktree("one"));
+    let output = garden_capture(&["--chdir", &root, "branch", "list", "all"]);
+    assert!(output.contains("topic"));
+    assert!(output.contains("# one +1 (current)"));
+    assert!(output.contains("# two +0 (current)"));
+
+    // Dirty trees are not switched unless --force is used.
+    std::fs::write(format!("{root}/one/untracked.txt"), "dirty")?;
+    let output = garden_capture(&["--chdir", &root, "branch", "switch", "default", "one"]);
+    assert!(output.contains("skipped"));
+    let cmd = ["git", "symbolic-ref", "--short", "HEAD"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("one"));
+    assert_eq!(output, "topic");
+    exec_garden(&[
+        "--chdir", &root, "branch", "switch", "--force", "default", "one",
+    ])?;
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("one"));
+    assert_eq!(output, "default");
+
+    // Unmerged branches are deleted using --force.
+    let status = exec_garden_code(&["--chdir", &root, "branch", "delete", "topic", "one"]);
+    assert_ne!(status, errors::EX_OK);
+    assert_ref(&fixture.worktree("one"), "topic");
+    exec_garden(&[
+        "--chdir", &root, "branch", "delete", "--force", "topic", "one",
+    ])?;
+    assert_ref_missing(&fixture.worktree("one"), "topic");
+
+    Ok(())
+}
+
+/// "garden worktree" adds, lists and removes worktree trees.
+#[test]
+#[named]
+fn worktree_add_list_remove() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+trees:
+  parent:
+    url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+"#,
+    )?;
+    exec_garden(&["--chdir", &root, "grow", "parent"])?;
+
+    // "garden worktree add" creates the worktree and records it in the garden file.
+    let output = garden_capture(&["--chdir", &root, "worktree", "add", "parent", "dev"]);
+    assert_eq!(output, "parent-dev");
+    assert_ref(&fixture.worktree("parent-dev"), "dev");
+    let cmd = ["git", "symbolic-ref", "--short", "HEAD"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("parent-dev"));
+    assert_eq!(output, "dev");
+
+    let args = [
+        "--chdir",
+        &root,
+        "worktree",
+        "add",
+        "--name",
+        "topic",
+        "--path",
+        "worktrees/topic",
+        "parent",
+        "topic",
+    ];
+    let output = garden_capture(&args);
+    assert_eq!(output, "topic");
+    fixture.worktree("worktrees/topic");
+
+    let config = std::fs::read_to_string(format!("{root}/garden.yaml"))?;
+    assert!(config.contains("parent-dev:\n    worktree: parent\n    branch: dev"));
+    assert!(config
+        .contains("topic:\n    worktree: parent\n    branch: topic\n    path: worktrees/topic"));
+
+    // "garden worktree list" displays parents and their children.
+    let output = garden_capture(&["--chdir", &root, "worktree", "list"]);
+    assert_eq!(
+        output,
+        "# parent [default]\n  # parent-dev [dev]\n  # topic [topic]"
+    );
+
+    // "garden worktree remove" removes the worktree and the tree entry.
+    exec_garden(&["--chdir", &root, "worktree", "remove", "topic"])?;
+    assert!(!std::path::Path::new(&format!("{root}/worktrees/topic")).exists());
+    let config = std::fs::read_to_string(format!("{root}/garden.yaml"))?;
+    assert!(!config.contains("topic:"));
+    assert!(config.contains("parent-dev:"));
+
+    // Regular trees cannot be removed.
+    let status = exec_garden_code(&["--chdir", &root, "worktree", "remove", "parent"]);
+    assert_eq!(status, errors::EX_CONFIG);
+
+    Ok(())
+}
+
+/// "garden grow" clones using cached mirrors when "garden.cache" is enabled.
+#[test]
+#[named]
+fn grow_with_cache() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    let cmd = [
+        "git",
+        "clone",
+        "--quiet",
+        "--bare",
+        "example.git",
+        "other.git",
+    ];
+    assert_cmd(&cmd, &format!("{root}/repos"));
+    let config = r#"
+garden:
+  cache: ${GARDEN_CONFIG_DIR}/cache
+variables:
+  repos: file://${GARDEN_CONFIG_DIR}/repos
+trees:
+  one: ${repos}/example.git
+  two: ${repos}/example.git
+  other: ${repos}/other.git
+"#;
+    std::fs::write(format!("{root}/garden.yaml"), config)?;
+    exec_garden(&["--chdir", &root, "grow", "one", "two", "other"])?;
+    assert_ref(&fixture.worktree("one"), "default");
+    assert_ref(&fixture.worktree("two"), "default");
+
+    // Both trees borrow objects from the same mirror.
+    let mirrors: Vec<std::path::PathBuf> = std::fs::read_dir(format!("{root}/cache"))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    assert_eq!(mirrors.len(), 2);
+    let mirror = mirrors
+        .iter()
+        .find(|path| path.to_string_lossy().ends_with("_repos_example.git"))
+        .map(std::fs::canonicalize)
+        .expect("example.git mirror not found")?;
+    assert!(mirror.join("HEAD").is_file());
+    let alternates = std::fs::read_to_string(format!("{root}/one/.git/objects/info/alternates"))?;
+    assert_eq!(alternates.trim(), mirror.join("objects").to_string_lossy());
+    fixture.path("two/.git/objects/info/alternates");
+
+    // "cache-mode: dissociate" does not leave the clone dependent on the mirror.
+    let args = [
+        "--chdir",
+        &root,
+        "-D",
+        "garden.cache-mode=dissociate",
+        "grow",
+        "other",
+    ];
+    std::fs::remove_dir_all(format!("{root}/other"))?;
+    exec_garden(&args)?;
+    assert_ref(&fixture.worktree("other"), "default");
+    assert!(!std::path::Path::new(&format!("{root}/other/.git/objects/info/alternates")).exists());
+
+    // "garden cache gc" removes mirrors that are no longer referenced.
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        config.replace("  other: ${repos}/other.git\n", ""),
+    )?;
+    let output = garden_capture(&["--chdir", &root, "cache", "gc", "--dry-run"]);
+    assert!(output.contains("other.git"));
+    exec_garden(&["--chdir", &root, "cache", "gc"])?;
+    assert!(mirror.join("HEAD").is_file());
+    let entries = std::fs::read_dir(format!("{root}/cache"))?.count();
+    assert_eq!(entries, 1);
+
+    // Mirrors that are borrowed from by clones in other gardens are not removed.
+    let other_config = r#"
+garden:
+  cache: ${GARDEN_CONFIG_DIR}/cache
+trees:
+  other: file://${GARDEN_CONFIG_DIR}/repos/other.git
+"#;
+    std::fs::write(format!("{root}/other.yaml"), other_config)?;
+    let other_path = fixture.path("other.yaml");
+    let output = garden_capture_stderr(&["--config", &other_path, "cache", "gc"]);
+    assert!(output.contains("skipped"));
+    assert!(mirror.join("HEAD").is_file());
+    assert_ref(&fixture.worktree("one"), "default");
+
+    Ok(())
+}
+
+/// "garden.url-rewrites" rewrites URLs when cloning and reconciling remotes.
+#[test]
+#[named]
+fn grow_url_rewrites() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+garden:
+  url-rewrites:
+    "git@example.com:": file://${GARDEN_CONFIG_DIR}/missing/
+    "git@example.com:repos/": file://${GARDEN_CONFIG_DIR}/repos/
+trees:
+  example:
+    url: git@example.com:repos/example.git
+    remotes:
+      upstream: git@example.com:repos/upstream.git
+"#,
+    )?;
+    // The longest matching prefix is used.
+    exec_garden(&["--chdir", &root, "grow", "example"])?;
+    assert_ref(&fixture.worktree("example"), "default");
+    let cmd = ["git", "config", "remote.upstream.url"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("example"));
+    assert!(output.starts_with("file://"));
+    assert!(output.ends_with("/repos/upstream.git"));
+
+    // "garden ls -v" displays the configured and effective URLs.
+    let output = garden_capture(&["--chdir", &root, "ls", "-v", "example"]);
+    assert!(output.contains("(configured: git@example.com:repos/example.git)"));
+    let output = garden_capture(&["--chdir", &root, "ls", "example"]);
+    assert!(!output.contains("configured:"));
+
+    // Rewrites can be overridden using "garden -D garden.url-rewrites.<prefix>=<value>".
+    let args = [
+        "--chdir",
+        &root,
+        "-D",
+        "garden.url-rewrites.git@example.com:repos/=https://example.com/",
+        "grow",
+        "example",
+    ];
+    exec_garden(&args)?;
+    let cmd = ["git", "config", "remote.origin.url"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("example"));
+    assert_eq!(output, "https://example.com/example.git");
+
+    // Trees from grafts use the graft's rewrites.
+    std::fs::write(
+        format!("{root}/graft.yaml"),
+        r#"
+garden:
+  url-rewrites:
+    "git@example.com:repos/": file://${GARDEN_CONFIG_DIR}/graft/
+trees:
+  grafted:
+    url: git@example.com:repos/example.git
+"#,
+    )?;
+    let config = std::fs::read_to_string(format!("{root}/garden.yaml"))?;
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        format!("{config}grafts:\n  graft: graft.yaml\n"),
+    )?;
+    let output = garden_capture(&["--chdir", &root, "ls", "-a", "-v", "graft::grafted"]);
+    assert!(output.contains("/graft/example.git"));
+    assert!(output.contains("(configured: git@example.com:repos/example.git)"));
+
+    Ok(())
+}
+
+/// "garden grow --check" reports drift and "--prune-remotes" removes extra remotes.
+#[test]
+#[named]
+fn grow_check_and_prune_remotes() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+trees:
+  example:
+    url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+    gitconfig:
+      garden.grown: true
+    branches:
+      dev: origin/dev
+  missing:
+    url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+"#,
+    )?;
+    exec_garden(&["--chdir", &root, "grow", "example"])?;
+    exec_garden(&["--chdir", &root, "grow", "--check", "example"])?;
+
+    // Missing trees are reported and nothing is grown.
+    let status = exec_garden_code(&["--chdir", &root, "grow", "--check", "missing"]);
+    assert_eq!(status, errors::EX_ERROR);
+    let output = garden_capture(&["--chdir", &root, "grow", "--check", "missing"]);
+    assert!(output.contains("missing"));
+    assert!(!std::path::PathBuf::from(format!("{root}/missing")).exists());
+
+    // Modify the repository so that it drifts from the configuration.
+    let example = fixture.worktree("example");
+    assert_cmd(&["git", "config", "garden.grown", "false"], &example);
+    assert_cmd(&["git", "branch", "-D", "dev"], &example);
+    assert_cmd(
+        &[
+            "git",
+            "remote",
+            "add",
+            "extra",
+            "https://example.com/extra.git",
+        ],
+        &example,
+    );
+    assert_cmd(
+        &[
+            "git",
+            "remote",
+            "set-url",
+            "origin",
+            "https://example.com/example.git",
+        ],
+        &example,
+    );
+    let status = exec_garden_code(&["--chdir", &root, "grow", "--check", "example"]);
+    assert_eq!(status, errors::EX_ERROR);
+    let output = garden_capture(&["--chdir", &root, "grow", "--check", "example"]);
+    assert!(output.contains("extra-remote"));
+    assert!(output.contains("remote-url"));
+    assert!(output.contains("gitconfig"));
+    assert!(output.contains("missing-branch"));
+    // The check does not modify the repository.
+    let cmd = ["git", "config", "remote.extra.url"];
+    let output = assert_cmd_capture(&cmd, &example);
+    assert_eq!(output, "https://example.com/extra.git");
+
+    // "garden grow --prune-remotes" removes remotes that are not configured.
+    exec_garden(&["--chdir", &root, "grow", "--prune-remotes", "example"])?;
+    assert_cmd_status(&cmd, &example, 1);
+    exec_garden(&["--chdir", &root, "grow", "--check", "example"])?;
+
+    Ok(())
+}
+
+/// "garden grow" checks out trees pinned using "tag" and "commit" on a detached HEAD.
+#[test]
+#[named]
+fn grow_tag_and_commit_pins() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    // Create a repository with a tagged commit followed by a newer commit.
+    let source = format!("{root}/source");
+    std::fs::create_dir_all(&source)?;
+    assert_cmd(
+        &["git", "init", "--quiet", "--initial-branch=main"],
+        &source,
+    );
+    let commit = [
+        "git",
+        "-c",
+        "user.name=Garden",
+        "-c",
+        "user.email=garden-tools@crates.io",
+        "commit",
+        "--quiet",
+        "--allow-empty",
+        "-m",
+    ];
+    assert_cmd(&[&commit[..], &["v1"]].concat(), &source);
+    assert_cmd(&["git", "tag", "v1.0"], &source);
+    let v1 = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &source);
+    assert_cmd(&[&commit[..], &["v2"]].concat(), &source);
+    assert_cmd(&[&commit[..], &["v3"]].concat(), &source);
+    let v2 = assert_cmd_capture(&["git", "rev-parse", "HEAD~1"], &source);
+
+    let config = format!(
+        r#"
+trees:
+  tagged:
+    url: file://${{GARDEN_CONFIG_DIR}}/source
+    tag: v1.0
+    depth: 1
+  pinned:
+    url: file://${{GARDEN_CONFIG_DIR}}/source
+    commit: {v2}
+"#
+    );
+    std::fs::write(format!("{root}/garden.yaml"), config)?;
+    exec_garden(&["--chdir", &root, "grow", "tagged", "pinned"])?;
+
+    let cmd = ["git", "rev-parse", "HEAD"];
+    assert_eq!(assert_cmd_capture(&cmd, &fixture.worktree("tagged")), v1);
+    assert_eq!(assert_cmd_capture(&cmd, &fixture.worktree("pinned")), v2);
+    // The tagged tree is a shallow clone.
+    let cmd = ["git", "rev-parse", "--is-shallow-repository"];
+    assert_eq!(
+        assert_cmd_capture(&cmd, &fixture.worktree("tagged")),
+        "true"
+    );
+
+    // "garden ls" displays the pin and flags trees that no longer match it.
+    let output = garden_capture_stderr(&["--chdir", &root, "ls", "tagged"]);
+    assert!(output.contains("(tag: v1.0)"));
+    assert_cmd(
+        &["git", "checkout", "--quiet", "--detach", "HEAD~1"],
+        &fixture.worktree("pinned"),
+    );
+    let output = garden_capture_stderr(&["--chdir", &root, "ls", "pinned"]);
+    assert!(output.contains("HEAD does not match"));
+    let output = garden_capture(&["--chdir", &root, "status", "pinned"]);
+    assert!(output.contains("HEAD does not match"));
+    let output = garden_capture(&["--chdir", &root, "grow", "--check", "pinned"]);
+    assert!(output.contains("pin"));
+    // Command headers do not inspect the pin.
+    let output = garden_capture_stderr(&["--chdir", &root, "exec", "pinned", "true"]);
+    assert!(output.contains("(commit: "));
+    assert!(!output.contains("HEAD does not match"));
+
+    // Re-growing a detached tree moves it back to the pin.
+    exec_garden(&["--chdir", &root, "grow", "pinned"])?;
+    let cmd = ["git", "rev-parse", "HEAD"];
+    assert_eq!(assert_cmd_capture(&cmd, &fixture.worktree("pinned")), v2);
+
+    // Trees with a branch checked out are not moved and a warning is displayed.
+    assert_cmd(
+        &["git", "checkout", "--quiet", "-b", "topic", "HEAD~1"],
+        &fixture.worktree("pinned"),
+    );
+    let output = garden_capture_stderr(&["--chdir", &root, "grow", "pinned"]);
+    assert!(output.contains("branch topic is checked out"));
+    let cmd = ["git", "symbolic-ref", "--short", "HEAD"];
+    assert_eq!(
+        assert_cmd_capture(&cmd, &fixture.worktree("pinned")),
+        "topic"
+    );
+
+    Ok(())
+}
+
+/// "garden rm" removes trees and their references and "--delete" refuses unsafe deletions.
+#[test]
+#[named]
+fn rm_trees() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+trees:
+  one: file://${GARDEN_CONFIG_DIR}/repos/example.git
+  two: file://${GARDEN_CONFIG_DIR}/repos/example.git
+  three: file://${GARDEN_CONFIG_DIR}/repos/example.git
+  parent: file://${GARDEN_CONFIG_DIR}/repos/example.git
+  child:
+    worktree: ${parent_tree}
+    branch: dev
+variables:
+  parent_tree: parent
+groups:
+  pair: [one, two]
+  solo: one
+gardens:
+  all:
+    trees: [one, three]
+"#,
+    )?;
+    exec_garden(&["--chdir", &root, "grow", "one", "two"])?;
+
+    // Trees are removed from the garden file along with their references.
+    exec_garden(&["--chdir", &root, "rm", "three"])?;
+    let config_path = fixture.pathbuf("garden.yaml");
+    let app_context = garden::model::ApplicationContext::from_path_and_root(
+        &config_path,
+        Some(&fixture.root_pathbuf()),
+    )?;
+    let config = app_context.get_root_config();
+    assert!(config.trees.get("three").is_none());
+    assert_eq!(config.gardens.get("all").unwrap().trees.len(), 1);
+
+    // Trees with uncommitted changes or unpushed commits are not deleted.
+    let two = fixture.worktree("two");
+    std::fs::write(format!("{two}/untracked.txt"), "untracked")?;
+    let args = ["--chdir", &root, "rm", "--delete", "--no-prompt", "two"];
+    assert_eq!(exec_garden_code(&args), errors::EX_ERROR);
+    std::fs::remove_file(format!("{two}/untracked.txt"))?;
+    assert_cmd(
+        &[
+            "git",
+            "-c",
+            "user.name=Garden",
+            "-c",
+            "user.email=garden-tools@crates.io",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "-m",
+            "unpushed",
+        ],
+        &two,
+    );
+    assert_eq!(exec_garden_code(&args), errors::EX_ERROR);
+    fixture.worktree("two");
+
+    // Commits on a detached HEAD are unpushed work.
+    let one = fixture.worktree("one");
+    assert_cmd(&["git", "checkout", "--quiet", "--detach"], &one);
+    let commit = [
+        "git",
+        "-c",
+        "user.name=Garden",
+        "-c",
+        "user.email=garden-tools@crates.io",
+        "commit",
+        "--quiet",
+        "--allow-empty",
+        "-m",
+        "detached",
+    ];
+    assert_cmd(&commit, &one);
+    let args = ["--chdir", &root, "rm", "--delete", "--no-prompt", "one"];
+    assert_eq!(exec_garden_code(&args), errors::EX_ERROR);
+    assert_cmd(&["git", "checkout", "--quiet", "-"], &one);
+
+    // "garden rm --delete" removes the checkout.
+    exec_garden(&["--chdir", &root, "rm", "--delete", "--no-prompt", "one"])?;
+    assert!(!std::path::Path::new(&format!("{root}/one")).exists());
+    let app_context = garden::model::ApplicationContext::from_path_and_root(
+        &config_path,
+        Some(&fixture.root_pathbuf()),
+    )?;
+    let config = app_context.get_root_config();
+    assert!(config.trees.get("one").is_none());
+    assert!(config.trees.get("two").is_some());
+    let members: Vec<&String> = config.groups.get("pair").unwrap().members.iter().collect();
+    assert_eq!(members, ["two"]);
+    assert!(config.groups.get("solo").unwrap().members.is_empty());
+    assert!(config.gardens.get("all").unwrap().trees.is_empty());
+
+    // Deleting a worktree prunes it from its parent repository.
+    exec_garden(&["--chdir", &root, "grow", "child"])?;
+    exec_garden(&["--chdir", &root, "rm", "--delete", "--no-prompt", "child"])?;
+    let cmd = ["git", "worktree", "list", "--porcelain"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("parent"));
+    assert_eq!(
+        output
+            .lines()
+            .filter(|line| line.starts_with("worktree "))
+            .count(),
+        1
+    );
+
+    // A worktree parent can be deleted along with its worktrees.
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+trees:
+  parent: file://${GARDEN_CONFIG_DIR}/repos/example.git
+  child:
+    worktree: ${parent_tree}
+    branch: feature
+    branches:
+      feature: origin/dev
+  two: file://${GARDEN_CONFIG_DIR}/repos/example.git
+variables:
+  parent_tree: parent
+"#,
+    )?;
+    exec_garden(&["--chdir", &root, "grow", "child"])?;
+    let args = ["--chdir", &root, "rm", "--delete", "--no-prompt", "parent"];
+    assert_eq!(exec_garden_code(&args), errors::EX_CONFIG);
+    exec_garden(&[
+        "--chdir",
+        &root,
+        "rm",
+        "--delete",
+        "--no-prompt",
+        "parent",
+        "child",
+    ])?;
+    assert!(!std::path::Path::new(&format!("{root}/parent")).exists());
+    assert!(!std::path::Path::new(&format!("{root}/child")).exists());
+
+    // "--force-unsafe" deletes checkouts with unpushed work.
+    exec_garden(&[
+        "--chdir",
+        &root,
+        "rm",
+        "--delete",
+        "--no-prompt",
+        "--force-unsafe",
+        "two",
+    ])?;
+    assert!(!std::path::Path::new(&format!("{root}/two")).exists());
+
+    Ok(())
+}
+
+/// "garden mv" moves trees, repairs worktrees and renames trees.
+#[test]
+#[named]
+fn mv_trees() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+trees:
+  parent: file://${GARDEN_CONFIG_DIR}/repos/example.git
+  child:
+    worktree: parent
+    branch: dev
+  extended:
+    extend: parent
+groups:
+  family: [parent, child]
+gardens:
+  all:
+    trees: parent
+"#,
+    )?;
+    exec_garden(&["--chdir", &root, "grow", "child"])?;
+
+    // Move a child worktree.
+    exec_garden(&["--chdir", &root, "mv", "child", "moved/child"])?;
+    assert_ref(&fixture.worktree("moved/child"), "dev");
+    let cmd = ["git", "worktree", "list", "--porcelain"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("parent"));
+    assert!(output.contains("/moved/child"));
+
+    // Move and rename a parent worktree.
+    let args = [
+        "--chdir", &root, "mv", "--name", "main", "parent", "src/main",
+    ];
+    exec_garden(&args)?;
+    assert_cmd(
+        &["git", "status", "--short"],
+        &fixture.worktree("moved/child"),
+    );
+    assert_cmd(&["git", "status", "--short"], &fixture.worktree("src/main"));
+    exec_garden(&["--chdir", &root, "grow", "--check", "main", "child"])?;
+
+    let config_path = fixture.pathbuf("garden.yaml");
+    let app_context = garden::model::ApplicationContext::from_path_and_root(
+        &config_path,
+        Some(&fixture.root_pathbuf()),
+    )?;
+    let config = app_context.get_root_config();
+    let names: Vec<&String> = config.trees.keys().collect();
+    assert_eq!(names, ["main", "child", "extended"]);
+    assert_eq!(config.trees["child"].worktree.get_expr(), "main");
+    let garden_yaml = std::fs::read_to_string(&config_path)?;
+    assert!(garden_yaml.contains("extend: main"));
+    let members: Vec<&String> = config.groups["family"].members.iter().collect();
+    assert_eq!(members, ["main", "child"]);
+    assert!(config.gardens["all"].trees.contains("main"));
+
+    Ok(())
+}
+
+/// "garden prune" skips repositories with unsaved work unless "--force-unsafe" is used.
+#[test]
+#[named]
+fn prune_unsafe() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(format!("{root}/garden.yaml"), "trees: {}\n")?;
+    for name in ["unknown/clean", "unknown/dirty"] {
+        assert_cmd(
+            &["git", "clone", "--quiet", "repos/example.git", name],
+            &root,
+        );
+    }
+    let dirty = fixture.worktree("unknown/dirty");
+    std::fs::write(format!("{dirty}/untracked.txt"), "untracked")?;
+    assert_cmd(&["git", "init", "--quiet", "unknown/local"], &root);
+    assert_cmd(
+        &[
+            "git",
+            "-c",
+            "user.name=Garden",
+            "-c",
+            "user.email=garden-tools@crates.io",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "-m",
+            "local",
+        ],
+        &fixture.worktree("unknown/local"),
+    );
+
+    // "--dry-run --format json" reports the safety analysis without deleting anything.
+    let output = garden_capture(&[
+        "--chdir",
+        &root,
+        "prune",
+        "--dry-run",
+        "--format",
+        "json",
+        "unknown",
+    ]);
+    let doc: serde_json::Value = serde_json::from_str(&output)?;
+    let entries = doc.as_array().unwrap();
+    assert_eq!(entries.len(), 3);
+    assert!(entries[0]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("unknown/clean"));
+    assert_eq!(entries[0]["safe"], true);
+    assert!(entries[1]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("unknown/dirty"));
+    assert_eq!(entries[1]["safe"], false);
+    assert_eq!(entries[1]["hazards"][0], "uncommitted changes");
+    assert!(entries[2]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("unknown/local"));
+    assert_eq!(entries[2]["safe"], false);
+    let hazards = entries[2]["hazards"].as_array().unwrap();
+    assert!(hazards.iter().any(|hazard| hazard == "1 unpushed commit"));
+    fixture.worktree("unknown/clean");
+
+    // "--no-prompt" only deletes repositories that are safe to delete.
+    exec_garden(&["--chdir", &root, "prune", "--no-prompt", "--rm", "unknown"])?;
+    assert!(!std::path::Path::new(&format!("{root}/unknown/clean")).exists());
+    fixture.worktree("unknown/dirty");
+    fixture.worktree("unknown/local");
+
+    // "--force-unsafe" deletes repositories with unsaved work.
+    exec_garden(&[
+        "--chdir",
+        &root,
+        "prune",
+        "--no-prompt",
+        "--force-unsafe",
+        "--rm",
+        "unknown",
+    ])?;
+    assert!(!std::path::Path::new(&format!("{root}/unknown/dirty")).exists());
+    assert!(!std::path::Path::new(&format!("{root}/unknown/local")).exists());
+
+    Ok(())
+}
+
+/// "garden prune --trash" moves repositories into the trash and "garden trash" restores them.
+#[test]
+#[named]
+fn prune_trash_and_restore() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(format!("{root}/garden.yaml"), "trees: {}\n")?;
+    for name in ["unknown/one", "unknown/two"] {
+        assert_cmd(
+            &["git", "clone", "--quiet", "repos/example.git", name],
+            &root,
+        );
+    }
+    let data_home = std::fs::canonicalize(fixture.root_pathbuf())?.join("data");
+    let data_home = data_home.to_string_lossy().to_string();
+    let env = [("XDG_DATA_HOME", data_home.as_str())];
+
+    // Pruned repositories are moved into the trash.
+    let args = [
+        "--chdir",
+        &root,
+        "prune",
+        "--no-prompt",
+        "--trash",
+        "unknown",
+    ];
+    exec_garden_env(&args, &env)?;
+    assert!(!std::path::Path::new(&format!("{root}/unknown")).exists());
+    let output = garden_capture_env(&["--chdir", &root, "trash", "list"], &env);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().any(|line| line.ends_with("/unknown/one")));
+    let line = lines
+        .iter()
+        .find(|line| line.ends_with("/unknown/two"))
+        .unwrap();
+    let id = line.split_whitespace().nth(1).unwrap();
+    assert!(id.ends_with("-two"));
+    let manifest = std::fs::read_to_string(format!("{data_home}/garden/trash/{id}/manifest.yaml"))?;
+    assert!(manifest.contains("head: "));
+
+    // Repositories are restored by their original path or by their trash ID.
+    exec_garden_env(&["--chdir", &root, "trash", "restore", "unknown/one"], &env)?;
+    assert_ref(&fixture.worktree("unknown/one"), "default");
+    exec_garden_env(&["--chdir", &root, "trash", "restore", id], &env)?;
+    assert_ref(&fixture.worktree("unknown/two"), "default");
+    let output = garden_capture_env(&["--chdir", &root, "trash", "list"], &env);
+    assert!(output.is_empty());
+
+    // "garden trash empty --older-than" only removes older repositories.
+    let args = [
+        "--chdir",
+        &root,
+        "prune",
+        "--no-prompt",
+        "--trash",
+        "unknown/one",
+    ];
+    exec_garden_env(&args, &env)?;
+    let args = ["--chdir", &root, "trash", "empty", "--older-than", "30d"];
+    exec_garden_env(&args, &env)?;
+    let output = garden_capture_env(&["--chdir", &root, "trash", "list"], &env);
+    assert_eq!(output.lines().count(), 1);
+    exec_garden_env(&["--chdir", &root, "trash", "empty"], &env)?;
+    let output = garden_capture_env(&["--chdir", &root, "trash", "list"], &env);
+    assert!(output.is_empty());
+    fixture.worktree("unknown/two");
+
+    // Repositories that cannot be moved into the trash are not reported as trashed.
+    let data_file = format!("{data_home}/file");
+    std::fs::write(&data_file, "")?;
+    let env = [("XDG_DATA_HOME", data_file.as_str())];
+    let args = [
+        "--chdir",
+        &root,
+        "prune",
+        "--no-prompt",
+        "--trash",
+        "unknown/two",
+    ];
+    let output = garden_capture_env(&args, &env);
+    assert!(!output.contains("Trashed"));
+    fixture.worktree("unknown/two");
+
+    Ok(())
+}
+
+/// "garden bundle create" writes bundles that "garden grow --from-bundles" grows trees from.
+#[test]
+#[named]
+fn bundle_create_and_grow_from_bundles() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    let garden_yaml = r#"
+trees:
+  example:
+    url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+    branch: default
+    branches:
+      default: origin/default
+      dev: origin/dev
+    remotes:
+      upstream: file://${GARDEN_CONFIG_DIR}/repos/upstream.git
+  detached: file://${GARDEN_CONFIG_DIR}/repos/example.git
+  extra:
+    url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+    gitconfig:
+      garden.grown: true
+grafts:
+  graft:
+    config: graft.yaml
+    root: grafted
+gardens:
+  bundled:
+    trees: [example, graft::example]
+"#;
+    let graft_yaml = r#"
+trees:
+  example:
+    url: file://${GARDEN_CONFIG_DIR}/repos/example.git
+    branch: dev
+"#;
+    std::fs::write(format!("{root}/garden.yaml"), garden_yaml)?;
+    std::fs::write(format!("{root}/graft.yaml"), graft_yaml)?;
+    exec_garden(&["--chdir", &root, "grow", "bundled", "detached"])?;
+
+    // Trees on a detached HEAD without a configured branch cannot be bundled.
+    let detached = fixture.worktree("detached");
+    assert_cmd(&["git", "checkout", "--quiet", "--detach"], &detached);
+    let args = ["--chdir", &root, "bundle", "create", "detached", "bundles"];
+    assert_eq!(exec_garden_code(&args), errors::EX_ERROR);
+
+    // Grafted trees are recorded using their "graft::tree" names.
+    exec_garden(&["--chdir", &root, "bundle", "create", "bundled", "bundles"])?;
+    fixture.path("bundles/example.bundle");
+    fixture.path("bundles/graft__example.bundle");
+    let index = std::fs::read_to_string(fixture.path("bundles/index.yaml"))?;
+    let docs = yaml_rust::YamlLoader::load_from_str(&index)?;
+    let trees = &docs[0]["trees"];
+    assert_eq!(
+        trees["example"]["branches"]["dev"]["upstream"].as_str(),
+        Some("origin/dev")
+    );
+    assert!(trees["graft::example"]["branches"]["default"].is_badvalue());
+
+    // Incremental bundles only contain the branches with new commits.
+    let example = fixture.worktree("example");
+    assert_cmd(
+        &[
+            "git",
+            "-c",
+            "user.name=Garden",
+            "-c",
+            "user.email=garden-tools@crates.io",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "-m",
+            "incremental",
+        ],
+        &example,
+    );
+    let commit = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &example);
+    let args = [
+        "--chdir",
+        &root,
+        "bundle",
+        "create",
+        "--base",
+        "bundles",
+        "example",
+        "increment",
+    ];
+    exec_garden(&args)?;
+    let heads = assert_cmd_capture(
+        &["git", "bundle", "list-heads", "../increment/example.bundle"],
+        &example,
+    );
+    assert!(heads.contains("refs/heads/default"));
+    assert!(!heads.contains("refs/heads/dev"));
+
+    // Trees are cloned from bundles without access to their remotes.
+    let offline = format!("{root}/offline");
+    std::fs::create_dir(&offline)?;
+    std::fs::write(format!("{offline}/garden.yaml"), garden_yaml)?;
+    std::fs::write(format!("{offline}/graft.yaml"), graft_yaml)?;
+    let args = [
+        "--chdir",
+        &offline,
+        "grow",
+        "--from-bundles",
+        "../bundles",
+        "bundled",
+    ];
+    exec_garden(&args)?;
+    let worktree = fixture.worktree("offline/example");
+    assert_ref(&worktree, "default");
+    assert_ref(&worktree, "dev");
+    let cmd = ["git", "symbolic-ref", "--short", "HEAD"];
+    let branch = assert_cmd_capture(&cmd, &fixture.worktree("offline/grafted/example"));
+    assert_eq!(branch, "dev");
+    let url = assert_cmd_capture(&["git", "config", "remote.origin.url"], &worktree);
+    assert!(url.ends_with("/offline/repos/example.git"));
+    let url = assert_cmd_capture(&["git", "config", "remote.upstream.url"], &worktree);
+    assert!(url.ends_with("/offline/repos/upstream.git"));
+
+    // Trees that are missing from the bundle index are not cloned. Existing trees
+    // are updated and the remaining trees are grown after an error.
+    assert_cmd(
+        &["git", "clone", "--quiet", "../example", "extra"],
+        &offline,
+    );
+    let args = [
+        "--chdir",
+        &offline,
+        "grow",
+        "--from-bundles",
+        "../bundles",
+        "detached",
+        "extra",
+    ];
+    assert_ne!(exec_garden_code(&args), errors::EX_OK);
+    assert!(!std::path::Path::new(&format!("{offline}/detached")).exists());
+    let cmd = ["git", "config", "garden.grown"];
+    let output = assert_cmd_capture(&cmd, &fixture.worktree("offline/extra"));
+    assert_eq!(output, "true");
+
+    // Existing trees are updated from incremental bundles.
+    let args = [
+        "--chdir",
+        &offline,
+        "grow",
+        "--from-bundles",
+        "../increment",
+        "example",
+    ];
+    exec_garden(&args)?;
+    let cmd = ["git", "rev-parse", "origin/default"];
+    assert_eq!(assert_cmd_capture(&cmd, &worktree), commit);
+
+    Ok(())
+}
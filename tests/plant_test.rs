@@ -412,3 +412,128 @@ fn plant_keep_variables_with_templates() -> Result<()> {
 
     Ok(())
 }
+
+/// `garden plant --recursive` discovers repositories and plants parents before worktrees.
+#[cfg(not(any(
+    target_arch = "aarch64",
+    target_arch = "powerpc64",
+    target_arch = "s390x",
+    target_arch = "x86"
+)))]
+#[test]
+#[named]
+fn plant_recursive() -> Result<()> {
+    let fixture = common::BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    std::fs::write(
+        format!("{root}/garden.yaml"),
+        r#"
+trees:
+  existing:
+    path: src/existing
+groups:
+  discovered: existing
+"#,
+    )?;
+    // Create repositories at different depths below "src".
+    for path in [
+        "src/existing",
+        "src/parent",
+        "src/nested/repo",
+        "src/a/b/deep",
+    ] {
+        let cmd = ["git", "init", "--quiet", path];
+        common::assert_cmd(&cmd, &root);
+    }
+    let cmd = ["git", "clone", "--quiet", "repos/example.git", "src/cloned"];
+    common::assert_cmd(&cmd, &root);
+    // Create a worktree whose name sorts before its parent.
+    let cmd = [
+        "git",
+        "worktree",
+        "add",
+        "--quiet",
+        "--track",
+        "-B",
+        "dev",
+        "../a-child",
+        "origin/dev",
+    ];
+    common::assert_cmd(&cmd, &fixture.path("src/cloned"));
+
+    let args = [
+        "--chdir",
+        &root,
+        "plant",
+        "--recursive",
+        "--max-depth",
+        "1",
+        "--group",
+        "discovered",
+        "src",
+    ];
+    common::exec_garden(&args)?;
+
+    let pathbuf = fixture.pathbuf("garden.yaml");
+    let app_context = garden::model::ApplicationContext::from_path_and_root(
+        &pathbuf,
+        Some(&fixture.root_pathbuf()),
+    )?;
+    let cfg = app_context.get_root_config();
+    let names: Vec<&String> = cfg.trees.keys().collect();
+    assert_eq!(
+        names,
+        [
+            "existing",
+            "src/cloned",
+            "src/nested/repo",
+            "src/parent",
+            "src/a-child"
+        ]
+    );
+    // The worktree was planted after its parent.
+    let child = cfg.get_tree("src/a-child").context("src/a-child")?;
+    assert!(child.is_worktree);
+    assert_eq!(child.worktree.get_expr(), "src/cloned");
+
+    // The discovered trees were added to the existing group.
+    let group = cfg.groups.get("discovered").context("discovered")?;
+    assert_eq!(group.members.len(), 5);
+    assert!(group.members.contains("existing"));
+    assert!(group.members.contains("src/a-child"));
+
+    // The searched directory is planted when it is a repository.
+    let cmd = ["git", "init", "--quiet", "solo"];
+    common::assert_cmd(&cmd, &root);
+    common::exec_garden(&["--chdir", &root, "plant", "--recursive", "solo"])?;
+
+    // Worktrees whose parent is outside of the searched directories are skipped.
+    let cmd = ["git", "clone", "--quiet", "repos/example.git", "host"];
+    common::assert_cmd(&cmd, &root);
+    let cmd = [
+        "git",
+        "worktree",
+        "add",
+        "--quiet",
+        "--track",
+        "-B",
+        "dev",
+        "../worktrees/orphan",
+        "origin/dev",
+    ];
+    common::assert_cmd(&cmd, &fixture.path("host"));
+    let args = ["--chdir", &root, "plant", "--recursive", "worktrees"];
+    let output = common::garden_capture_stderr(&args);
+    assert!(output.contains("skipped because its parent repository"));
+
+    let app_context = garden::model::ApplicationContext::from_path_and_root(
+        &pathbuf,
+        Some(&fixture.root_pathbuf()),
+    )?;
+    let cfg = app_context.get_root_config();
+    assert!(cfg.get_tree("solo").is_some());
+    assert!(cfg.get_tree("host").is_none());
+    assert!(cfg.get_tree("worktrees/orphan").is_none());
+
+    Ok(())
+}
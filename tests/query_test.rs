@@ -202,3 +202,32 @@ fn tree_query() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn resolve_trees_union_exclusion_and_intersection() -> Result<()> {
+    let app_context = common::garden_context()?;
+    let config = app_context.get_root_config();
+    let tree_names = |query: &str| -> Vec<String> {
+        garden::query::resolve_trees(&app_context, config, None, query)
+            .into_iter()
+            .map(|context| context.tree)
+            .collect()
+    };
+
+    // Unions are de-duplicated and retain the order in which trees are first seen.
+    assert_eq!(tree_names("%reverse,%cola"), ["cola", "git", "python/qtpy"]);
+    assert_eq!(tree_names("@git, @tmp, @git"), ["git", "tmp"]);
+    // Exclusions remove trees from the preceding union.
+    assert_eq!(tree_names("%cola,-%reverse"), ["python/qtpy"]);
+    assert_eq!(tree_names(":cola,!@git"), ["cola", "python/qtpy"]);
+    assert_eq!(tree_names("!@git,@git"), ["git"]);
+    // Intersections contain the trees found in all of the queries.
+    assert_eq!(tree_names("%cola&%reverse"), ["git", "cola"]);
+    assert_eq!(tree_names("%cola&!@cola"), ["git", "python/qtpy"]);
+    assert_eq!(
+        tree_names("%annex&@*/local,@oneline"),
+        ["annex/local", "oneline"]
+    );
+
+    Ok(())
+}